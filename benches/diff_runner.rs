@@ -0,0 +1,92 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use dtfterminal::{diff_runner::DiffRunner, dtfterminal_types::ConfigBuilder};
+use serde_json::{json, Map, Value};
+
+/// How many records the generated documents have
+const RECORD_COUNT: usize = 2000;
+
+/// Generates a document of records, each with nested objects and arrays.
+/// The data only depends on the arguments, so every run measures the same work.
+/// Every record with an index divisible by `change_every` gets a changed value, a changed type, an extra key and a reordered array.
+fn generate_document(record_count: usize, change_every: Option<usize>) -> Map<String, Value> {
+    (0..record_count)
+        .map(|index| {
+            let changed = change_every.is_some_and(|every| index % every == 0);
+            let mut record = json!({
+                "id": index,
+                "name": format!("Product {}", index),
+                "price": if changed { json!(format!("{}", index * 3)) } else { json!(index * 3) },
+                "details": {
+                    "color": if changed { "red" } else { "blue" },
+                    "size": index % 7,
+                    "dimensions": { "width": index % 13, "height": index % 17 }
+                },
+                "tags": if changed {
+                    json!(["office", "sale", format!("tag{}", index % 5)])
+                } else {
+                    json!(["sale", "office", format!("tag{}", index % 5)])
+                }
+            });
+            if changed {
+                record["discount"] = json!(index % 10);
+            }
+            (format!("record{}", index), record)
+        })
+        .collect()
+}
+
+/// Checks two documents with every kind of difference turned on, the same way the command line does
+fn run_check(data_a: &Map<String, Value>, data_b: &Map<String, Value>, array_same_order: bool) {
+    let config = ConfigBuilder::new()
+        .check_for_key_diffs(true)
+        .check_for_type_diffs(true)
+        .check_for_value_diffs(true)
+        .check_for_array_diffs(true)
+        .array_same_order(array_same_order)
+        .build();
+    let result = DiffRunner::from_data("a.json", data_a.clone(), "b.json", data_b.clone())
+        .with_config(config)
+        .run();
+    black_box(result.expect("Data check failed!"));
+}
+
+/// Measures a check of the small fixture files, including reading and parsing them
+fn benchmark_collect_data_no_array_same_order(c: &mut Criterion) {
+    c.bench_function("collect_data_no_array_same_order", |b| {
+        b.iter(|| {
+            let result = DiffRunner::from_files(
+                black_box("test_data/json/person3.json"),
+                black_box("test_data/json/person4.json"),
+            )
+            .run();
+            black_box(result.expect("Data check failed!"))
+        })
+    });
+}
+
+/// Measures the checks of a large generated document, where every tenth record has differences of every kind.
+/// Reading files is left out, so the cost of finding the differences dominates.
+fn benchmark_collect_data_large_document(c: &mut Criterion) {
+    let data_a = generate_document(RECORD_COUNT, None);
+    let data_b = generate_document(RECORD_COUNT, Some(10));
+    c.bench_function("collect_data_large_document", |b| {
+        b.iter(|| run_check(black_box(&data_a), black_box(&data_b), false))
+    });
+}
+
+/// Measures the same large document with `--array-same-order`, where arrays are compared item by item, as Value differences
+fn benchmark_collect_data_array_same_order(c: &mut Criterion) {
+    let data_a = generate_document(RECORD_COUNT, None);
+    let data_b = generate_document(RECORD_COUNT, Some(10));
+    c.bench_function("collect_data_array_same_order", |b| {
+        b.iter(|| run_check(black_box(&data_a), black_box(&data_b), true))
+    });
+}
+
+criterion_group!(
+    benches,
+    benchmark_collect_data_no_array_same_order,
+    benchmark_collect_data_large_document,
+    benchmark_collect_data_array_same_order
+);
+criterion_main!(benches);
@@ -1,19 +1,37 @@
 use std::path;
-use std::{error::Error, fs::File, io::Write};
+use std::{fs::File, io::Write};
 
 use colored::Colorize;
 use html_builder::Buffer;
 
 use crate::html_renderer::HtmlRenderer;
-use crate::utils::{create_working_context, is_yaml_file, CHECKMARK};
+use crate::json_patch_renderer::render_json_patch;
+use crate::junit_renderer::render_junit;
+use crate::move_table::MoveTable;
+use crate::plain_renderer::{
+    render_by_direction, render_grouped, render_moves_plain, render_plain,
+};
+use crate::postprocessor::process_diffs;
+use crate::preprocessor::{check_supported_yaml_values, select_subtree};
+use crate::side_by_side_renderer::render_side_by_side;
+#[cfg(feature = "tui")]
+use crate::tui_app::TuiApp;
+use crate::utils::{
+    create_working_context, find_equal_values, find_type_diff_values, find_unchanged_siblings,
+    find_value_types, format_counts, get_data_format, get_git_revision_name, get_output_path,
+    get_self_compare_name, get_split_file_name, has_diffs_of_kinds, is_json_file, is_url,
+    is_yaml_file, print_diff_counts, print_summary, run_with_timeout, time_phase, CHECKMARK,
+};
 use crate::{
     array_table::ArrayTable,
     dtfterminal_types::{
-        Config, ConfigBuilder, DiffCollection, DtfError, ParsedArgs, TermTable, WorkingContext,
+        merge_diffs, Config, ConfigBuilder, DataFormat, DiffCollection, DiffKind, DtfError,
+        MovedElement, ParsedArgs, TermTable, Theme, ValueTypes, WorkingContext,
     },
-    file_handler::FileHandler,
+    file_handler::{check_write_target, read_with_retry, FileHandler},
     json_app::JsonApp,
     key_table::KeyTable,
+    profile::{get_profiles_path, load_profile, save_profile, Profile},
     type_table::TypeTable,
     value_table::ValueTable,
     yaml_app::YamlApp,
@@ -21,6 +39,7 @@ use crate::{
 };
 
 use ::clap::Parser;
+use libdtf::core::diff_types::{TypeDiff, ValueDiff};
 use spinners::Spinner;
 
 /// Responsible for the main functionality of the app. Makes sure everything runs in the correct order.
@@ -30,35 +49,56 @@ pub struct App {
     file_handler: FileHandler,
     json_app: Option<JsonApp>,
     yaml_app: Option<YamlApp>,
+    unchanged_siblings: Vec<Vec<(String, String)>>,
+    /// The keys with the same value in both files, shown in the Value table with `--include-equal`
+    equal_values: Vec<(String, String)>,
+    /// The array items moved to another position, shown with `--report-moves`
+    moved_elements: Vec<MovedElement>,
+    /// The types of the values of the Value differences, shown next to the values
+    value_types: ValueTypes,
+    /// The Type differences with the values of both files, shown as rows of the Value table with `--combine-type-value`
+    type_values: Vec<ValueDiff>,
 }
 
 impl App {
     /// Creates a new App instance
-    /// 1. Parses the command line arguments
+    /// 1. Reads the files or the saved results
     /// 2. Checks for differences and stores them
-    pub fn new() -> App {
-        let (path1, path2, config) = App::parse_args();
+    pub fn new(path1: Option<String>, path2: Option<String>, config: Config) -> App {
         let mut file_handler = FileHandler::new(config.clone(), None);
         let (diffs, context) = if config.read_from_file.is_empty() {
-            ((None, None, None, None), create_working_context(&config))
+            (
+                (None, None, None, None),
+                create_working_context(&config).expect("Could not start the check!"),
+            )
         } else {
             file_handler
                 .load_saved_results()
                 .expect("Could not load saved file!")
         };
 
-        let json_app = match (&path1, &path2) {
-            (Some(p1), Some(p2)) if p1.ends_with(".json") && p2.ends_with(".json") => {
-                Some(JsonApp::new(p1.clone(), p2.clone(), context.clone()))
+        let (json_app, yaml_app) = match (&path1, &path2) {
+            (Some(p1), Some(p2)) => App::create_file_apps(p1, p2, &config, &context),
+            _ => (None, None),
+        };
+
+        let (json_app, yaml_app) = match &config.split_file {
+            Some(split_file) => App::create_split_file_apps(split_file, &context),
+            None => (json_app, yaml_app),
+        };
+
+        let json_app = match (&config.self_compare_file, &config.self_compare_pointers) {
+            (Some(file), Some(pointers)) => {
+                Some(App::create_self_compare_app(file, pointers, &context))
             }
-            _ => None,
+            _ => json_app,
         };
 
-        let yaml_app = match (&path1, &path2) {
-            (Some(p1), Some(p2)) if is_yaml_file(p1) && is_yaml_file(p2) => {
-                Some(YamlApp::new(p1.clone(), p2.clone(), context.clone()))
+        let json_app = match (&config.git_file, &config.git_revisions) {
+            (Some(file), Some(revisions)) => {
+                Some(App::create_git_app(file, revisions, &config, &context))
             }
-            _ => None,
+            _ => json_app,
         };
 
         if App::are_diffs_empty(&diffs) && json_app.is_none() && yaml_app.is_none() {
@@ -71,46 +111,282 @@ impl App {
             file_handler,
             json_app,
             yaml_app,
+            unchanged_siblings: vec![],
+            equal_values: vec![],
+            moved_elements: vec![],
+            value_types: ValueTypes::new(),
+            type_values: vec![],
         };
 
-        app.collect_data(&config);
+        app.collect_data(&config).expect("Data check failed!");
 
         app
     }
 
+    /// Creates an App checking the data already read by the JSON app, the same way as the files on the command line.
+    /// Returns the error of the check, instead of panicking.
+    pub(crate) fn from_json_app(
+        json_app: JsonApp,
+        context: WorkingContext,
+        config: &Config,
+    ) -> Result<App, DtfError> {
+        let mut app = App {
+            diffs: (None, None, None, None),
+            file_handler: FileHandler::new(config.clone(), None),
+            context,
+            json_app: Some(json_app),
+            yaml_app: None,
+            unchanged_siblings: vec![],
+            equal_values: vec![],
+            moved_elements: vec![],
+            value_types: ValueTypes::new(),
+            type_values: vec![],
+        };
+        app.collect_data(config)?;
+        Ok(app)
+    }
+
+    /// Returns the differences found, processed according to the config
+    pub(crate) fn diffs(&self) -> &DiffCollection {
+        &self.diffs
+    }
+
+    /// Creates a new App instance like `new`, within the time limit of `--timeout`.
+    /// Reading and preparing the files counts as well, as preparing large arrays can take long.
+    /// Returns the error of the time running out, instead of waiting for the check.
+    pub fn with_timeout(
+        path1: Option<String>,
+        path2: Option<String>,
+        config: Config,
+    ) -> Result<App, DtfError> {
+        run_with_timeout(config.timeout, move || App::new(path1, path2, config))
+    }
+
+    /// Creates the app checking the two files against each other, based on their formats.
+    /// The formats are taken from the extensions of the files, unless they are given with `--format-a` and `--format-b`.
+    /// Files of different formats are both converted to JSON data.
+    fn create_file_apps(
+        path1: &str,
+        path2: &str,
+        config: &Config,
+        context: &WorkingContext,
+    ) -> (Option<JsonApp>, Option<YamlApp>) {
+        let format1 = config.format_a.unwrap_or_else(|| get_data_format(path1));
+        let format2 = config.format_b.unwrap_or_else(|| get_data_format(path2));
+        match (format1, format2) {
+            (DataFormat::Json, DataFormat::Json) => {
+                if config.warn_duplicate_keys {
+                    App::warn_duplicate_keys(path1);
+                    App::warn_duplicate_keys(path2);
+                }
+                let app = JsonApp::new(path1.to_owned(), path2.to_owned(), context.clone());
+                (Some(app), None)
+            }
+            (DataFormat::Yaml, DataFormat::Yaml) => {
+                let app = YamlApp::new(path1.to_owned(), path2.to_owned(), context.clone());
+                (None, Some(app))
+            }
+            _ => {
+                if config.strict {
+                    App::check_supported_yaml_values(path1, format1, config);
+                    App::check_supported_yaml_values(path2, format2, config);
+                }
+                let array_key = config.array_key.as_deref();
+                let data1 = read_with_retry(config.read_retry, || {
+                    FileHandler::read_data_as_json(path1, format1, array_key)
+                })
+                .expect("Could not read file!");
+                let data2 = read_with_retry(config.read_retry, || {
+                    FileHandler::read_data_as_json(path2, format2, array_key)
+                })
+                .expect("Could not read file!");
+                (
+                    Some(JsonApp::from_data(data1, data2, context.clone())),
+                    None,
+                )
+            }
+        }
+    }
+
+    /// Creates the app checking the two objects of a split file against each other, based on the file type
+    fn create_split_file_apps(
+        split_file: &str,
+        context: &WorkingContext,
+    ) -> (Option<JsonApp>, Option<YamlApp>) {
+        if is_json_file(split_file) {
+            let (data1, data2) =
+                FileHandler::read_json_split_file(split_file).expect("Could not read split file!");
+            (
+                Some(JsonApp::from_data(data1, data2, context.clone())),
+                None,
+            )
+        } else if is_yaml_file(split_file) {
+            let (data1, data2) =
+                FileHandler::read_yaml_split_file(split_file).expect("Could not read split file!");
+            (
+                None,
+                Some(YamlApp::from_data(data1, data2, context.clone())),
+            )
+        } else {
+            (None, None)
+        }
+    }
+
+    /// Creates the app checking the parts of a JSON file the two pointers point to against each other
+    fn create_self_compare_app(
+        file: &str,
+        (pointer_a, pointer_b): &(String, String),
+        context: &WorkingContext,
+    ) -> JsonApp {
+        let mut data1 = FileHandler::read_json_data(file).expect("Could not read JSON file");
+        let mut data2 = data1.clone();
+        select_subtree(&mut data1, pointer_a, file).expect("Could not select the part to check!");
+        select_subtree(&mut data2, pointer_b, file).expect("Could not select the part to check!");
+        JsonApp::from_data(data1, data2, context.clone())
+    }
+
+    /// Creates the app checking a file at two revisions of its git repository against each other
+    fn create_git_app(
+        file: &str,
+        (revision_a, revision_b): &(String, String),
+        config: &Config,
+        context: &WorkingContext,
+    ) -> JsonApp {
+        let array_key = config.array_key.as_deref();
+        let data1 = FileHandler::read_git_revision(revision_a, file, array_key)
+            .expect("Could not read the file from git!");
+        let data2 = FileHandler::read_git_revision(revision_b, file, array_key)
+            .expect("Could not read the file from git!");
+        JsonApp::from_data(data1, data2, context.clone())
+    }
+
     /// Handles the output into file or to the terminal
     pub fn execute(&self) -> Result<(), DtfError> {
-        let mut spinner = Spinner::new(
-            spinners::Spinners::Monkey,
-            "Checking for differences...\n".into(),
-        );
+        if self.context.config.interactive {
+            return self.browse();
+        }
 
-        if self.context.config.write_to_file.is_some() {
-            self.file_handler
-                .write_to_file(self.diffs.clone())
-                .map_err(|e| DtfError::GeneralError(Box::new(e)))?;
-        } else if let Some(browser_view) = &self.context.config.browser_view {
+        if self.context.config.count_only {
+            print!("{}", format_counts(&self.diffs));
+            return Ok(());
+        }
+
+        let mut spinner = if self.context.config.quiet {
+            None
+        } else {
+            Some(Spinner::new(
+                spinners::Spinners::Monkey,
+                "Checking for differences...\n".into(),
+            ))
+        };
+
+        time_phase(&self.context.config, "Rendering", || self.write_output())?;
+
+        if let Some(spinner) = spinner.as_mut() {
+            spinner.stop_with_message(format!("{} {}", CHECKMARK.green(), "Done!".green()));
+        }
+
+        print_summary(&self.context.config, &self.diffs);
+        Ok(())
+    }
+
+    /// Opens the interactive view of the differences
+    #[cfg(feature = "tui")]
+    fn browse(&self) -> Result<(), DtfError> {
+        TuiApp::new(&self.diffs, &self.context).run()
+    }
+
+    /// Opens the interactive view of the differences
+    #[cfg(not(feature = "tui"))]
+    fn browse(&self) -> Result<(), DtfError> {
+        Err(DtfError::DiffError(
+            "Interactive mode needs dtfterminal to be built with the tui feature".to_string(),
+        ))
+    }
+
+    /// Writes the differences into a JSON file, a YAML file, an HTML page, a JUnit report and a JSON Patch, if the user asked for them.
+    /// The differences are rendered to the terminal if neither a saved file nor the HTML page was asked for, and they were not streamed already.
+    fn write_output(&self) -> Result<(), DtfError> {
+        let config = &self.context.config;
+
+        if let Some(output_dir) = &config.output_dir {
+            std::fs::create_dir_all(output_dir).map_err(DtfError::IoError)?;
+        }
+
+        if config.write_to_file.is_some() {
+            self.file_handler.write_to_file(self.diffs.clone())?;
+        }
+
+        if let Some(browser_view) = &config.browser_view {
             self.render_html()
                 .map_err(|e| DtfError::DiffError(e.to_string()))?;
 
-            if !self.context.config.no_browser_show {
+            if !config.no_browser_show {
                 opener::open(path::Path::new(browser_view))
                     .map_err(|e| DtfError::DiffError(e.to_string()))?;
             }
-        } else {
+        }
+
+        if let Some(yaml) = &config.yaml {
+            self.file_handler
+                .write_yaml_file(self.diffs.clone(), yaml)?;
+        }
+
+        if let Some(junit) = &config.junit {
+            check_write_target(path::Path::new(junit), config.force)?;
+            std::fs::write(junit, render_junit(&self.diffs, &self.context))
+                .map_err(DtfError::IoError)?;
+        }
+
+        if let Some(json_patch) = &config.json_patch {
+            self.write_json_patch(json_patch)?;
+        }
+
+        if config.write_to_file.is_none()
+            && config.yaml.is_none()
+            && config.browser_view.is_none()
+            && !config.stream
+        {
             self.render_tables()
                 .map_err(|e| DtfError::DiffError(e.to_string()))?;
         }
-
-        spinner.stop_with_message(format!("{} {}", CHECKMARK.green(), "Done!".green()));
         Ok(())
     }
 
+    /// Writes the JSON Patch turning the data of the first file into the data of the second one
+    fn write_json_patch(&self, path: &str) -> Result<(), DtfError> {
+        let (data1, data2) = self.checked_data().ok_or(DtfError::DiffError(
+            "A JSON Patch can only be created from the checked files".to_owned(),
+        ))?;
+        let patch = render_json_patch(
+            &self.diffs,
+            (&data1, &data2),
+            self.context.config.json_pointer,
+        )?;
+        let content =
+            serde_json::to_string_pretty(&patch).map_err(|e| DtfError::DiffError(e.to_string()))?;
+        check_write_target(path::Path::new(path), self.context.config.force)?;
+        std::fs::write(path, content).map_err(DtfError::IoError)
+    }
+
+    /// Checks if there are differences of the kinds the user wants the run to fail on
+    pub fn has_failing_diffs(&self) -> bool {
+        has_diffs_of_kinds(&self.context.config.fail_on, &self.diffs)
+    }
+
+    /// Checks if any differences were found
+    pub fn has_diffs(&self) -> bool {
+        has_diffs_of_kinds(&[DiffKind::All], &self.diffs)
+    }
+
     /// Parses the command line arguments
-    fn parse_args() -> ParsedArgs {
-        let args = Arguments::parse();
+    pub fn parse_args() -> ParsedArgs {
+        App::from_arguments(Arguments::parse())
+    }
 
-        let (path1, path2) = if args.read_from_file.is_empty() {
+    /// Turns the parsed command line arguments into the paths of the files and the config
+    pub(crate) fn from_arguments(args: Arguments) -> ParsedArgs {
+        let (path1, path2) = if args.read_from_file.is_empty() && !args.check_files.is_empty() {
             (
                 Some(args.check_files[0].clone()),
                 Some(args.check_files[1].clone()),
@@ -119,90 +395,378 @@ impl App {
             (None, None)
         };
 
+        let (self_compare_file, self_compare_pointers) = match args.self_compare.as_slice() {
+            [file, pointer_a, pointer_b] => (
+                Some(file.clone()),
+                Some((pointer_a.clone(), pointer_b.clone())),
+            ),
+            _ => (None, None),
+        };
+
+        let (file_a, file_b) = match (&args.split_file, &self_compare_file, &self_compare_pointers)
+        {
+            (Some(split_file), _, _) => (
+                Some(get_split_file_name(split_file, 0)),
+                Some(get_split_file_name(split_file, 1)),
+            ),
+            (None, Some(file), Some((pointer_a, pointer_b))) => (
+                Some(get_self_compare_name(file, pointer_a)),
+                Some(get_self_compare_name(file, pointer_b)),
+            ),
+            _ => (path1.clone(), path2.clone()),
+        };
+
+        let (git_file, git_revisions) = match args.git.as_slice() {
+            [revision_a, revision_b, file] => (
+                Some(file.clone()),
+                Some((revision_a.clone(), revision_b.clone())),
+            ),
+            _ => (None, None),
+        };
+        let (file_a, file_b) = match (&git_file, &git_revisions) {
+            (Some(file), Some((revision_a, revision_b))) => (
+                Some(get_git_revision_name(file, revision_a)),
+                Some(get_git_revision_name(file, revision_b)),
+            ),
+            _ => (file_a, file_b),
+        };
+
+        let (profile, profile_to_save) = (args.profile.clone(), args.save_profile.clone());
+        let (theme_file, ignore_file) = (args.theme.clone(), args.ignore_file.clone());
+
+        let key_diffs = args.key_diffs || args.structure_only;
+        let type_diffs = args.type_diffs || args.structure_only;
         let config = ConfigBuilder::new()
-            .check_for_key_diffs(args.key_diffs)
-            .check_for_type_diffs(args.type_diffs)
+            .check_for_key_diffs(key_diffs)
+            .check_for_type_diffs(type_diffs)
             .check_for_value_diffs(args.value_diffs)
             .check_for_array_diffs(args.array_diffs)
-            .render_key_diffs(args.key_diffs)
-            .render_type_diffs(args.type_diffs)
+            .render_key_diffs(key_diffs)
+            .render_type_diffs(type_diffs)
             .render_value_diffs(args.value_diffs)
             .render_array_diffs(args.array_diffs)
             .read_from_file(args.read_from_file)
-            .write_to_file(args.write_to_file)
-            .file_a(path1.clone())
-            .file_b(path2.clone())
+            .write_to_file(
+                args.write_to_file
+                    .map(|path| get_output_path(args.output_dir.as_deref(), &path, "diff.json")),
+            )
+            .file_a(file_a)
+            .file_b(file_b)
             .array_same_order(args.array_same_order)
-            .browser_view(args.browser_view)
+            .browser_view(
+                args.browser_view
+                    .map(|path| get_output_path(args.output_dir.as_deref(), &path, "diff.html")),
+            )
             .printer_friendly(args.printer_friendly)
             .no_browser_show(args.no_browser_show)
+            .null_is_missing(args.null_is_missing)
+            .dir_a(args.check_dirs.first().cloned())
+            .dir_b(args.check_dirs.get(1).cloned())
+            .quiet(args.quiet)
+            .ignore_patterns(args.ignore)
+            .ignore_globs(args.ignore_glob)
+            .only_patterns(args.only)
+            .warn_duplicate_keys(args.warn_duplicate_keys)
+            .intersection_only(args.intersection_only)
+            .compact_values(args.compact_values)
+            .fail_on(args.fail_on)
+            .check_args(args.check_args)
+            .no_color(args.no_color)
+            .numbers_as_text(args.numbers_as_text)
+            .array_key(args.array_key)
+            .json_pointer(args.json_pointer)
+            .split_file(args.split_file)
+            .self_compare_file(self_compare_file)
+            .self_compare_pointers(self_compare_pointers)
+            .verbose(args.verbose)
+            .context_size(args.context_size)
+            .interactive(args.interactive)
+            .junit(
+                args.junit
+                    .map(|path| get_output_path(args.output_dir.as_deref(), &path, "diff.xml")),
+            )
+            .baseline(args.baseline)
+            .theme(Theme::default())
+            .no_dedup(args.no_dedup)
+            .print_summary(args.print_summary)
+            .subtree_pointer(args.subtree_pointer)
+            .plain(args.plain)
+            .limit(args.limit)
+            .swap(args.swap)
+            .group_by_key(args.group_by_key)
+            .explain(args.explain)
+            .watch(args.watch)
+            .max_value_len(args.max_value_len)
+            .has_symbol(args.has_symbol)
+            .misses_symbol(args.misses_symbol)
+            .json_patch(
+                args.json_patch.map(|path| {
+                    get_output_path(args.output_dir.as_deref(), &path, "diff.patch.json")
+                }),
+            )
+            .normalize_keys(args.normalize_keys)
+            .stream(args.stream)
+            .combine_type_value(args.combine_type_value)
+            .round(args.round)
+            .save_format(args.save_format)
+            .include_equal(args.include_equal)
+            .max_width(args.max_width)
+            .require_non_empty(args.require_non_empty)
+            .ignore_types(args.ignore_types)
+            .format_a(args.format_a)
+            .format_b(args.format_b)
+            .by_direction(args.by_direction)
+            .abbreviate_keys(args.abbreviate_keys)
+            .git_file(git_file)
+            .git_revisions(git_revisions)
+            .force(args.force)
+            .show_delta(args.show_delta)
+            .pretty_save(args.pretty_save)
+            .thousands_sep(args.thousands_sep)
+            .expand_env(args.expand_env)
+            .strict(args.strict)
+            .smart_dates(args.smart_dates)
+            .min_severity(args.min_severity)
+            .severity_overrides(args.severity)
+            .count_only(args.count_only)
+            .stacked(args.stacked)
+            .max_array_diffs(args.max_array_diffs)
+            .empty_equals_null(args.empty_equals_null)
+            .yaml(
+                args.yaml
+                    .map(|path| get_output_path(args.output_dir.as_deref(), &path, "diff.yaml")),
+            )
+            .output_dir(args.output_dir)
+            .side_by_side(args.side_by_side)
+            .min_array_size(args.min_array_size)
+            .read_retry(args.read_retry)
+            .only_changed_files(args.only_changed_files)
+            .key_separator(args.key_separator)
+            .timeout(args.timeout)
+            .report_moves(args.report_moves)
             .build();
 
+        // Checking the arguments doesn't touch any file, so the ones the arguments name are read only when running
+        if config.check_args {
+            return (path1, path2, config);
+        }
+
+        let mut config = config;
+        if let Some(theme_file) = &theme_file {
+            config.theme =
+                FileHandler::read_theme_file(theme_file).expect("Could not read theme file!");
+        }
+        if let Some(ignore_file) = &ignore_file {
+            config.ignore_patterns.extend(
+                FileHandler::read_ignore_file(ignore_file).expect("Could not read ignore file!"),
+            );
+        }
+
+        let config = match &profile {
+            Some(name) => {
+                let profiles_path = get_profiles_path().expect("Could not find profiles!");
+                load_profile(&profiles_path, name)
+                    .expect("Could not load profile!")
+                    .apply(config)
+            }
+            None => config,
+        };
+
+        if let Some(name) = &profile_to_save {
+            let profiles_path = get_profiles_path().expect("Could not find profiles!");
+            save_profile(&profiles_path, name, Profile::from_config(&config))
+                .expect("Could not save profile!");
+        }
+
         (path1, path2, config)
     }
 
     /// Collects the data from the files
     /// If the user has specified a file to read from, it will load the saved results
     /// Otherwise it will perform a new check
-    fn collect_data(&mut self, user_config: &Config) {
-        if user_config.read_from_file.is_empty() {
-            self.diffs = self.check_for_diffs().expect("Data check failed!")
+    fn collect_data(&mut self, user_config: &Config) -> Result<(), DtfError> {
+        self.diffs = if user_config.stream {
+            self.stream_diffs(user_config)?
+        } else if user_config.read_from_file.is_empty() {
+            let diffs = self.check_for_diffs()?;
+            if user_config.render_value_diffs {
+                self.collect_value_types(&diffs, user_config)?;
+            }
+            process_diffs(diffs, user_config)?
         } else {
-            self.diffs = self
-                .file_handler
-                .load_saved_results()
-                .expect("Could not load saved file!")
-                .0;
+            process_diffs(self.file_handler.load_saved_results()?.0, user_config)?
+        };
+
+        let type_diffs = self.diffs.1.as_deref().unwrap_or_default();
+        self.type_values
+            .retain(|row| type_diffs.iter().any(|td| td.key == row.key));
+
+        self.moved_elements = self.find_moved_elements(user_config);
+        if user_config.swap {
+            // The differences got swapped, the file names and the saved file have to follow
+            self.context.swap_files();
+            self.file_handler = FileHandler::new(self.context.config.clone(), None);
         }
+        print_diff_counts(user_config, &self.diffs);
+
+        if user_config.context_size > 0 {
+            self.unchanged_siblings = self.find_unchanged_siblings(user_config);
+        }
+
+        if user_config.include_equal {
+            self.equal_values = self
+                .checked_data()
+                .map(|(data1, data2)| find_equal_values(&data1, &data2, user_config.json_pointer))
+                .unwrap_or_default();
+        }
+        Ok(())
     }
 
-    /// Checks for differences in the files
-    /// Handles both JSON and YAML files
-    /// Returns an error if no file is found
-    fn check_for_diffs(&self) -> Result<DiffCollection, Box<dyn Error>> {
-        if let Some(json_app) = &self.json_app {
-            Ok(json_app.perform_new_check())
+    /// Finds the types of the values of the Value differences found by the check, in the order of the files the user asked for.
+    /// With `--combine-type-value` the values of the Type differences are found too, to show them in the Value table.
+    /// The types are taken from the data before `--numbers-as-text` or `--empty-equals-null` changed them.
+    fn collect_value_types(
+        &mut self,
+        diffs: &DiffCollection,
+        user_config: &Config,
+    ) -> Result<(), DtfError> {
+        let (data1, data2) = if let Some(json_app) = &self.json_app {
+            json_app.typed_data_as_json()
         } else if let Some(yaml_app) = &self.yaml_app {
-            Ok(yaml_app.perform_new_check())
+            yaml_app.typed_data_as_json()
         } else {
-            Err(Box::new(DtfError::DiffError(
-                "No file to check".to_string(),
-            )))
+            return Ok(());
+        };
+
+        let value_diffs = diffs.2.as_deref().unwrap_or_default();
+        self.value_types = find_value_types(&data1, &data2, value_diffs, user_config);
+        if user_config.combine_type_value {
+            let type_diffs = diffs.1.as_deref().unwrap_or_default();
+            let type_values = find_type_diff_values(&data1, &data2, type_diffs);
+            self.value_types
+                .extend(find_value_types(&data1, &data2, &type_values, user_config));
+            self.type_values = process_diffs((None, None, Some(type_values), None), user_config)?
+                .2
+                .unwrap_or_default();
         }
+
+        if user_config.swap {
+            for types in self.value_types.values_mut() {
+                *types = (types.1, types.0);
+            }
+        }
+        Ok(())
     }
 
-    /// Renders the tables to the terminal
-    fn render_tables(&self) -> Result<(), DtfError> {
-        let (key_diff, type_diff, value_diff, array_diff) = &self.diffs;
+    /// Returns the array items moved to another position, in the order of the files the user asked for
+    fn find_moved_elements(&self, user_config: &Config) -> Vec<MovedElement> {
+        let mut moved_elements = match (&self.json_app, &self.yaml_app) {
+            (Some(json_app), _) => json_app.moved_elements().to_vec(),
+            (_, Some(yaml_app)) => yaml_app.moved_elements().to_vec(),
+            _ => vec![],
+        };
+        if user_config.swap {
+            moved_elements.iter_mut().for_each(MovedElement::swap);
+        }
+        moved_elements
+    }
 
-        let mut rendered_tables = vec![];
-        if self.context.config.render_key_diffs {
-            if let Some(diffs) = key_diff.as_ref().filter(|kd| !kd.is_empty()) {
-                let table = KeyTable::new(diffs, &self.context);
-                rendered_tables.push(table.render());
-            }
+    /// Finds the unchanged keys to show next to the Value differences
+    fn find_unchanged_siblings(&self, user_config: &Config) -> Vec<Vec<(String, String)>> {
+        let (data1, data2) = match self.checked_data() {
+            Some(data) => data,
+            None => return vec![],
+        };
+
+        find_unchanged_siblings(
+            &data1,
+            &data2,
+            self.diffs.2.as_deref().unwrap_or_default(),
+            user_config.context_size,
+            user_config.json_pointer,
+        )
+    }
+
+    /// Returns the data of both files as JSON, in the order the differences are reported in.
+    /// There is no data if the differences were read from a saved file
+    fn checked_data(&self) -> Option<(serde_json::Value, serde_json::Value)> {
+        let (data1, data2) = if let Some(json_app) = &self.json_app {
+            json_app.data_as_json()
+        } else if let Some(yaml_app) = &self.yaml_app {
+            yaml_app.data_as_json()
+        } else {
+            return None;
+        };
+
+        if self.context.config.swap {
+            Some((data2, data1))
+        } else {
+            Some((data1, data2))
         }
+    }
 
-        if self.context.config.render_type_diffs {
-            if let Some(diffs) = type_diff.as_ref().filter(|td| !td.is_empty()) {
-                let table = TypeTable::new(diffs, &self.context);
-                rendered_tables.push(table.render());
-            }
+    /// Checks for differences in the files, printing the differences of every kind as plain text lines as soon as the kind is checked.
+    /// Returns the processed differences of every kind, for the rest of the output.
+    fn stream_diffs(&self, user_config: &Config) -> Result<DiffCollection, DtfError> {
+        let mut render_context = self.context.clone();
+        if user_config.swap {
+            render_context.swap_files();
         }
 
-        if self.context.config.render_value_diffs {
-            if let Some(diffs) = value_diff.as_ref().filter(|vd| !vd.is_empty()) {
-                let table = ValueTable::new(diffs, &self.context);
-                rendered_tables.push(table.render());
+        let mut diffs = (None, None, None, None);
+        let mut result = Ok(());
+        let mut any_printed = false;
+        let on_checked = |checked: DiffCollection| {
+            if result.is_err() {
+                return;
             }
+            match process_diffs(checked, user_config) {
+                Ok(processed) => {
+                    for section in render_plain(&processed, &render_context) {
+                        println!("{}", section);
+                        any_printed = true;
+                    }
+                    merge_diffs(&mut diffs, processed);
+                }
+                Err(e) => result = Err(e),
+            }
+        };
+
+        if let Some(json_app) = &self.json_app {
+            json_app.perform_streaming_check(on_checked);
+        } else if let Some(yaml_app) = &self.yaml_app {
+            yaml_app.perform_streaming_check(on_checked);
+        } else {
+            return Err(DtfError::DiffError("No file to check".to_string()));
         }
+        result?;
 
-        if self.context.config.render_array_diffs {
-            if let Some(diffs) = array_diff.as_ref().filter(|ad| !ad.is_empty()) {
-                let table = ArrayTable::new(diffs, &self.context);
-                rendered_tables.push(table.render());
-            }
+        if !any_printed {
+            println!("The data is identical!");
         }
+        Ok(diffs)
+    }
+
+    /// Checks for differences in the files
+    /// Handles both JSON and YAML files
+    /// Returns an error if no file is found
+    fn check_for_diffs(&self) -> Result<DiffCollection, DtfError> {
+        if let Some(json_app) = &self.json_app {
+            Ok(json_app.perform_new_check())
+        } else if let Some(yaml_app) = &self.yaml_app {
+            Ok(yaml_app.perform_new_check())
+        } else {
+            Err(DtfError::DiffError("No file to check".to_string()))
+        }
+    }
+
+    /// Renders the tables, or the side by side view if the user asked for it, to the terminal
+    pub fn render_tables(&self) -> Result<(), DtfError> {
+        if self.context.config.side_by_side {
+            return self.render_side_by_side();
+        }
+
+        let rendered_tables = self.rendered_diffs();
 
         if rendered_tables.is_empty() {
             println!("The data is identical!");
@@ -216,6 +780,18 @@ impl App {
         Ok(())
     }
 
+    /// Prints both checked files side by side, with the lines of the differences colored
+    fn render_side_by_side(&self) -> Result<(), DtfError> {
+        let (data1, data2) = self.checked_data().ok_or(DtfError::DiffError(
+            "The side by side view can only be shown for the checked files".to_owned(),
+        ))?;
+        println!(
+            "{}",
+            render_side_by_side(&self.diffs, (&data1, &data2), &self.context)
+        );
+        Ok(())
+    }
+
     /// Renders the HTML output
     fn render_html(&self) -> Result<(), DtfError> {
         let mut buf = Buffer::new();
@@ -279,13 +855,470 @@ impl App {
         }
 
         // At this point the file name is sure to exist
-        let mut file = File::create(self.context.config.browser_view.as_ref().unwrap())
+        let browser_view = self.context.config.browser_view.as_ref().unwrap();
+        check_write_target(path::Path::new(browser_view), self.context.config.force)?;
+        let mut file = File::create(browser_view)
             .map_err(|e| DtfError::DiffError(format!("Could not create file: {}", e)))?;
 
         write!(file, "{}", buf.finish()).map_err(|e| DtfError::DiffError(format!("{}", e)))
     }
 
+    /// Makes sure a YAML file checked against a file of another format has no values, that can't be converted to JSON as they are.
+    /// The check of two YAML files makes sure of it while preparing the data.
+    fn check_supported_yaml_values(path: &str, format: DataFormat, config: &Config) {
+        if format != DataFormat::Yaml {
+            return;
+        }
+        let data = read_with_retry(config.read_retry, || FileHandler::read_yaml_file(path))
+            .expect("Could not read YAML file");
+        check_supported_yaml_values(&data, path).expect("Unsupported value!");
+    }
+
+    /// Prints a warning to stderr if the JSON file contains duplicate keys
+    fn warn_duplicate_keys(path: &str) {
+        // Fetching the data twice just for the warning is not worth it
+        if is_url(path) {
+            return;
+        }
+
+        let duplicate_keys =
+            FileHandler::find_duplicate_json_keys(path).expect("Could not read JSON file");
+        if !duplicate_keys.is_empty() {
+            eprintln!(
+                "{} {} contains duplicate keys: {}",
+                "Warning:".yellow(),
+                path,
+                duplicate_keys.join(", ")
+            );
+        }
+    }
+
     fn are_diffs_empty(diffs: &DiffCollection) -> bool {
         diffs.0.is_none() && diffs.1.is_none() && diffs.2.is_none() && diffs.3.is_none()
     }
+
+    /// Renders the differences with everything shown next to them, the way the user asked for.
+    /// Returns nothing if there are no differences to show.
+    pub(crate) fn rendered_diffs(&self) -> Vec<String> {
+        render_diffs(
+            &self.diffs,
+            &self.context,
+            &self.unchanged_siblings,
+            &self.equal_values,
+            &self.moved_elements,
+            &self.value_types,
+            &self.type_values,
+        )
+    }
+}
+
+/// Renders the differences the way the user asked for: grouped by key, as plain text lines or as terminal tables.
+/// The moved array items follow the differences, in the same style.
+/// Returns nothing if there are no differences to show.
+fn render_diffs(
+    diffs: &DiffCollection,
+    context: &WorkingContext,
+    unchanged_siblings: &[Vec<(String, String)>],
+    equal_values: &[(String, String)],
+    moved_elements: &[MovedElement],
+    value_types: &ValueTypes,
+    type_values: &[ValueDiff],
+) -> Vec<String> {
+    let mut rendered = if context.config.group_by_key {
+        render_grouped(diffs, context)
+    } else if context.config.by_direction {
+        render_by_direction(diffs, context)
+    } else if context.config.plain {
+        render_plain(diffs, context)
+    } else {
+        render_term_tables(
+            diffs,
+            context,
+            unchanged_siblings,
+            equal_values,
+            value_types,
+            type_values,
+        )
+    };
+
+    if !moved_elements.is_empty() {
+        rendered.push(if context.config.plain {
+            render_moves_plain(moved_elements)
+        } else {
+            MoveTable::new(moved_elements, context).render()
+        });
+    }
+    rendered
+}
+
+/// Renders a table for every kind of differences the user wants to see, skipping the ones without differences
+fn render_term_tables(
+    diffs: &DiffCollection,
+    context: &WorkingContext,
+    unchanged_siblings: &[Vec<(String, String)>],
+    equal_values: &[(String, String)],
+    value_types: &ValueTypes,
+    type_values: &[ValueDiff],
+) -> Vec<String> {
+    let (key_diff, type_diff, value_diff, array_diff) = diffs;
+
+    let mut rendered_tables = vec![];
+    if context.config.render_key_diffs {
+        if let Some(diffs) = key_diff.as_ref().filter(|kd| !kd.is_empty()) {
+            let table = KeyTable::new(diffs, context);
+            rendered_tables.push(table.render());
+        }
+    }
+
+    if context.config.render_type_diffs {
+        let diffs = type_diff
+            .iter()
+            .flatten()
+            .filter(|td| !type_values.iter().any(|row| row.key == td.key))
+            .cloned()
+            .collect::<Vec<TypeDiff>>();
+        if !diffs.is_empty() {
+            let table = TypeTable::new(&diffs, context);
+            rendered_tables.push(table.render());
+        }
+    }
+
+    if context.config.render_value_diffs {
+        if let Some(diffs) = value_diff
+            .as_ref()
+            .filter(|vd| !vd.is_empty() || !equal_values.is_empty() || !type_values.is_empty())
+        {
+            let rows = diffs
+                .iter()
+                .chain(type_values)
+                .cloned()
+                .collect::<Vec<ValueDiff>>();
+            let table = ValueTable::new(
+                &rows,
+                unchanged_siblings.to_vec(),
+                value_types,
+                equal_values,
+                context,
+            );
+            rendered_tables.push(table.render());
+        }
+    }
+
+    if context.config.render_array_diffs {
+        if let Some(diffs) = array_diff.as_ref().filter(|ad| !ad.is_empty()) {
+            let table = ArrayTable::new(diffs, context);
+            rendered_tables.push(table.render());
+        }
+    }
+
+    rendered_tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_args_reads_no_files() {
+        let args = Arguments::try_parse_from([
+            "dtfterminal",
+            "-c",
+            "missing_a.json",
+            "missing_b.json",
+            "--profile",
+            "missing_profile",
+            "--save-profile",
+            "dtfterminal_check_args_profile",
+            "--theme",
+            "missing_theme.json",
+            "--ignore-file",
+            "missing_ignore.txt",
+            "--check-args",
+        ])
+        .unwrap();
+
+        let (path1, _, config) = App::from_arguments(args);
+
+        assert_eq!(path1, Some("missing_a.json".to_owned()));
+        assert!(config.check_args);
+    }
+
+    #[test]
+    fn test_write_to_file_and_browser_view_both_written() {
+        let file_path = std::env::temp_dir().join("dtfterminal_both_outputs.json");
+        let html_path = std::env::temp_dir().join("dtfterminal_both_outputs.html");
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .render_key_diffs(true)
+            .file_a(Some("test_data/json/person1.json".to_owned()))
+            .file_b(Some("test_data/json/person2.json".to_owned()))
+            .write_to_file(Some(file_path.to_string_lossy().to_string()))
+            .browser_view(Some(html_path.to_string_lossy().to_string()))
+            .no_browser_show(true)
+            .quiet(true)
+            .build();
+        let app = App::new(config.file_a.clone(), config.file_b.clone(), config);
+
+        app.write_output().unwrap();
+
+        assert!(std::fs::read_to_string(&file_path)
+            .unwrap()
+            .contains("key_diff"));
+        assert!(std::fs::read_to_string(&html_path)
+            .unwrap()
+            .contains("Key Differences"));
+        std::fs::remove_file(file_path).unwrap();
+        std::fs::remove_file(html_path).unwrap();
+    }
+
+    #[test]
+    fn test_outputs_written_into_output_dir() {
+        let output_dir = std::env::temp_dir().join("dtfterminal_output_dir");
+        let output_dir = output_dir.to_string_lossy().to_string();
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .render_key_diffs(true)
+            .file_a(Some("test_data/json/person1.json".to_owned()))
+            .file_b(Some("test_data/json/person2.json".to_owned()))
+            .write_to_file(Some(get_output_path(Some(&output_dir), "", "diff.json")))
+            .junit(Some(get_output_path(Some(&output_dir), "", "diff.xml")))
+            .output_dir(Some(output_dir.clone()))
+            .quiet(true)
+            .build();
+        let app = App::new(config.file_a.clone(), config.file_b.clone(), config);
+
+        app.write_output().unwrap();
+
+        let output_dir = path::Path::new(&output_dir);
+        assert!(output_dir.join("diff.json").exists());
+        assert!(output_dir.join("diff.xml").exists());
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_saved_file_with_checks_given() {
+        let config = ConfigBuilder::new()
+            .check_for_value_diffs(true)
+            .render_value_diffs(true)
+            .read_from_file("test_data/saved_all_checks.json".to_owned())
+            .quiet(true)
+            .build();
+        let app = App::new(None, None, config);
+
+        let rendered = render_diffs(
+            &app.diffs,
+            &app.context,
+            &[],
+            &[],
+            &[],
+            &ValueTypes::new(),
+            &[],
+        );
+
+        assert_eq!(rendered.len(), 1);
+        assert!(rendered[0].contains("Value Differences"));
+        assert!(rendered[0].contains("Pencil") && !rendered[0].contains("discount"));
+    }
+
+    #[test]
+    fn test_swap_matches_opposite_order() {
+        let swapped = App::new(
+            Some("test_data/json/person1.json".to_owned()),
+            Some("test_data/json/person3.json".to_owned()),
+            get_config("person1.json", "person3.json", true),
+        );
+        let opposite = App::new(
+            Some("test_data/json/person3.json".to_owned()),
+            Some("test_data/json/person1.json".to_owned()),
+            get_config("person3.json", "person1.json", false),
+        );
+
+        assert_eq!(
+            swapped.context.get_file_names(),
+            opposite.context.get_file_names()
+        );
+        assert_eq!(sorted(&swapped.diffs.0), sorted(&opposite.diffs.0));
+        assert_eq!(sorted(&swapped.diffs.1), sorted(&opposite.diffs.1));
+        assert_eq!(sorted(&swapped.diffs.2), sorted(&opposite.diffs.2));
+        assert_eq!(sorted(&swapped.diffs.3), sorted(&opposite.diffs.3));
+    }
+
+    #[test]
+    fn test_self_compare() {
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_value_diffs(true)
+            .file_a(Some("test_data/json/sections.json#/a".to_owned()))
+            .file_b(Some("test_data/json/sections.json#/b".to_owned()))
+            .self_compare_file(Some("test_data/json/sections.json".to_owned()))
+            .self_compare_pointers(Some(("/a".to_owned(), "/b".to_owned())))
+            .quiet(true)
+            .build();
+        let app = App::new(None, None, config);
+
+        assert!(app.diffs.0.unwrap().is_empty());
+        let value_diffs = app.diffs.2.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "x");
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_xml_files() {
+        let config = ConfigBuilder::new()
+            .check_for_value_diffs(true)
+            .file_a(Some("test_data/xml/service1.xml".to_owned()))
+            .file_b(Some("test_data/xml/service2.xml".to_owned()))
+            .quiet(true)
+            .build();
+        let app = App::new(config.file_a.clone(), config.file_b.clone(), config);
+
+        let value_diffs = app.diffs.2.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "service.endpoint.@timeout");
+        assert!(value_diffs[0].value1.contains("30"));
+        assert!(value_diffs[0].value2.contains("60"));
+    }
+
+    #[cfg(feature = "git")]
+    #[test]
+    fn test_git_revisions() {
+        use std::{fs, process::Command};
+
+        let repository = std::env::temp_dir().join("dtfterminal_git_test");
+        let _ = fs::remove_dir_all(&repository);
+        fs::create_dir_all(&repository).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(&repository)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?} failed", args);
+        };
+        let file = repository.join("config.json");
+        git(&["init", "-q"]);
+        fs::write(&file, r#"{"name": "service", "port": 80}"#).unwrap();
+        git(&["add", "config.json"]);
+        git(&["commit", "-q", "-m", "first"]);
+        fs::write(&file, r#"{"name": "service", "port": 8080}"#).unwrap();
+        git(&["commit", "-q", "-a", "-m", "second"]);
+
+        let path = file.to_string_lossy().to_string();
+        let config = ConfigBuilder::new()
+            .check_for_value_diffs(true)
+            .file_a(Some(get_git_revision_name(&path, "HEAD~1")))
+            .file_b(Some(get_git_revision_name(&path, "HEAD")))
+            .git_file(Some(path.clone()))
+            .git_revisions(Some(("HEAD~1".to_owned(), "HEAD".to_owned())))
+            .quiet(true)
+            .build();
+        let app = App::new(None, None, config);
+        let missing_revision = FileHandler::read_git_revision("no-such-revision", &path, None);
+        fs::remove_dir_all(&repository).unwrap();
+
+        let value_diffs = app.diffs.2.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "port");
+        assert!(value_diffs[0].value1.contains("80"));
+        assert!(value_diffs[0].value2.contains("8080"));
+        assert!(matches!(missing_revision, Err(DtfError::DiffError(_))));
+    }
+
+    #[test]
+    fn test_csv_files() {
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_value_diffs(true)
+            .file_a(Some("test_data/csv/products1.csv".to_owned()))
+            .file_b(Some("test_data/csv/products2.csv".to_owned()))
+            .quiet(true)
+            .build();
+        let app = App::new(config.file_a.clone(), config.file_b.clone(), config);
+
+        assert!(app.diffs.0.unwrap().is_empty());
+        let value_diffs = app.diffs.2.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "row 2.price");
+        assert!(value_diffs[0].value1.contains('5'));
+        assert!(value_diffs[0].value2.contains('6'));
+    }
+
+    #[test]
+    fn test_format_overrides_extension() {
+        let config = ConfigBuilder::new()
+            .check_for_value_diffs(true)
+            .file_a(Some("test_data/yaml/person1_without_extension".to_owned()))
+            .file_b(Some("test_data/yaml/person2.yaml".to_owned()))
+            .format_a(Some(DataFormat::Yaml))
+            .quiet(true)
+            .build();
+        let app = App::new(config.file_a.clone(), config.file_b.clone(), config);
+
+        assert!(app.yaml_app.is_some());
+        let value_diffs = app.diffs.2.unwrap();
+        assert!(value_diffs.iter().any(|vd| vd.key == "name"));
+    }
+
+    #[test]
+    fn test_files_of_different_formats() {
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_value_diffs(true)
+            .file_a(Some("test_data/yaml/person1.yaml".to_owned()))
+            .file_b(Some("test_data/json/person1.json".to_owned()))
+            .quiet(true)
+            .build();
+        let app = App::new(config.file_a.clone(), config.file_b.clone(), config);
+
+        assert!(app.json_app.is_some());
+        assert!(app.diffs.0.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_with_timeout_covers_reading_and_preparing() {
+        let mut config = get_config("large1.json", "large2.json", false);
+        config.array_same_order = true;
+        config.timeout = Some(std::time::Duration::from_nanos(1));
+
+        let error = App::with_timeout(config.file_a.clone(), config.file_b.clone(), config)
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            error.to_string(),
+            "Diff error: The check did not finish within the timeout of 1ns"
+        );
+
+        let mut config = get_config("person1.json", "person2.json", false);
+        config.timeout = Some(std::time::Duration::from_secs(60));
+        let app = App::with_timeout(config.file_a.clone(), config.file_b.clone(), config).unwrap();
+        assert!(app.has_diffs());
+    }
+
+    fn get_config(file_a: &str, file_b: &str, swap: bool) -> Config {
+        ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_type_diffs(true)
+            .check_for_value_diffs(true)
+            .check_for_array_diffs(true)
+            .file_a(Some(format!("test_data/json/{}", file_a)))
+            .file_b(Some(format!("test_data/json/{}", file_b)))
+            .swap(swap)
+            .quiet(true)
+            .build()
+    }
+
+    /// Sorts the differences, as the order they are found in depends on the order of the files
+    fn sorted<T: std::fmt::Debug>(diffs: &Option<Vec<T>>) -> Vec<String> {
+        let mut diffs = diffs
+            .iter()
+            .flatten()
+            .map(|diff| format!("{:?}", diff))
+            .collect::<Vec<String>>();
+        diffs.sort();
+        diffs
+    }
 }
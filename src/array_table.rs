@@ -1,12 +1,12 @@
-use libdtf::core::diff_types::{ArrayDiff, ArrayDiffDesc};
+use libdtf::core::diff_types::ArrayDiff;
 use term_table::{
     row::Row,
     table_cell::{Alignment, TableCell},
 };
 
-use crate::utils::{get_display_values_by_column, group_by_key};
+use crate::utils::{cap_array_diffs, get_display_values_by_file, group_by_key, with_hidden_count};
 use crate::{
-    dtfterminal_types::{TableContext, TermTable, WorkingContext},
+    dtfterminal_types::{DiffKind, TableContext, TermTable, WorkingContext},
     utils::is_yaml_file,
 };
 
@@ -30,6 +30,7 @@ impl<'a> TermTable<ArrayDiff> for ArrayTable<'a> {
         let file_name_a = file_name_a_str.to_owned();
         let file_name_b = file_name_b_str.to_owned();
         self.add_title_row();
+        self.context.add_explanation_row(DiffKind::Array);
         self.add_file_names_row(file_name_a, file_name_b);
     }
 
@@ -38,24 +39,23 @@ impl<'a> TermTable<ArrayDiff> for ArrayTable<'a> {
         let file_name_a = self.context.working_context().get_file_names().0;
         let join_str = if is_yaml_file(file_name_a) { "" } else { ",\n" };
 
-        for (key, values) in map {
-            let display_values1: Vec<String> = get_display_values_by_column(
-                self.context.working_context(),
-                &values,
-                ArrayDiffDesc::AHas,
-            );
-            let display_values2 = get_display_values_by_column(
-                self.context.working_context(),
-                &values,
-                ArrayDiffDesc::BHas,
-            );
+        let total = map.len();
+        for (key, values) in map.into_iter().take(self.context.row_limit()) {
+            let count = values.len();
+            let (values, hidden) = cap_array_diffs(&self.context.working_context().config, values);
+            let (display_values1, display_values2) =
+                get_display_values_by_file(self.context.working_context(), &values);
 
+            let key_text = self
+                .context
+                .key_text(key, || format!("{} items only in one of the files", count));
             self.context.add_row(Row::new(vec![
-                TableCell::new(key),
+                TableCell::new(with_hidden_count(key_text, hidden)),
                 TableCell::new(display_values1.join(join_str)),
                 TableCell::new(display_values2.join(join_str)),
             ]));
         }
+        self.context.add_truncated_row(total);
     }
 }
 
@@ -70,10 +70,15 @@ impl<'a> ArrayTable<'a> {
 
     /// Adds the header row to the table
     fn add_title_row(&mut self) {
-        self.context
-            .add_row(Row::new(vec![TableCell::builder("Array Differences")
-                .col_span(3)
-                .alignment(Alignment::Center)]));
+        let title = self
+            .context
+            .working_context()
+            .config
+            .theme
+            .color_title("Array Differences");
+        self.context.add_row(Row::new(vec![TableCell::builder(title)
+            .col_span(3)
+            .alignment(Alignment::Center)]));
     }
 
     /// Adds the file names row to the table
@@ -85,3 +90,85 @@ impl<'a> ArrayTable<'a> {
         ]));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use libdtf::core::diff_types::ArrayDiffDesc;
+
+    use crate::dtfterminal_types::ConfigBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_values_split_by_descriptor() {
+        let working_context = get_working_context();
+        let data = vec![
+            ArrayDiff {
+                key: "items".to_owned(),
+                descriptor: ArrayDiffDesc::BMisses,
+                value: "only_in_a".to_owned(),
+            },
+            ArrayDiff {
+                key: "items".to_owned(),
+                descriptor: ArrayDiffDesc::AMisses,
+                value: "only_in_b".to_owned(),
+            },
+        ];
+
+        let rendered = ArrayTable::new(&data, &working_context).render();
+
+        let row = rendered
+            .lines()
+            .find(|line| line.contains("items"))
+            .unwrap();
+        let a_position = row.find("only_in_a").unwrap();
+        let b_position = row.find("only_in_b").unwrap();
+        assert!(a_position < b_position);
+    }
+
+    #[test]
+    fn test_max_array_diffs() {
+        let mut working_context = get_working_context();
+        working_context.config.max_array_diffs = Some(3);
+        let data = (0..100)
+            .map(|i| ArrayDiff {
+                key: if i % 2 == 0 { "evens" } else { "odds" }.to_owned(),
+                descriptor: if i < 50 {
+                    ArrayDiffDesc::AHas
+                } else {
+                    ArrayDiffDesc::BHas
+                },
+                value: format!("item{}", i),
+            })
+            .collect::<Vec<ArrayDiff>>();
+
+        let rendered = ArrayTable::new(&data, &working_context).render();
+
+        assert_eq!(rendered.matches("item").count(), 6);
+        assert_eq!(rendered.matches("\u{2026} and 47 more").count(), 2);
+    }
+
+    #[test]
+    fn test_file_names_row() {
+        let working_context = get_working_context();
+
+        let rendered = ArrayTable::new(&[], &working_context).render();
+
+        assert!(rendered.contains("Array Differences"));
+        assert!(rendered.contains("Only file_a.json contains"));
+        assert!(rendered.contains("Only file_b.json contains"));
+    }
+
+    fn get_working_context() -> WorkingContext {
+        let working_file_a = libdtf::core::diff_types::WorkingFile::new("file_a.json".to_string());
+        let working_file_b = libdtf::core::diff_types::WorkingFile::new("file_b.json".to_string());
+        let lib_working_context = libdtf::core::diff_types::WorkingContext::new(
+            working_file_a,
+            working_file_b,
+            libdtf::core::diff_types::Config {
+                array_same_order: false,
+            },
+        );
+        WorkingContext::new(lib_working_context, ConfigBuilder::new().build())
+    }
+}
@@ -0,0 +1,277 @@
+use std::{
+    any::Any,
+    fs,
+    io::IsTerminal,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+};
+
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::{
+    app::App,
+    dtfterminal_types::{Config, DtfError},
+    utils::{
+        is_csv_file, is_json5_file, is_json_file, is_json_lines_file, is_msgpack_file, is_xml_file,
+        is_yaml_file, CHECKMARK, MULTIPLY,
+    },
+};
+
+/// A checked file pair: the paths of the files and the app holding the differences, or the error of the check
+type CheckedPair = (String, String, Result<App, DtfError>);
+
+/// Responsible for checking every file in a directory against the file with the same name in another directory
+pub struct BatchApp {
+    config: Config,
+}
+
+impl BatchApp {
+    pub fn new(config: Config) -> BatchApp {
+        BatchApp { config }
+    }
+
+    /// Checks every file pair for differences, then prints a summary of the pairs and renders the tables of each pair to the terminal.
+    /// Tables are only rendered once every check is done, so they don't get mixed up with the progress bar.
+    /// Returns whether any of the pairs has differences the user wants the run to fail on, or could not be checked.
+    pub fn execute(&self) -> Result<bool, DtfError> {
+        let checked_pairs = self.check_file_pairs()?;
+        println!("{}", render_summary(&checked_pairs));
+
+        let mut has_failing_diffs = false;
+        for (path_a, path_b, app) in &checked_pairs {
+            let app = match app {
+                Ok(app) => app,
+                Err(_) => {
+                    has_failing_diffs = true;
+                    continue;
+                }
+            };
+            has_failing_diffs |= app.has_failing_diffs();
+            if !self.is_rendered(app) {
+                continue;
+            }
+            println!("Comparing {} against {}", path_a, path_b);
+            app.render_tables()?;
+        }
+
+        Ok(has_failing_diffs)
+    }
+
+    /// Checks every file pair for differences, showing the progress on the progress bar.
+    /// A pair, that could not be checked keeps its error, so the other pairs are still checked.
+    fn check_file_pairs(&self) -> Result<Vec<CheckedPair>, DtfError> {
+        let file_pairs = self.find_file_pairs()?;
+        let progress_bar = self.create_progress_bar(file_pairs.len() as u64);
+
+        let mut apps = vec![];
+        for (path_a, path_b) in file_pairs {
+            progress_bar.set_message(path_a.clone());
+            let app = self.check_file_pair(&path_a, &path_b);
+            apps.push((path_a, path_b, app));
+            progress_bar.inc(1);
+        }
+        progress_bar.finish_and_clear();
+
+        Ok(apps)
+    }
+
+    /// Checks a file pair for differences.
+    /// Reading a file panics if it can't be read, which is caught like in watch mode, so it only fails this pair.
+    fn check_file_pair(&self, path_a: &str, path_b: &str) -> Result<App, DtfError> {
+        let mut config = self.config.clone();
+        config.file_a = Some(path_a.to_owned());
+        config.file_b = Some(path_b.to_owned());
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            App::with_timeout(Some(path_a.to_owned()), Some(path_b.to_owned()), config)
+        }))
+        .unwrap_or_else(|panic| Err(DtfError::DiffError(get_panic_message(panic.as_ref()))))
+    }
+
+    /// Checks if the tables of a pair are rendered. With `--only-changed-files` the pairs without differences are left out
+    fn is_rendered(&self, app: &App) -> bool {
+        !self.config.only_changed_files || app.has_diffs()
+    }
+
+    /// Finds the files present in both directories.
+    /// Files without a pair or with an unsupported format are skipped with a warning.
+    fn find_file_pairs(&self) -> Result<Vec<(String, String)>, DtfError> {
+        let dir_a = self.config.dir_a.as_ref().unwrap();
+        let dir_b = self.config.dir_b.as_ref().unwrap();
+
+        let mut file_names = fs::read_dir(dir_a)
+            .map_err(DtfError::IoError)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|file_name| {
+                is_json_file(file_name)
+                    || is_json_lines_file(file_name)
+                    || is_yaml_file(file_name)
+                    || is_xml_file(file_name)
+                    || is_csv_file(file_name)
+                    || is_json5_file(file_name)
+                    || is_msgpack_file(file_name)
+            })
+            .collect::<Vec<String>>();
+        file_names.sort();
+
+        let mut file_pairs = vec![];
+        for file_name in file_names {
+            let path_a = Path::new(dir_a).join(&file_name);
+            let path_b = Path::new(dir_b).join(&file_name);
+            if path_b.is_file() {
+                file_pairs.push((
+                    path_a.to_string_lossy().to_string(),
+                    path_b.to_string_lossy().to_string(),
+                ));
+            } else {
+                eprintln!("Skipping {}: no matching file in {}", file_name, dir_b);
+            }
+        }
+
+        Ok(file_pairs)
+    }
+
+    /// Creates a progress bar on stderr, which is hidden if the user asked for quiet output or stderr is not a terminal
+    fn create_progress_bar(&self, length: u64) -> ProgressBar {
+        if self.config.quiet || !std::io::stderr().is_terminal() {
+            return ProgressBar::hidden();
+        }
+
+        let progress_bar = ProgressBar::new(length);
+        if let Ok(style) = ProgressStyle::with_template("{bar:40} {pos}/{len} {msg}") {
+            progress_bar.set_style(style);
+        }
+        progress_bar
+    }
+}
+
+/// Renders a line for every pair, marked with a checkmark if the files are identical, or a cross if they differ.
+/// The pairs, that could not be checked are marked with a cross, followed by the error.
+fn render_summary(checked_pairs: &[CheckedPair]) -> String {
+    checked_pairs
+        .iter()
+        .map(|(path_a, path_b, app)| match app {
+            Ok(app) if !app.has_diffs() => format!("{} {} - {}", CHECKMARK.green(), path_a, path_b),
+            Ok(_) => format!("{} {} - {}", MULTIPLY.red(), path_a, path_b),
+            Err(e) => format!("{} {} - {}: {}", MULTIPLY.red(), path_a, path_b, e),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Gets the message of a caught panic, which is a `String` or a `&str` for the panics of `expect` and `panic!`
+fn get_panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| {
+            panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+        })
+        .unwrap_or_else(|| "The check stopped unexpectedly".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dtfterminal_types::ConfigBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_find_file_pairs() {
+        let batch_app = BatchApp::new(get_config(false));
+
+        let file_pairs = batch_app.find_file_pairs().unwrap();
+
+        assert_eq!(
+            file_pairs,
+            vec![
+                (
+                    format!("test_data/dir_a{}person.json", std::path::MAIN_SEPARATOR),
+                    format!("test_data/dir_b{}person.json", std::path::MAIN_SEPARATOR)
+                ),
+                (
+                    format!("test_data/dir_a{}person.yaml", std::path::MAIN_SEPARATOR),
+                    format!("test_data/dir_b{}person.yaml", std::path::MAIN_SEPARATOR)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_summary_of_changed_and_identical_files() {
+        let mut config = get_config(true);
+        config.dir_a = Some("test_data/batch_a".to_owned());
+        config.dir_b = Some("test_data/batch_b".to_owned());
+        config.check_for_value_diffs = true;
+        config.render_value_diffs = true;
+        config.only_changed_files = true;
+        let batch_app = BatchApp::new(config);
+
+        let checked_pairs = batch_app.check_file_pairs().unwrap();
+
+        let separator = std::path::MAIN_SEPARATOR;
+        let summary = render_summary(&checked_pairs);
+        assert_eq!(
+            summary,
+            format!(
+                "{} test_data/batch_a{}changed.json - test_data/batch_b{}changed.json\n{} test_data/batch_a{}same.json - test_data/batch_b{}same.json",
+                MULTIPLY.red(),
+                separator,
+                separator,
+                CHECKMARK.green(),
+                separator,
+                separator
+            )
+        );
+        let rendered = checked_pairs
+            .iter()
+            .filter(|(_, _, app)| batch_app.is_rendered(app.as_ref().unwrap()))
+            .map(|(path_a, _, _)| path_a.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            rendered,
+            vec![format!("test_data/batch_a{}changed.json", separator)]
+        );
+    }
+
+    #[test]
+    fn test_unreadable_file_only_fails_its_pair() {
+        let mut config = get_config(true);
+        config.dir_a = Some("test_data/batch_broken_a".to_owned());
+        config.dir_b = Some("test_data/batch_broken_b".to_owned());
+        let batch_app = BatchApp::new(config);
+
+        let checked_pairs = batch_app.check_file_pairs().unwrap();
+
+        assert_eq!(checked_pairs.len(), 2);
+        assert!(checked_pairs[0].0.ends_with("broken.json"));
+        assert!(checked_pairs[0].2.is_err());
+        assert!(checked_pairs[1].0.ends_with("same.json"));
+        assert!(!checked_pairs[1].2.as_ref().unwrap().has_diffs());
+        let summary = render_summary(&checked_pairs);
+        assert!(summary.lines().next().unwrap().contains("broken.json: "));
+    }
+
+    #[test]
+    fn test_progress_bar_hidden_when_quiet() {
+        let batch_app = BatchApp::new(get_config(true));
+
+        let progress_bar = batch_app.create_progress_bar(2);
+
+        assert!(progress_bar.is_hidden());
+    }
+
+    fn get_config(quiet: bool) -> Config {
+        ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .render_key_diffs(true)
+            .dir_a(Some("test_data/dir_a".to_owned()))
+            .dir_b(Some("test_data/dir_b".to_owned()))
+            .quiet(quiet)
+            .build()
+    }
+}
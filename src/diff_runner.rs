@@ -0,0 +1,470 @@
+use libdtf::core::diff_types::{ArrayDiff, KeyDiff, TypeDiff, ValueDiff};
+use serde_json::{Map, Value};
+
+use crate::{
+    app::App,
+    array_table::ArrayTable,
+    dtfterminal_types::{
+        Config, ConfigBuilder, DataFormat, DiffCollection, DtfError, LibWorkingContext, TermTable,
+        ValueTypes, WorkingContext,
+    },
+    file_handler::{read_with_retry, FileHandler},
+    json_app::JsonApp,
+    key_table::KeyTable,
+    type_table::TypeTable,
+    utils::{create_working_context, get_data_format, run_with_timeout},
+    value_table::ValueTable,
+};
+
+/// The key the roots are stored under, when they are not both objects
+const ROOT_KEY: &str = "root";
+
+/// Where the data to check comes from
+enum DataSource {
+    Files(String, String),
+    Data((String, Map<String, Value>), (String, Map<String, Value>)),
+}
+
+/// Compares a single pair of values stored under the key, with every kind of difference checked.
+/// This lets custom traversals reuse the same comparison as the rest of the app, one field at a time.
+/// The keys of the differences found start with the key given.
+///
+/// ```
+/// use dtfterminal::diff_runner::compare_field;
+/// use serde_json::json;
+///
+/// let (_, type_diffs, value_diffs, _) = compare_field("price", json!(10), json!(12))?;
+/// assert!(type_diffs.unwrap().is_empty());
+/// assert_eq!(value_diffs.unwrap()[0].key, "price");
+/// # Ok::<(), dtfterminal::dtfterminal_types::DtfError>(())
+/// ```
+pub fn compare_field(
+    key: &str,
+    value_a: Value,
+    value_b: Value,
+) -> Result<DiffCollection, DtfError> {
+    DiffRunner::from_data(
+        "a",
+        Map::from_iter([(key.to_owned(), value_a)]),
+        "b",
+        Map::from_iter([(key.to_owned(), value_b)]),
+    )
+    .run()
+}
+
+/// Checks two values already in memory with the config given, the same way as two files.
+/// Objects are checked key by key. Any other root, e.g. an array or a number, is checked as the value of the `root` key,
+/// so roots of different kinds give a Type difference of `root`.
+///
+/// ```
+/// use dtfterminal::diff_runner::diff_values;
+/// use dtfterminal::dtfterminal_types::ConfigBuilder;
+/// use serde_json::json;
+///
+/// let config = ConfigBuilder::new().check_for_array_diffs(true).build();
+/// let (_, _, _, array_diffs) = diff_values(&json!(["pen"]), &json!(["pencil"]), &config)?;
+/// assert_eq!(array_diffs.unwrap()[0].key, "root");
+/// # Ok::<(), dtfterminal::dtfterminal_types::DtfError>(())
+/// ```
+pub fn diff_values(a: &Value, b: &Value, config: &Config) -> Result<DiffCollection, DtfError> {
+    let (data_a, data_b) = match (a, b) {
+        (Value::Object(data_a), Value::Object(data_b)) => (data_a.clone(), data_b.clone()),
+        _ => (
+            Map::from_iter([(ROOT_KEY.to_owned(), a.clone())]),
+            Map::from_iter([(ROOT_KEY.to_owned(), b.clone())]),
+        ),
+    };
+    DiffRunner::from_data(
+        config.file_a.as_deref().unwrap_or("a"),
+        data_a,
+        config.file_b.as_deref().unwrap_or("b"),
+        data_b,
+    )
+    .with_config(config.clone())
+    .run()
+}
+
+/// Renders a table of Key differences the way the command line does, with the default display options.
+/// The file names are taken from the context.
+pub fn render_key_table(diffs: &[KeyDiff], ctx: &LibWorkingContext) -> String {
+    KeyTable::new(diffs, &table_context(ctx)).render()
+}
+
+/// Renders a table of Type differences the way the command line does, with the default display options.
+/// The file names are taken from the context.
+pub fn render_type_table(diffs: &[TypeDiff], ctx: &LibWorkingContext) -> String {
+    TypeTable::new(diffs, &table_context(ctx)).render()
+}
+
+/// Renders a table of Value differences the way the command line does, with the default display options.
+/// The file names are taken from the context. The types of the values are not shown, as they are only known from the data.
+///
+/// ```
+/// use dtfterminal::diff_runner::render_value_table;
+/// use libdtf::core::diff_types::{Config, ValueDiff, WorkingContext, WorkingFile};
+///
+/// let ctx = WorkingContext::new(
+///     WorkingFile::new("a.json".to_owned()),
+///     WorkingFile::new("b.json".to_owned()),
+///     Config::new(false),
+/// );
+/// let diffs = vec![ValueDiff {
+///     key: "name".to_owned(),
+///     value1: "Pen".to_owned(),
+///     value2: "Pencil".to_owned(),
+/// }];
+///
+/// let table = render_value_table(&diffs, &ctx);
+/// assert!(table.contains("Value Differences"));
+/// assert!(table.contains("Pencil"));
+/// ```
+pub fn render_value_table(diffs: &[ValueDiff], ctx: &LibWorkingContext) -> String {
+    ValueTable::new(diffs, vec![], &ValueTypes::new(), &[], &table_context(ctx)).render()
+}
+
+/// Renders a table of Array differences the way the command line does, with the default display options.
+/// The file names are taken from the context.
+pub fn render_array_table(diffs: &[ArrayDiff], ctx: &LibWorkingContext) -> String {
+    ArrayTable::new(diffs, &table_context(ctx)).render()
+}
+
+/// The context of the tables rendered for library users, with the default config
+fn table_context(ctx: &LibWorkingContext) -> WorkingContext {
+    WorkingContext::new(ctx.clone(), ConfigBuilder::new().build())
+}
+
+/// Runs a check of two files or two maps of data, for using dtfterminal as a library.
+/// The data goes through the same steps as on the command line, according to the config, but nothing gets printed.
+///
+/// ```no_run
+/// use dtfterminal::diff_runner::DiffRunner;
+///
+/// let mut runner = DiffRunner::from_files("a.json", "b.json");
+/// let diffs = runner.run()?;
+/// println!("{}", runner.render());
+/// # Ok::<(), dtfterminal::dtfterminal_types::DtfError>(())
+/// ```
+pub struct DiffRunner {
+    source: DataSource,
+    config: Config,
+    /// The app of the last run, holding everything needed to render the differences
+    result: Option<App>,
+}
+
+impl DiffRunner {
+    /// Creates a runner checking two files. Their format is decided by their extension, the same way as on the command line, unless it is set in the config.
+    /// Every kind of difference is checked and rendered, unless a config is given with `with_config`.
+    pub fn from_files(path_a: &str, path_b: &str) -> DiffRunner {
+        DiffRunner::new(DataSource::Files(path_a.to_owned(), path_b.to_owned()))
+    }
+
+    /// Creates a runner checking two maps of data already in memory, shown under the names given.
+    /// Every kind of difference is checked and rendered, unless a config is given with `with_config`.
+    pub fn from_data(
+        name_a: &str,
+        data_a: Map<String, Value>,
+        name_b: &str,
+        data_b: Map<String, Value>,
+    ) -> DiffRunner {
+        DiffRunner::new(DataSource::Data(
+            (name_a.to_owned(), data_a),
+            (name_b.to_owned(), data_b),
+        ))
+    }
+
+    fn new(source: DataSource) -> DiffRunner {
+        DiffRunner {
+            source,
+            config: ConfigBuilder::new()
+                .check_for_key_diffs(true)
+                .check_for_type_diffs(true)
+                .check_for_value_diffs(true)
+                .check_for_array_diffs(true)
+                .render_key_diffs(true)
+                .render_type_diffs(true)
+                .render_value_diffs(true)
+                .render_array_diffs(true)
+                .build(),
+            result: None,
+        }
+    }
+
+    /// Replaces the config of the runner. The names of the files are always taken from the runner
+    pub fn with_config(mut self, config: Config) -> DiffRunner {
+        self.config = config;
+        self
+    }
+
+    /// Reads the data and checks it for differences, which are processed according to the config, e.g. ignored keys are left out
+    pub fn run(&mut self) -> Result<DiffCollection, DtfError> {
+        let ((name_a, data_a), (name_b, data_b)) = match &self.source {
+            DataSource::Files(path_a, path_b) => (
+                (
+                    path_a.clone(),
+                    self.read_data(path_a, self.config.format_a)?,
+                ),
+                (
+                    path_b.clone(),
+                    self.read_data(path_b, self.config.format_b)?,
+                ),
+            ),
+            DataSource::Data(data_a, data_b) => (data_a.clone(), data_b.clone()),
+        };
+        // The runner checks its own data and prints nothing, so saved results and streaming don't apply
+        let config = Config {
+            file_a: Some(name_a),
+            file_b: Some(name_b),
+            read_from_file: String::new(),
+            stream: false,
+            ..self.config.clone()
+        };
+
+        let context = create_working_context(&config)?;
+        let app = run_with_timeout(config.timeout, move || {
+            let json_app = JsonApp::try_from_data(data_a, data_b, context.clone())?;
+            App::from_json_app(json_app, context, &config)
+        })??;
+        let diffs = app.diffs().clone();
+        self.result = Some(app);
+        Ok(diffs)
+    }
+
+    /// Renders the differences found by the last run the same way they are printed on the command line.
+    /// Nothing is rendered before the first run.
+    pub fn render(&self) -> String {
+        let Some(app) = &self.result else {
+            return String::new();
+        };
+
+        let rendered = app.rendered_diffs();
+        if rendered.is_empty() {
+            return "The data is identical!\n".to_owned();
+        }
+        rendered
+            .into_iter()
+            .map(|table| format!("{}\n", table))
+            .collect()
+    }
+
+    /// Reads a file as JSON data, in the format given in the config or the one its extension suggests
+    fn read_data(
+        &self,
+        path: &str,
+        format: Option<DataFormat>,
+    ) -> Result<Map<String, Value>, DtfError> {
+        read_with_retry(self.config.read_retry, || {
+            FileHandler::read_data_as_json(
+                path,
+                format.unwrap_or_else(|| get_data_format(path)),
+                self.config.array_key.as_deref(),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::dtfterminal_types::{Severity, SeverityOverride};
+
+    use super::*;
+
+    #[test]
+    fn test_run_data() {
+        let data_a = json!({ "name": "Pen", "price": 10, "discount": 5 });
+        let data_b = json!({ "name": "Pencil", "price": 10 });
+        let mut runner = DiffRunner::from_data(
+            "a.json",
+            data_a.as_object().unwrap().clone(),
+            "b.json",
+            data_b.as_object().unwrap().clone(),
+        )
+        .with_config(
+            ConfigBuilder::new()
+                .check_for_key_diffs(true)
+                .check_for_value_diffs(true)
+                .render_value_diffs(true)
+                .plain(true)
+                .build(),
+        );
+
+        assert_eq!(runner.render(), "");
+        let (key_diffs, type_diffs, value_diffs, array_diffs) = runner.run().unwrap();
+
+        assert_eq!(key_diffs.unwrap().len(), 1);
+        assert!(type_diffs.is_none() && array_diffs.is_none());
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "name");
+        let rendered = runner.render();
+        assert!(rendered.contains("[value] name"));
+        assert!(
+            !rendered.contains("[key]"),
+            "only value differences are rendered"
+        );
+    }
+
+    #[test]
+    fn test_run_with_min_severity() {
+        let data_a = json!({ "name": "Pen", "price": "10", "tags": ["sale"], "discount": 5 });
+        let data_b = json!({ "name": "Pencil", "price": 10, "tags": ["office"] });
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_type_diffs(true)
+            .check_for_value_diffs(true)
+            .check_for_array_diffs(true)
+            .render_key_diffs(true)
+            .render_type_diffs(true)
+            .render_value_diffs(true)
+            .render_array_diffs(true)
+            .plain(true)
+            .min_severity(Some(Severity::High))
+            .build();
+        let mut runner = DiffRunner::from_data(
+            "a.json",
+            data_a.as_object().unwrap().clone(),
+            "b.json",
+            data_b.as_object().unwrap().clone(),
+        )
+        .with_config(config.clone());
+
+        let (key_diffs, _, value_diffs, _) = runner.run().unwrap();
+
+        assert!(!key_diffs.unwrap().is_empty() && !value_diffs.unwrap().is_empty());
+        let rendered = runner.render();
+        assert!(rendered.contains("[type] price"));
+        assert!(!rendered.contains("[key]") && !rendered.contains("[value]"));
+        assert!(!rendered.contains("[array]"));
+
+        let overrides = vec!["key=high".parse::<SeverityOverride>().unwrap()];
+        runner = runner.with_config(Config {
+            severity_overrides: overrides,
+            ..config
+        });
+        runner.run().unwrap();
+        assert!(runner.render().contains("[key] discount"));
+    }
+
+    #[test]
+    fn test_render_moved_elements() {
+        let data_a = json!({ "tags": ["x", "a", "b"] });
+        let data_b = json!({ "tags": ["a", "b", "x"] });
+        let config = ConfigBuilder::new()
+            .check_for_array_diffs(true)
+            .render_array_diffs(true)
+            .report_moves(true)
+            .plain(true)
+            .build();
+        let mut runner = DiffRunner::from_data(
+            "a.json",
+            data_a.as_object().unwrap().clone(),
+            "b.json",
+            data_b.as_object().unwrap().clone(),
+        )
+        .with_config(config.clone());
+
+        let (_, _, _, array_diffs) = runner.run().unwrap();
+
+        assert!(array_diffs.unwrap().is_empty());
+        let rendered = runner.render();
+        assert!(rendered.contains("Moved Array Items"));
+        assert!(rendered.contains(r#"[moved] tags: "x": [0] -> [2]"#));
+
+        runner = runner.with_config(Config {
+            plain: false,
+            ..config
+        });
+        runner.run().unwrap();
+        assert!(runner.render().contains("[0] \u{2192} [2]"));
+    }
+
+    #[test]
+    fn test_run_with_timeout() {
+        let data: serde_json::Map<String, Value> = (0..20000)
+            .map(|i| {
+                (
+                    format!("record{}", i),
+                    json!({ "id": i, "tags": ["a", "b", i] }),
+                )
+            })
+            .collect();
+        let config = ConfigBuilder::new()
+            .check_for_value_diffs(true)
+            .check_for_array_diffs(true)
+            .timeout(Some(std::time::Duration::from_nanos(1)))
+            .build();
+
+        let error = DiffRunner::from_data("a.json", data.clone(), "b.json", data)
+            .with_config(config)
+            .run()
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Diff error: The check did not finish within the timeout of 1ns"
+        );
+    }
+
+    #[test]
+    fn test_compare_field() {
+        let (key_diffs, type_diffs, _, array_diffs) =
+            compare_field("name", json!("Pen"), json!(null)).unwrap();
+
+        assert!(key_diffs.unwrap().is_empty());
+        assert_eq!(type_diffs.unwrap()[0].key, "name");
+        assert!(array_diffs.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_values() {
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_type_diffs(true)
+            .check_for_value_diffs(true)
+            .check_for_array_diffs(true)
+            .file_a(Some("test_data/json/person1.json".to_owned()))
+            .file_b(Some("test_data/json/person2.json".to_owned()))
+            .build();
+        let read = |path: &str| {
+            serde_json::from_str::<Value>(&std::fs::read_to_string(path).unwrap()).unwrap()
+        };
+
+        let diffs = diff_values(
+            &read("test_data/json/person1.json"),
+            &read("test_data/json/person2.json"),
+            &config,
+        )
+        .unwrap();
+        let file_diffs =
+            DiffRunner::from_files("test_data/json/person1.json", "test_data/json/person2.json")
+                .with_config(config.clone())
+                .run()
+                .unwrap();
+        assert_eq!(
+            serde_json::to_value(diffs).unwrap(),
+            serde_json::to_value(file_diffs).unwrap()
+        );
+
+        let (_, type_diffs, _, _) =
+            diff_values(&json!([1, 2]), &json!({ "count": 2 }), &config).unwrap();
+        assert_eq!(type_diffs.unwrap()[0].key, "root");
+
+        let (_, _, value_diffs, _) = diff_values(&json!(1), &json!(2), &config).unwrap();
+        assert_eq!(value_diffs.unwrap()[0].key, "root");
+    }
+
+    #[test]
+    fn test_run_files() {
+        let mut runner =
+            DiffRunner::from_files("test_data/yaml/person1.yaml", "test_data/json/missing.json");
+        assert!(matches!(runner.run(), Err(DtfError::FileNotFound(_))));
+
+        let mut runner =
+            DiffRunner::from_files("test_data/json/person1.json", "test_data/json/person1.json");
+        let diffs = runner.run().unwrap();
+
+        assert!(diffs.0.unwrap().is_empty());
+        assert_eq!(runner.render(), "The data is identical!\n");
+    }
+}
@@ -1,12 +1,35 @@
-use std::{error::Error, fmt};
+use std::{collections::HashMap, error::Error, fmt, str::FromStr, time::Duration};
 
-use libdtf::core::diff_types::{ArrayDiff, Diff, KeyDiff, TypeDiff, ValueDiff};
-use serde::{Deserialize, Serialize};
-use term_table::{row::Row, Table, TableStyle};
+use colored::{Color, ColoredString, Colorize};
+
+use libdtf::core::diff_types::{ArrayDiff, ArrayDiffDesc, Diff, KeyDiff, TypeDiff, ValueDiff};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use term_table::{
+    row::Row,
+    table_cell::{Alignment, TableCell},
+    Table, TableStyle,
+};
+
+use crate::utils::{abbreviate_key, get_column_width, CHECKMARK, MULTIPLY};
 
 pub type LibConfig = libdtf::core::diff_types::Config;
 pub type LibWorkingContext = libdtf::core::diff_types::WorkingContext;
 
+/// The maximum width of a column in the terminal tables
+pub const MAX_COLUMN_WIDTH: usize = 80;
+
+/// The minimum width of a column in the terminal tables, so even very narrow terminals get readable columns
+pub const MIN_COLUMN_WIDTH: usize = 10;
+
+/// The width of the terminal tables, if the width of the terminal can't be found, e.g. when the output is redirected
+pub const DEFAULT_TABLE_WIDTH: usize = 80;
+
+/// The characters of a table row besides the content of its three columns: four borders and a space on both sides of every column
+pub const TABLE_BORDER_WIDTH: usize = 10;
+
+/// The version of the saved file format. Increase it whenever SavedContext or SavedConfig changes
+pub const SAVED_FILE_VERSION: u32 = 1;
+
 /// Stores the data required for rendering a table of the differences to the terminal
 pub struct TableContext<'a> {
     working_context: &'a WorkingContext,
@@ -16,7 +39,7 @@ pub struct TableContext<'a> {
 impl<'a> TableContext<'a> {
     pub fn new(working_context: &'a WorkingContext) -> TableContext {
         let mut table = Table::new();
-        table.max_column_width = 80;
+        table.max_column_width = get_column_width(&working_context.config);
         table.style = TableStyle::extended();
         TableContext {
             working_context,
@@ -39,6 +62,51 @@ impl<'a> TableContext<'a> {
         self.table.add_row(row);
     }
 
+    /// The number of rows the table can have besides the header, based on the row limit of the user
+    pub fn row_limit(&self) -> usize {
+        self.working_context.config.limit.unwrap_or(usize::MAX)
+    }
+
+    /// Adds a footer row telling how many rows were left out, if there were more than the row limit
+    pub fn add_truncated_row(&mut self, total: usize) {
+        let limit = self.row_limit();
+        if total > limit {
+            self.add_row(Row::new(vec![TableCell::builder(format!(
+                "\u{2026} and {} more",
+                total - limit
+            ))
+            .col_span(3)
+            .alignment(Alignment::Center)]));
+        }
+    }
+
+    /// Adds a row explaining what the differences of the kind mean, if the user asked for explanations
+    pub fn add_explanation_row(&mut self, kind: DiffKind) {
+        if self.working_context.config.explain {
+            self.add_row(Row::new(vec![TableCell::builder(
+                kind.explanation().italic(),
+            )
+            .col_span(3)
+            .alignment(Alignment::Center)]));
+        }
+    }
+
+    /// The text of the key cell of a row, followed by the reason of the difference if the user asked for explanations.
+    /// Long keys are abbreviated if the user asked for it.
+    pub fn key_text(&self, key: &str, reason: impl FnOnce() -> String) -> String {
+        let config = &self.working_context.config;
+        let key = if config.abbreviate_keys {
+            abbreviate_key(key, config.json_pointer)
+        } else {
+            key.to_owned()
+        };
+        if config.explain {
+            format!("{}\n{}", key, reason().dimmed())
+        } else {
+            key
+        }
+    }
+
     /// Returns the built terminal table string
     pub fn render(&self) -> String {
         self.table.render()
@@ -71,6 +139,340 @@ pub type DiffCollection = (
     Option<Vec<ArrayDiff>>,
 );
 
+/// The types of the values of the Value differences in both files, by the keys of the differences.
+/// The differences only hold the values as text, so the types are taken from the checked data.
+pub type ValueTypes = HashMap<String, (ValueType, ValueType)>;
+
+/// The kinds of differences the user can select
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+pub enum DiffKind {
+    Key,
+    Type,
+    Value,
+    Array,
+    All,
+}
+
+impl DiffKind {
+    /// A sentence about what differences of the kind mean, for users new to the categories
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            DiffKind::Key => "The key is present in only one of the files.",
+            DiffKind::Type => "The key is present in both files, but its values are of different types.",
+            DiffKind::Value => "The key is present in both files with values of the same type, but the values differ.",
+            DiffKind::Array => "The array contains items, that the array at the same key in the other file doesn't.",
+            DiffKind::All => "",
+        }
+    }
+}
+
+/// How important a difference is, used to hide the less important kinds of differences
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum, Serialize, Deserialize,
+)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    /// Gets the severity of a kind of differences. The last severity given by the user for the kind wins, otherwise
+    /// Key differences are low, Type differences are high, and Value and Array differences are medium
+    pub fn of(kind: DiffKind, overrides: &[SeverityOverride]) -> Severity {
+        overrides
+            .iter()
+            .rev()
+            .find(|o| o.kind == kind || o.kind == DiffKind::All)
+            .map(|o| o.severity)
+            .unwrap_or(match kind {
+                DiffKind::Key => Severity::Low,
+                DiffKind::Type => Severity::High,
+                _ => Severity::Medium,
+            })
+    }
+}
+
+/// The severity given to a kind of differences by the user, e.g. `key=high`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeverityOverride {
+    pub kind: DiffKind,
+    pub severity: Severity,
+}
+
+impl FromStr for SeverityOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, severity) = s
+            .split_once('=')
+            .ok_or(format!("expected KIND=SEVERITY, e.g. key=high, got {}", s))?;
+        Ok(SeverityOverride {
+            kind: clap::ValueEnum::from_str(kind, true)?,
+            severity: clap::ValueEnum::from_str(severity, true)?,
+        })
+    }
+}
+
+/// The ways keys can be normalized before the keys of the files are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+pub enum KeyNormalization {
+    /// Leading and trailing whitespace is removed
+    Trim,
+    /// Letters are made lowercase
+    Lower,
+    /// Both whitespace is removed and letters are made lowercase
+    Both,
+}
+
+impl KeyNormalization {
+    /// Gets the normalized form of a key
+    pub fn normalize(&self, key: &str) -> String {
+        match self {
+            KeyNormalization::Trim => key.trim().to_owned(),
+            KeyNormalization::Lower => key.to_lowercase(),
+            KeyNormalization::Both => key.trim().to_lowercase(),
+        }
+    }
+}
+
+/// What happens to `${VAR}` placeholders of environment variables, that are not set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+pub enum EnvExpansion {
+    /// The placeholder is left as it is
+    Keep,
+    /// The check fails
+    Error,
+}
+
+/// The formats the checked files can be read in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+pub enum DataFormat {
+    Json,
+    /// JSON Lines, holding a JSON value in every line
+    Jsonl,
+    Yaml,
+    Csv,
+    Xml,
+    /// JSON5, which allows comments and trailing commas, like JSONC does
+    Json5,
+    /// MessagePack, a binary format with the same kinds of values as JSON
+    #[value(name = "msgpack")]
+    MessagePack,
+}
+
+/// The types of values, that differences can be ignored by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+pub enum ValueType {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl ValueType {
+    /// Gets the type by the name used in the differences, e.g. `boolean`
+    pub fn from_name(name: &str) -> Option<ValueType> {
+        match name {
+            "null" => Some(ValueType::Null),
+            "boolean" | "bool" => Some(ValueType::Bool),
+            "number" => Some(ValueType::Number),
+            "string" => Some(ValueType::String),
+            "array" => Some(ValueType::Array),
+            "object" => Some(ValueType::Object),
+            _ => None,
+        }
+    }
+
+    /// Gets the name of the type used in the differences, e.g. `boolean`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValueType::Null => "null",
+            ValueType::Bool => "boolean",
+            ValueType::Number => "number",
+            ValueType::String => "string",
+            ValueType::Array => "array",
+            ValueType::Object => "object",
+        }
+    }
+
+    /// Gets the type of a value of the data
+    pub fn of(value: &serde_json::Value) -> ValueType {
+        match value {
+            serde_json::Value::Null => ValueType::Null,
+            serde_json::Value::Bool(_) => ValueType::Bool,
+            serde_json::Value::Number(_) => ValueType::Number,
+            serde_json::Value::String(_) => ValueType::String,
+            serde_json::Value::Array(_) => ValueType::Array,
+            serde_json::Value::Object(_) => ValueType::Object,
+        }
+    }
+}
+
+/// The colors used in the terminal tables. Every color can be overridden by a theme file
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// The color of the checkmarks of files containing a key
+    #[serde(deserialize_with = "deserialize_color")]
+    pub has: Color,
+    /// The color of the crosses of files missing a key
+    #[serde(deserialize_with = "deserialize_color")]
+    pub misses: Color,
+    /// The color of the table titles. The titles are not colored if missing
+    #[serde(deserialize_with = "deserialize_optional_color")]
+    pub title: Option<Color>,
+    pub types: TypeColors,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            has: Color::Green,
+            misses: Color::Red,
+            title: None,
+            types: TypeColors::default(),
+        }
+    }
+}
+
+impl Theme {
+    /// Colors the title of a table
+    pub fn color_title(&self, title: &str) -> ColoredString {
+        match self.title {
+            Some(color) => title.color(color),
+            None => title.normal(),
+        }
+    }
+}
+
+/// The colors of the type names in the Type table
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TypeColors {
+    #[serde(deserialize_with = "deserialize_color")]
+    pub string: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub number: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub boolean: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub array: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub object: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub null: Color,
+}
+
+impl Default for TypeColors {
+    fn default() -> TypeColors {
+        TypeColors {
+            string: Color::Green,
+            number: Color::Blue,
+            boolean: Color::Yellow,
+            array: Color::Magenta,
+            object: Color::Cyan,
+            null: Color::BrightBlack,
+        }
+    }
+}
+
+/// Reads a color by its name, e.g. `blue` or `bright black`
+fn deserialize_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Color::from_str(&name).map_err(|_| de::Error::custom(format!("unknown color {}", name)))
+}
+
+/// Reads an optional color by its name
+fn deserialize_optional_color<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Color>, D::Error> {
+    deserialize_color(deserializer).map(Some)
+}
+
+/// An array item present in both files, but at a position breaking the order of the other items
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovedElement {
+    /// The key of the array
+    pub key: String,
+    pub value: String,
+    /// The index of the item in the first file
+    pub from: usize,
+    /// The index of the item in the second file
+    pub to: usize,
+}
+
+impl MovedElement {
+    /// Swaps the indexes of the item, as if the files were swapped
+    pub fn swap(&mut self) {
+        std::mem::swap(&mut self.from, &mut self.to);
+    }
+}
+
+/// A single difference of any kind, for handling every difference in a collection the same way
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnyDiff {
+    Key(KeyDiff),
+    Type(TypeDiff),
+    Value(ValueDiff),
+    Array(ArrayDiff),
+}
+
+impl AnyDiff {
+    /// Checks if the difference is of the given kind
+    pub fn is_kind(&self, kind: DiffKind) -> bool {
+        matches!(
+            (kind, self),
+            (DiffKind::All, _)
+                | (DiffKind::Key, AnyDiff::Key(_))
+                | (DiffKind::Type, AnyDiff::Type(_))
+                | (DiffKind::Value, AnyDiff::Value(_))
+                | (DiffKind::Array, AnyDiff::Array(_))
+        )
+    }
+
+    /// Returns the key the difference was found at
+    pub fn key(&self) -> &str {
+        match self {
+            AnyDiff::Key(diff) => &diff.key,
+            AnyDiff::Type(diff) => &diff.key,
+            AnyDiff::Value(diff) => &diff.key,
+            AnyDiff::Array(diff) => &diff.key,
+        }
+    }
+}
+
+/// Iterates over every difference in the collection: key, type, value, then array differences
+pub fn iter_diffs(collection: &DiffCollection) -> impl Iterator<Item = AnyDiff> + '_ {
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = collection;
+    let key_diffs = key_diffs.iter().flatten().cloned().map(AnyDiff::Key);
+    let type_diffs = type_diffs.iter().flatten().cloned().map(AnyDiff::Type);
+    let value_diffs = value_diffs.iter().flatten().cloned().map(AnyDiff::Value);
+    let array_diffs = array_diffs.iter().flatten().cloned().map(AnyDiff::Array);
+
+    key_diffs
+        .chain(type_diffs)
+        .chain(value_diffs)
+        .chain(array_diffs)
+}
+
+/// Adds the differences of the kinds checked in `other` to the collection
+pub fn merge_diffs(diffs: &mut DiffCollection, other: DiffCollection) {
+    fn merge<T>(diffs: &mut Option<Vec<T>>, other: Option<Vec<T>>) {
+        if let Some(other) = other {
+            diffs.get_or_insert_with(Vec::new).extend(other);
+        }
+    }
+
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = other;
+    merge(&mut diffs.0, key_diffs);
+    merge(&mut diffs.1, type_diffs);
+    merge(&mut diffs.2, value_diffs);
+    merge(&mut diffs.3, array_diffs);
+}
+
 /// The structure a result set gets saved in for later re-use
 #[derive(Serialize, Deserialize)]
 pub struct SavedConfig {
@@ -105,6 +507,16 @@ impl SavedConfig {
     }
 }
 
+/// The formats the differences can be saved in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SaveFormat {
+    /// Every difference is an object with named fields
+    #[default]
+    Full,
+    /// Short field names, every difference is an array and empty kinds are left out
+    Compact,
+}
+
 /// The structure the runtime configurations are stored in
 #[derive(Clone)]
 pub struct Config {
@@ -124,6 +536,81 @@ pub struct Config {
     pub browser_view: Option<String>,
     pub printer_friendly: bool,
     pub no_browser_show: bool,
+    pub null_is_missing: bool,
+    pub dir_a: Option<String>,
+    pub dir_b: Option<String>,
+    pub quiet: bool,
+    pub ignore_patterns: Vec<String>,
+    pub warn_duplicate_keys: bool,
+    pub intersection_only: bool,
+    pub compact_values: bool,
+    pub fail_on: Vec<DiffKind>,
+    pub check_args: bool,
+    pub no_color: bool,
+    pub numbers_as_text: bool,
+    pub array_key: Option<String>,
+    pub json_pointer: bool,
+    pub split_file: Option<String>,
+    pub verbose: bool,
+    pub context_size: usize,
+    pub interactive: bool,
+    pub only_patterns: Vec<String>,
+    pub ignore_globs: Vec<String>,
+    pub junit: Option<String>,
+    pub baseline: Option<String>,
+    pub theme: Theme,
+    pub no_dedup: bool,
+    pub print_summary: bool,
+    pub subtree_pointer: Option<String>,
+    pub plain: bool,
+    pub limit: Option<usize>,
+    pub swap: bool,
+    pub group_by_key: bool,
+    pub explain: bool,
+    pub self_compare_file: Option<String>,
+    pub self_compare_pointers: Option<(String, String)>,
+    pub watch: bool,
+    pub max_value_len: Option<usize>,
+    pub has_symbol: String,
+    pub misses_symbol: String,
+    pub json_patch: Option<String>,
+    pub normalize_keys: Option<KeyNormalization>,
+    pub stream: bool,
+    pub combine_type_value: bool,
+    pub round: Option<u32>,
+    pub save_format: SaveFormat,
+    pub include_equal: bool,
+    pub max_width: Option<usize>,
+    pub require_non_empty: bool,
+    pub ignore_types: Vec<ValueType>,
+    pub format_a: Option<DataFormat>,
+    pub format_b: Option<DataFormat>,
+    pub by_direction: bool,
+    pub abbreviate_keys: bool,
+    pub git_file: Option<String>,
+    pub git_revisions: Option<(String, String)>,
+    pub force: bool,
+    pub show_delta: bool,
+    pub pretty_save: bool,
+    pub thousands_sep: Option<String>,
+    pub expand_env: Option<EnvExpansion>,
+    pub strict: bool,
+    pub smart_dates: bool,
+    pub min_severity: Option<Severity>,
+    pub severity_overrides: Vec<SeverityOverride>,
+    pub count_only: bool,
+    pub stacked: bool,
+    pub max_array_diffs: Option<usize>,
+    pub empty_equals_null: bool,
+    pub output_dir: Option<String>,
+    pub side_by_side: bool,
+    pub min_array_size: Option<usize>,
+    pub read_retry: usize,
+    pub yaml: Option<String>,
+    pub only_changed_files: bool,
+    pub key_separator: Option<String>,
+    pub timeout: Option<Duration>,
+    pub report_moves: bool,
 }
 
 /// Helper class for creating Config instances
@@ -145,6 +632,81 @@ pub struct ConfigBuilder {
     browser_view: Option<String>,
     printer_friendly: bool,
     no_browser_show: bool,
+    null_is_missing: bool,
+    dir_a: Option<String>,
+    dir_b: Option<String>,
+    quiet: bool,
+    ignore_patterns: Vec<String>,
+    warn_duplicate_keys: bool,
+    intersection_only: bool,
+    compact_values: bool,
+    fail_on: Vec<DiffKind>,
+    check_args: bool,
+    no_color: bool,
+    numbers_as_text: bool,
+    array_key: Option<String>,
+    json_pointer: bool,
+    split_file: Option<String>,
+    verbose: bool,
+    context_size: usize,
+    interactive: bool,
+    only_patterns: Vec<String>,
+    ignore_globs: Vec<String>,
+    junit: Option<String>,
+    baseline: Option<String>,
+    theme: Theme,
+    no_dedup: bool,
+    print_summary: bool,
+    subtree_pointer: Option<String>,
+    plain: bool,
+    limit: Option<usize>,
+    swap: bool,
+    group_by_key: bool,
+    explain: bool,
+    self_compare_file: Option<String>,
+    self_compare_pointers: Option<(String, String)>,
+    watch: bool,
+    max_value_len: Option<usize>,
+    has_symbol: String,
+    misses_symbol: String,
+    json_patch: Option<String>,
+    normalize_keys: Option<KeyNormalization>,
+    stream: bool,
+    combine_type_value: bool,
+    round: Option<u32>,
+    save_format: SaveFormat,
+    include_equal: bool,
+    max_width: Option<usize>,
+    require_non_empty: bool,
+    ignore_types: Vec<ValueType>,
+    format_a: Option<DataFormat>,
+    format_b: Option<DataFormat>,
+    by_direction: bool,
+    abbreviate_keys: bool,
+    git_file: Option<String>,
+    git_revisions: Option<(String, String)>,
+    force: bool,
+    show_delta: bool,
+    pretty_save: bool,
+    thousands_sep: Option<String>,
+    expand_env: Option<EnvExpansion>,
+    strict: bool,
+    smart_dates: bool,
+    min_severity: Option<Severity>,
+    severity_overrides: Vec<SeverityOverride>,
+    count_only: bool,
+    stacked: bool,
+    max_array_diffs: Option<usize>,
+    empty_equals_null: bool,
+    output_dir: Option<String>,
+    side_by_side: bool,
+    min_array_size: Option<usize>,
+    read_retry: usize,
+    yaml: Option<String>,
+    only_changed_files: bool,
+    key_separator: Option<String>,
+    timeout: Option<Duration>,
+    report_moves: bool,
 }
 
 impl ConfigBuilder {
@@ -166,6 +728,81 @@ impl ConfigBuilder {
             browser_view: None,
             printer_friendly: false,
             no_browser_show: false,
+            null_is_missing: false,
+            dir_a: None,
+            dir_b: None,
+            quiet: false,
+            ignore_patterns: vec![],
+            warn_duplicate_keys: false,
+            intersection_only: false,
+            compact_values: false,
+            fail_on: vec![],
+            check_args: false,
+            no_color: false,
+            numbers_as_text: false,
+            array_key: None,
+            json_pointer: false,
+            split_file: None,
+            verbose: false,
+            context_size: 0,
+            interactive: false,
+            only_patterns: vec![],
+            ignore_globs: vec![],
+            junit: None,
+            baseline: None,
+            theme: Theme::default(),
+            no_dedup: false,
+            print_summary: false,
+            subtree_pointer: None,
+            plain: false,
+            limit: None,
+            swap: false,
+            group_by_key: false,
+            explain: false,
+            self_compare_file: None,
+            self_compare_pointers: None,
+            watch: false,
+            max_value_len: None,
+            has_symbol: CHECKMARK.to_owned(),
+            misses_symbol: MULTIPLY.to_owned(),
+            json_patch: None,
+            normalize_keys: None,
+            stream: false,
+            combine_type_value: false,
+            round: None,
+            save_format: SaveFormat::Full,
+            include_equal: false,
+            max_width: None,
+            require_non_empty: false,
+            ignore_types: vec![],
+            format_a: None,
+            format_b: None,
+            by_direction: false,
+            abbreviate_keys: false,
+            git_file: None,
+            git_revisions: None,
+            force: false,
+            show_delta: false,
+            pretty_save: false,
+            thousands_sep: None,
+            expand_env: None,
+            strict: false,
+            smart_dates: false,
+            min_severity: None,
+            severity_overrides: vec![],
+            count_only: false,
+            stacked: false,
+            max_array_diffs: None,
+            empty_equals_null: false,
+            output_dir: None,
+            side_by_side: false,
+            min_array_size: None,
+            read_retry: 0,
+            yaml: None,
+            only_changed_files: false,
+            key_separator: None,
+            timeout: None,
+            report_moves: false,
         }
     }
 
@@ -249,6 +886,387 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn null_is_missing(mut self, null_is_missing: bool) -> ConfigBuilder {
+        self.null_is_missing = null_is_missing;
+        self
+    }
+
+    pub fn dir_a(mut self, dir_a: Option<String>) -> ConfigBuilder {
+        self.dir_a = dir_a;
+        self
+    }
+
+    pub fn dir_b(mut self, dir_b: Option<String>) -> ConfigBuilder {
+        self.dir_b = dir_b;
+        self
+    }
+
+    pub fn quiet(mut self, quiet: bool) -> ConfigBuilder {
+        self.quiet = quiet;
+        self
+    }
+
+    pub fn ignore_patterns(mut self, ignore_patterns: Vec<String>) -> ConfigBuilder {
+        self.ignore_patterns = ignore_patterns;
+        self
+    }
+
+    pub fn warn_duplicate_keys(mut self, warn_duplicate_keys: bool) -> ConfigBuilder {
+        self.warn_duplicate_keys = warn_duplicate_keys;
+        self
+    }
+
+    pub fn intersection_only(mut self, intersection_only: bool) -> ConfigBuilder {
+        self.intersection_only = intersection_only;
+        self
+    }
+
+    pub fn compact_values(mut self, compact_values: bool) -> ConfigBuilder {
+        self.compact_values = compact_values;
+        self
+    }
+
+    pub fn fail_on(mut self, fail_on: Vec<DiffKind>) -> ConfigBuilder {
+        self.fail_on = fail_on;
+        self
+    }
+
+    pub fn check_args(mut self, check_args: bool) -> ConfigBuilder {
+        self.check_args = check_args;
+        self
+    }
+
+    pub fn no_color(mut self, no_color: bool) -> ConfigBuilder {
+        self.no_color = no_color;
+        self
+    }
+
+    pub fn numbers_as_text(mut self, numbers_as_text: bool) -> ConfigBuilder {
+        self.numbers_as_text = numbers_as_text;
+        self
+    }
+
+    pub fn array_key(mut self, array_key: Option<String>) -> ConfigBuilder {
+        self.array_key = array_key;
+        self
+    }
+
+    pub fn json_pointer(mut self, json_pointer: bool) -> ConfigBuilder {
+        self.json_pointer = json_pointer;
+        self
+    }
+
+    pub fn split_file(mut self, split_file: Option<String>) -> ConfigBuilder {
+        self.split_file = split_file;
+        self
+    }
+
+    pub fn verbose(mut self, verbose: bool) -> ConfigBuilder {
+        self.verbose = verbose;
+        self
+    }
+
+    pub fn context_size(mut self, context_size: usize) -> ConfigBuilder {
+        self.context_size = context_size;
+        self
+    }
+
+    pub fn interactive(mut self, interactive: bool) -> ConfigBuilder {
+        self.interactive = interactive;
+        self
+    }
+
+    pub fn only_patterns(mut self, only_patterns: Vec<String>) -> ConfigBuilder {
+        self.only_patterns = only_patterns;
+        self
+    }
+
+    pub fn ignore_globs(mut self, ignore_globs: Vec<String>) -> ConfigBuilder {
+        self.ignore_globs = ignore_globs;
+        self
+    }
+
+    pub fn junit(mut self, junit: Option<String>) -> ConfigBuilder {
+        self.junit = junit;
+        self
+    }
+
+    pub fn baseline(mut self, baseline: Option<String>) -> ConfigBuilder {
+        self.baseline = baseline;
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> ConfigBuilder {
+        self.theme = theme;
+        self
+    }
+
+    pub fn no_dedup(mut self, no_dedup: bool) -> ConfigBuilder {
+        self.no_dedup = no_dedup;
+        self
+    }
+
+    pub fn print_summary(mut self, print_summary: bool) -> ConfigBuilder {
+        self.print_summary = print_summary;
+        self
+    }
+
+    pub fn subtree_pointer(mut self, subtree_pointer: Option<String>) -> ConfigBuilder {
+        self.subtree_pointer = subtree_pointer;
+        self
+    }
+
+    pub fn plain(mut self, plain: bool) -> ConfigBuilder {
+        self.plain = plain;
+        self
+    }
+
+    pub fn limit(mut self, limit: Option<usize>) -> ConfigBuilder {
+        self.limit = limit;
+        self
+    }
+
+    pub fn swap(mut self, swap: bool) -> ConfigBuilder {
+        self.swap = swap;
+        self
+    }
+
+    pub fn group_by_key(mut self, group_by_key: bool) -> ConfigBuilder {
+        self.group_by_key = group_by_key;
+        self
+    }
+
+    pub fn explain(mut self, explain: bool) -> ConfigBuilder {
+        self.explain = explain;
+        self
+    }
+
+    pub fn self_compare_file(mut self, self_compare_file: Option<String>) -> ConfigBuilder {
+        self.self_compare_file = self_compare_file;
+        self
+    }
+
+    pub fn self_compare_pointers(
+        mut self,
+        self_compare_pointers: Option<(String, String)>,
+    ) -> ConfigBuilder {
+        self.self_compare_pointers = self_compare_pointers;
+        self
+    }
+
+    pub fn watch(mut self, watch: bool) -> ConfigBuilder {
+        self.watch = watch;
+        self
+    }
+
+    pub fn max_value_len(mut self, max_value_len: Option<usize>) -> ConfigBuilder {
+        self.max_value_len = max_value_len;
+        self
+    }
+
+    pub fn has_symbol(mut self, has_symbol: String) -> ConfigBuilder {
+        self.has_symbol = has_symbol;
+        self
+    }
+
+    pub fn misses_symbol(mut self, misses_symbol: String) -> ConfigBuilder {
+        self.misses_symbol = misses_symbol;
+        self
+    }
+
+    pub fn json_patch(mut self, json_patch: Option<String>) -> ConfigBuilder {
+        self.json_patch = json_patch;
+        self
+    }
+
+    pub fn normalize_keys(mut self, normalize_keys: Option<KeyNormalization>) -> ConfigBuilder {
+        self.normalize_keys = normalize_keys;
+        self
+    }
+
+    pub fn stream(mut self, stream: bool) -> ConfigBuilder {
+        self.stream = stream;
+        self
+    }
+
+    pub fn combine_type_value(mut self, combine_type_value: bool) -> ConfigBuilder {
+        self.combine_type_value = combine_type_value;
+        self
+    }
+
+    pub fn round(mut self, round: Option<u32>) -> ConfigBuilder {
+        self.round = round;
+        self
+    }
+
+    pub fn save_format(mut self, save_format: SaveFormat) -> ConfigBuilder {
+        self.save_format = save_format;
+        self
+    }
+
+    pub fn include_equal(mut self, include_equal: bool) -> ConfigBuilder {
+        self.include_equal = include_equal;
+        self
+    }
+
+    pub fn max_width(mut self, max_width: Option<usize>) -> ConfigBuilder {
+        self.max_width = max_width;
+        self
+    }
+
+    pub fn require_non_empty(mut self, require_non_empty: bool) -> ConfigBuilder {
+        self.require_non_empty = require_non_empty;
+        self
+    }
+
+    pub fn ignore_types(mut self, ignore_types: Vec<ValueType>) -> ConfigBuilder {
+        self.ignore_types = ignore_types;
+        self
+    }
+
+    pub fn format_a(mut self, format_a: Option<DataFormat>) -> ConfigBuilder {
+        self.format_a = format_a;
+        self
+    }
+
+    pub fn format_b(mut self, format_b: Option<DataFormat>) -> ConfigBuilder {
+        self.format_b = format_b;
+        self
+    }
+
+    pub fn by_direction(mut self, by_direction: bool) -> ConfigBuilder {
+        self.by_direction = by_direction;
+        self
+    }
+
+    pub fn abbreviate_keys(mut self, abbreviate_keys: bool) -> ConfigBuilder {
+        self.abbreviate_keys = abbreviate_keys;
+        self
+    }
+
+    pub fn git_file(mut self, git_file: Option<String>) -> ConfigBuilder {
+        self.git_file = git_file;
+        self
+    }
+
+    pub fn git_revisions(mut self, git_revisions: Option<(String, String)>) -> ConfigBuilder {
+        self.git_revisions = git_revisions;
+        self
+    }
+
+    pub fn force(mut self, force: bool) -> ConfigBuilder {
+        self.force = force;
+        self
+    }
+
+    pub fn show_delta(mut self, show_delta: bool) -> ConfigBuilder {
+        self.show_delta = show_delta;
+        self
+    }
+
+    pub fn pretty_save(mut self, pretty_save: bool) -> ConfigBuilder {
+        self.pretty_save = pretty_save;
+        self
+    }
+
+    pub fn thousands_sep(mut self, thousands_sep: Option<String>) -> ConfigBuilder {
+        self.thousands_sep = thousands_sep;
+        self
+    }
+
+    pub fn expand_env(mut self, expand_env: Option<EnvExpansion>) -> ConfigBuilder {
+        self.expand_env = expand_env;
+        self
+    }
+
+    pub fn strict(mut self, strict: bool) -> ConfigBuilder {
+        self.strict = strict;
+        self
+    }
+
+    pub fn smart_dates(mut self, smart_dates: bool) -> ConfigBuilder {
+        self.smart_dates = smart_dates;
+        self
+    }
+
+    pub fn min_severity(mut self, min_severity: Option<Severity>) -> ConfigBuilder {
+        self.min_severity = min_severity;
+        self
+    }
+
+    pub fn severity_overrides(
+        mut self,
+        severity_overrides: Vec<SeverityOverride>,
+    ) -> ConfigBuilder {
+        self.severity_overrides = severity_overrides;
+        self
+    }
+
+    pub fn count_only(mut self, count_only: bool) -> ConfigBuilder {
+        self.count_only = count_only;
+        self
+    }
+
+    pub fn stacked(mut self, stacked: bool) -> ConfigBuilder {
+        self.stacked = stacked;
+        self
+    }
+
+    pub fn max_array_diffs(mut self, max_array_diffs: Option<usize>) -> ConfigBuilder {
+        self.max_array_diffs = max_array_diffs;
+        self
+    }
+
+    pub fn empty_equals_null(mut self, empty_equals_null: bool) -> ConfigBuilder {
+        self.empty_equals_null = empty_equals_null;
+        self
+    }
+
+    pub fn output_dir(mut self, output_dir: Option<String>) -> ConfigBuilder {
+        self.output_dir = output_dir;
+        self
+    }
+
+    pub fn side_by_side(mut self, side_by_side: bool) -> ConfigBuilder {
+        self.side_by_side = side_by_side;
+        self
+    }
+
+    pub fn min_array_size(mut self, min_array_size: Option<usize>) -> ConfigBuilder {
+        self.min_array_size = min_array_size;
+        self
+    }
+
+    pub fn read_retry(mut self, read_retry: usize) -> ConfigBuilder {
+        self.read_retry = read_retry;
+        self
+    }
+
+    pub fn yaml(mut self, yaml: Option<String>) -> ConfigBuilder {
+        self.yaml = yaml;
+        self
+    }
+
+    pub fn only_changed_files(mut self, only_changed_files: bool) -> ConfigBuilder {
+        self.only_changed_files = only_changed_files;
+        self
+    }
+
+    pub fn key_separator(mut self, key_separator: Option<String>) -> ConfigBuilder {
+        self.key_separator = key_separator;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Option<Duration>) -> ConfigBuilder {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn report_moves(mut self, report_moves: bool) -> ConfigBuilder {
+        self.report_moves = report_moves;
+        self
+    }
+
     pub fn build(self) -> Config {
         Config {
             check_for_key_diffs: self.check_for_key_diffs,
@@ -267,6 +1285,81 @@ impl ConfigBuilder {
             browser_view: self.browser_view,
             printer_friendly: self.printer_friendly,
             no_browser_show: self.no_browser_show,
+            null_is_missing: self.null_is_missing,
+            dir_a: self.dir_a,
+            dir_b: self.dir_b,
+            quiet: self.quiet,
+            ignore_patterns: self.ignore_patterns,
+            warn_duplicate_keys: self.warn_duplicate_keys,
+            intersection_only: self.intersection_only,
+            compact_values: self.compact_values,
+            fail_on: self.fail_on,
+            check_args: self.check_args,
+            no_color: self.no_color,
+            numbers_as_text: self.numbers_as_text,
+            array_key: self.array_key,
+            json_pointer: self.json_pointer,
+            split_file: self.split_file,
+            verbose: self.verbose,
+            context_size: self.context_size,
+            interactive: self.interactive,
+            only_patterns: self.only_patterns,
+            ignore_globs: self.ignore_globs,
+            junit: self.junit,
+            baseline: self.baseline,
+            theme: self.theme,
+            no_dedup: self.no_dedup,
+            print_summary: self.print_summary,
+            subtree_pointer: self.subtree_pointer,
+            plain: self.plain,
+            limit: self.limit,
+            swap: self.swap,
+            group_by_key: self.group_by_key,
+            explain: self.explain,
+            self_compare_file: self.self_compare_file,
+            self_compare_pointers: self.self_compare_pointers,
+            watch: self.watch,
+            max_value_len: self.max_value_len,
+            has_symbol: self.has_symbol,
+            misses_symbol: self.misses_symbol,
+            json_patch: self.json_patch,
+            normalize_keys: self.normalize_keys,
+            stream: self.stream,
+            combine_type_value: self.combine_type_value,
+            round: self.round,
+            save_format: self.save_format,
+            include_equal: self.include_equal,
+            max_width: self.max_width,
+            require_non_empty: self.require_non_empty,
+            ignore_types: self.ignore_types,
+            format_a: self.format_a,
+            format_b: self.format_b,
+            by_direction: self.by_direction,
+            abbreviate_keys: self.abbreviate_keys,
+            git_file: self.git_file,
+            git_revisions: self.git_revisions,
+            force: self.force,
+            show_delta: self.show_delta,
+            pretty_save: self.pretty_save,
+            thousands_sep: self.thousands_sep,
+            expand_env: self.expand_env,
+            strict: self.strict,
+            smart_dates: self.smart_dates,
+            min_severity: self.min_severity,
+            severity_overrides: self.severity_overrides,
+            count_only: self.count_only,
+            stacked: self.stacked,
+            max_array_diffs: self.max_array_diffs,
+            empty_equals_null: self.empty_equals_null,
+            output_dir: self.output_dir,
+            side_by_side: self.side_by_side,
+            min_array_size: self.min_array_size,
+            read_retry: self.read_retry,
+            yaml: self.yaml,
+            only_changed_files: self.only_changed_files,
+            key_separator: self.key_separator,
+            timeout: self.timeout,
+            report_moves: self.report_moves,
         }
     }
 }
@@ -292,11 +1385,23 @@ impl WorkingContext {
         let file_name_b = self.lib_working_context.file_b.name.as_str();
         (file_name_a, file_name_b)
     }
+
+    /// Swaps the two files, so the first file becomes the second and the other way around
+    pub fn swap_files(&mut self) {
+        std::mem::swap(
+            &mut self.lib_working_context.file_a,
+            &mut self.lib_working_context.file_b,
+        );
+        std::mem::swap(&mut self.config.file_a, &mut self.config.file_b);
+    }
 }
 
 /// How a WorkingContext gets stored on disk
 #[derive(Serialize, Deserialize)]
 pub struct SavedContext {
+    /// Missing from the files saved before the version was introduced
+    #[serde(default)]
+    pub version: u32,
     pub key_diff: Vec<KeyDiff>,
     pub type_diff: Vec<TypeDiff>,
     pub value_diff: Vec<ValueDiff>,
@@ -313,6 +1418,7 @@ impl SavedContext {
         config: SavedConfig,
     ) -> SavedContext {
         SavedContext {
+            version: SAVED_FILE_VERSION,
             key_diff,
             type_diff,
             value_diff,
@@ -322,12 +1428,158 @@ impl SavedContext {
     }
 }
 
+/// The value of the `format` field marking files saved in the compact format
+pub const COMPACT_SAVE_FORMAT: &str = "compact";
+
+/// How a WorkingContext gets stored on disk in the compact format.
+/// Every difference is stored as an array of its fields, in the order they are declared in, and kinds without differences are left out.
+#[derive(Serialize, Deserialize)]
+pub struct CompactSavedContext {
+    pub version: u32,
+    pub format: String,
+    #[serde(rename = "k", default, skip_serializing_if = "Vec::is_empty")]
+    pub key_diff: Vec<(String, String, String)>,
+    #[serde(rename = "t", default, skip_serializing_if = "Vec::is_empty")]
+    pub type_diff: Vec<(String, String, String)>,
+    #[serde(rename = "v", default, skip_serializing_if = "Vec::is_empty")]
+    pub value_diff: Vec<(String, String, String)>,
+    #[serde(rename = "a", default, skip_serializing_if = "Vec::is_empty")]
+    pub array_diff: Vec<(String, ArrayDiffDesc, String)>,
+    #[serde(rename = "c")]
+    pub config: CompactSavedConfig,
+}
+
+/// How a SavedConfig gets stored on disk in the compact format
+#[derive(Serialize, Deserialize)]
+pub struct CompactSavedConfig {
+    #[serde(rename = "k")]
+    pub check_for_key_diffs: bool,
+    #[serde(rename = "t")]
+    pub check_for_type_diffs: bool,
+    #[serde(rename = "v")]
+    pub check_for_value_diffs: bool,
+    #[serde(rename = "a")]
+    pub check_for_array_diffs: bool,
+    #[serde(rename = "fa")]
+    pub file_a: String,
+    #[serde(rename = "fb")]
+    pub file_b: String,
+    #[serde(rename = "o")]
+    pub array_same_order: bool,
+}
+
+impl From<SavedContext> for CompactSavedContext {
+    fn from(saved: SavedContext) -> CompactSavedContext {
+        let config = saved.config;
+        CompactSavedContext {
+            version: saved.version,
+            format: COMPACT_SAVE_FORMAT.to_owned(),
+            key_diff: saved
+                .key_diff
+                .into_iter()
+                .map(|kd| (kd.key, kd.has, kd.misses))
+                .collect(),
+            type_diff: saved
+                .type_diff
+                .into_iter()
+                .map(|td| (td.key, td.type1, td.type2))
+                .collect(),
+            value_diff: saved
+                .value_diff
+                .into_iter()
+                .map(|vd| (vd.key, vd.value1, vd.value2))
+                .collect(),
+            array_diff: saved
+                .array_diff
+                .into_iter()
+                .map(|ad| (ad.key, ad.descriptor, ad.value))
+                .collect(),
+            config: CompactSavedConfig {
+                check_for_key_diffs: config.check_for_key_diffs,
+                check_for_type_diffs: config.check_for_type_diffs,
+                check_for_value_diffs: config.check_for_value_diffs,
+                check_for_array_diffs: config.check_for_array_diffs,
+                file_a: config.file_a,
+                file_b: config.file_b,
+                array_same_order: config.array_same_order,
+            },
+        }
+    }
+}
+
+impl From<CompactSavedContext> for SavedContext {
+    fn from(compact: CompactSavedContext) -> SavedContext {
+        let config = compact.config;
+        SavedContext {
+            version: compact.version,
+            key_diff: compact
+                .key_diff
+                .into_iter()
+                .map(|(key, has, misses)| KeyDiff { key, has, misses })
+                .collect(),
+            type_diff: compact
+                .type_diff
+                .into_iter()
+                .map(|(key, type1, type2)| TypeDiff { key, type1, type2 })
+                .collect(),
+            value_diff: compact
+                .value_diff
+                .into_iter()
+                .map(|(key, value1, value2)| ValueDiff {
+                    key,
+                    value1,
+                    value2,
+                })
+                .collect(),
+            array_diff: compact
+                .array_diff
+                .into_iter()
+                .map(|(key, descriptor, value)| ArrayDiff {
+                    key,
+                    descriptor,
+                    value,
+                })
+                .collect(),
+            config: SavedConfig {
+                check_for_key_diffs: config.check_for_key_diffs,
+                check_for_type_diffs: config.check_for_type_diffs,
+                check_for_value_diffs: config.check_for_value_diffs,
+                check_for_array_diffs: config.check_for_array_diffs,
+                file_a: config.file_a,
+                file_b: config.file_b,
+                array_same_order: config.array_same_order,
+            },
+        }
+    }
+}
+
 /// Custom Error type
 #[derive(Debug)]
 pub enum DtfError {
     IoError(std::io::Error),
     DiffError(String),
-    GeneralError(Box<DtfError>),
+    ParseError { file: String, message: String },
+    FileNotFound(String),
+    EmptyFile(String),
+}
+
+impl DtfError {
+    /// Creates the error of a file, that could not be parsed
+    pub fn parse_error(file: &str, message: impl fmt::Display) -> DtfError {
+        DtfError::ParseError {
+            file: file.to_owned(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Creates the error of a file, that could not be read. Missing files get their own error
+    pub fn read_error(file: &str, err: std::io::Error) -> DtfError {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            DtfError::FileNotFound(file.to_owned())
+        } else {
+            DtfError::IoError(err)
+        }
+    }
 }
 
 impl fmt::Display for DtfError {
@@ -335,9 +1587,91 @@ impl fmt::Display for DtfError {
         match self {
             DtfError::IoError(err) => write!(f, "IO error: {}", err),
             DtfError::DiffError(msg) => write!(f, "Diff error: {}", msg),
-            DtfError::GeneralError(err) => write!(f, "General error happened {}", err),
+            DtfError::ParseError { file, message } => {
+                write!(f, "Could not parse {}: {}", file, message)
+            }
+            DtfError::FileNotFound(file) => write!(f, "File not found: {}", file),
+            DtfError::EmptyFile(file) => write!(f, "{} has no data to check", file),
         }
     }
 }
 
 impl Error for DtfError {}
+
+impl From<std::io::Error> for DtfError {
+    fn from(err: std::io::Error) -> DtfError {
+        DtfError::IoError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libdtf::core::diff_types::ArrayDiffDesc;
+
+    use crate::utils::create_working_context;
+
+    use super::*;
+
+    #[test]
+    fn test_narrow_tables_have_narrow_columns() {
+        let table_width = |max_width| {
+            let context = create_working_context(
+                &ConfigBuilder::new()
+                    .file_a(Some("a.json".to_owned()))
+                    .file_b(Some("b.json".to_owned()))
+                    .check_for_key_diffs(true)
+                    .max_width(Some(max_width))
+                    .build(),
+            )
+            .unwrap();
+            TableContext::new(&context).table.max_column_width
+        };
+
+        assert!(table_width(60) < table_width(200));
+    }
+
+    #[test]
+    fn test_iter_diffs() {
+        let collection: DiffCollection = (
+            Some(vec![KeyDiff {
+                key: "employment.employer".to_owned(),
+                has: "a.json".to_owned(),
+                misses: "b.json".to_owned(),
+            }]),
+            None,
+            Some(vec![
+                ValueDiff {
+                    key: "name".to_owned(),
+                    value1: "Sarah".to_owned(),
+                    value2: "John".to_owned(),
+                },
+                ValueDiff {
+                    key: "age".to_owned(),
+                    value1: "30".to_owned(),
+                    value2: "31".to_owned(),
+                },
+            ]),
+            Some(vec![ArrayDiff {
+                key: "email_addresses".to_owned(),
+                descriptor: ArrayDiffDesc::AHas,
+                value: "sarah.smith@example.com".to_owned(),
+            }]),
+        );
+
+        let diffs = iter_diffs(&collection).collect::<Vec<AnyDiff>>();
+
+        assert_eq!(
+            diffs.iter().map(AnyDiff::key).collect::<Vec<&str>>(),
+            vec!["employment.employer", "name", "age", "email_addresses"]
+        );
+        assert!(matches!(diffs[0], AnyDiff::Key(_)));
+        assert!(matches!(diffs[1], AnyDiff::Value(_)));
+        assert!(matches!(diffs[3], AnyDiff::Array(_)));
+        assert_eq!(
+            iter_diffs(&collection)
+                .filter(|diff| matches!(diff, AnyDiff::Value(_)))
+                .count(),
+            2
+        );
+    }
+}
@@ -1,11 +1,84 @@
-use std::{error::Error, fs::File, io::BufReader};
+#[cfg(feature = "git")]
+use std::process::Command;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    fs::File,
+    io::{self, BufReader, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex, PoisonError},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use flate2::read::GzDecoder;
 
-use libdtf::{core::diff_types::WorkingFile, json::read_json_file, yaml::read_yaml_file};
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
 
 use crate::dtfterminal_types::{
-    Config, ConfigBuilder, DiffCollection, DtfError, LibConfig, LibWorkingContext, SavedConfig,
-    SavedContext, WorkingContext,
+    CompactSavedContext, Config, ConfigBuilder, DataFormat, DiffCollection, DtfError, SaveFormat,
+    SavedConfig, SavedContext, Theme, WorkingContext, COMPACT_SAVE_FORMAT, SAVED_FILE_VERSION,
+};
+use crate::preprocessor::yaml_to_json;
+use crate::utils::{
+    create_working_context, get_data_format, get_git_revision_name, is_gzip_file, is_url,
+    is_yaml_file,
 };
+#[cfg(feature = "xml")]
+use crate::xml_converter::xml_to_json;
+
+type JsonMap = serde_json::Map<String, serde_json::Value>;
+
+/// The byte order mark some tools put at the start of UTF-8 files
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// The pause before reading a file again the first time, doubled before every further attempt
+const READ_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// The longest pause between two attempts of reading a file, so the pauses stop doubling
+const MAX_READ_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// The most attempts `--read-retry` can make, so a file, that never becomes readable, doesn't hold up the app for long
+pub(crate) const MAX_READ_RETRIES: usize = 20;
+
+/// The baselines loaded so far. In batch mode every pair is checked against the same baseline, which only needs to be parsed once
+static BASELINE_CACHE: LazyLock<Mutex<FileCache<DiffCollection>>> =
+    LazyLock::new(|| Mutex::new(FileCache::new()));
+
+/// Holds the data of parsed files by their path, so a file read multiple times is only parsed once.
+/// The modification time of the file is stored with its data, and a file changed since it was parsed is parsed again.
+pub(crate) struct FileCache<T> {
+    entries: HashMap<PathBuf, (SystemTime, T)>,
+}
+
+impl<T: Clone> FileCache<T> {
+    pub(crate) fn new() -> FileCache<T> {
+        FileCache {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Gets the data of the file, parsing it with `load` if it's not cached yet, or it changed since it was cached
+    pub(crate) fn get_or_load(
+        &mut self,
+        file_path: &str,
+        load: impl FnOnce(&str) -> Result<T, DtfError>,
+    ) -> Result<T, DtfError> {
+        let modified = fs::metadata(file_path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| DtfError::read_error(file_path, e))?;
+        let path = PathBuf::from(file_path);
+        if let Some((cached_modified, data)) = self.entries.get(&path) {
+            if *cached_modified == modified {
+                return Ok(data.clone());
+            }
+        }
+
+        let data = load(file_path)?;
+        self.entries.insert(path, (modified, data.clone()));
+        Ok(data)
+    }
+}
 
 /// Responsible for reading and writing files
 pub struct FileHandler {
@@ -22,19 +95,203 @@ impl FileHandler {
     }
 
     /// Reads a JSON file and returns a map of the data
-    pub fn read_json_file(
-        file_path: &str,
-    ) -> Result<serde_json::Map<String, serde_json::Value>, serde_json::Error> {
-        read_json_file(file_path)
+    pub fn read_json_file(file_path: &str) -> Result<JsonMap, DtfError> {
+        parse_json(&read_data_file(file_path)?, file_path)
+    }
+
+    /// Reads the JSON data of a file, or fetches it if the path is an HTTP(S) URL
+    pub fn read_json_data(path: &str) -> Result<JsonMap, DtfError> {
+        if is_url(path) {
+            return fetch_json(path);
+        }
+
+        FileHandler::read_json_file(path)
+    }
+
+    /// Reads the data of a file in the given format, converted to a JSON map if it's in another format.
+    /// `array_key` is only used by CSV files, see `read_csv_file`.
+    pub fn read_data_as_json(
+        path: &str,
+        format: DataFormat,
+        array_key: Option<&str>,
+    ) -> Result<JsonMap, DtfError> {
+        match format {
+            DataFormat::Json => FileHandler::read_json_data(path),
+            DataFormat::Jsonl => FileHandler::read_json_lines_file(path),
+            DataFormat::Yaml => yaml_to_json(&FileHandler::read_yaml_file(path)?),
+            DataFormat::Csv => FileHandler::read_csv_file(path, array_key),
+            DataFormat::Xml => FileHandler::read_xml_file(path),
+            DataFormat::Json5 => parse_data_as_json(&read_data_bytes(path)?, path, format, None),
+            DataFormat::MessagePack => {
+                msgpack_to_json(&read_data_bytes(path)?).map_err(|e| DtfError::parse_error(path, e))
+            }
+        }
+    }
+
+    /// Reads the data of a file at a revision of the git repository it is in, like `git show <revision>:<path>` would.
+    /// The format of the data is decided by the extension of the file.
+    pub fn read_git_revision(
+        revision: &str,
+        path: &str,
+        array_key: Option<&str>,
+    ) -> Result<JsonMap, DtfError> {
+        let content = git_show(revision, path)?;
+        parse_data_as_json(
+            &content,
+            &get_git_revision_name(path, revision),
+            get_data_format(path),
+            array_key,
+        )
+    }
+
+    /// Reads an XML file and returns its data converted to a JSON map
+    pub fn read_xml_file(file_path: &str) -> Result<JsonMap, DtfError> {
+        parse_data_as_json(
+            &read_data_bytes(file_path)?,
+            file_path,
+            DataFormat::Xml,
+            None,
+        )
+    }
+
+    /// Reads a CSV file, using the header row as the field names of the records.
+    /// Every record is stored under a `row N` key, N being the number of the record, not counting the header row.
+    /// If `array_key` names a field, that has a different value in every record, the records are stored under `[array_key=value]` keys instead,
+    /// so the records of both files are matched by the field.
+    pub fn read_csv_file(file_path: &str, array_key: Option<&str>) -> Result<JsonMap, DtfError> {
+        parse_csv(&read_data_file(file_path)?, file_path, array_key)
+    }
+
+    /// Finds the keys, that are present multiple times in the same object of a JSON file.
+    /// The keys are returned with their full path, in the order they were found.
+    pub fn find_duplicate_json_keys(file_path: &str) -> Result<Vec<String>, DtfError> {
+        let content = read_data_file(file_path)?;
+        let mut deserializer = serde_json::Deserializer::from_str(&content);
+        let mut duplicate_keys = vec![];
+        DuplicateKeyFinder {
+            path: String::new(),
+            duplicate_keys: &mut duplicate_keys,
+        }
+        .deserialize(&mut deserializer)
+        .map_err(|e| DtfError::parse_error(file_path, e))?;
+        Ok(duplicate_keys)
     }
 
     /// Reads a YAML file and returns a map of the data
-    pub fn read_yaml_file(file_path: &str) -> Result<serde_yaml::Mapping, serde_yaml::Error> {
-        read_yaml_file(file_path)
+    pub fn read_yaml_file(file_path: &str) -> Result<serde_yaml::Mapping, DtfError> {
+        serde_yaml::from_str(&read_data_file(file_path)?)
+            .map_err(|e| DtfError::parse_error(file_path, e))
+    }
+
+    /// Reads a JSON Lines file and returns its records under `line N` keys, N being the number of the record.
+    /// Empty lines are skipped, so the number only matches the line number if the file has no empty lines.
+    pub fn read_json_lines_file(file_path: &str) -> Result<JsonMap, DtfError> {
+        parse_json_lines(&read_data_file(file_path)?, file_path)
+    }
+
+    /// Reads a JSON file holding an array of exactly two objects and returns the objects
+    pub fn read_json_split_file(file_path: &str) -> Result<(JsonMap, JsonMap), DtfError> {
+        let data: serde_json::Value = parse_json(&read_data_file(file_path)?, file_path)?;
+        match data {
+            serde_json::Value::Array(values) => match <[serde_json::Value; 2]>::try_from(values) {
+                Ok([serde_json::Value::Object(data1), serde_json::Value::Object(data2)]) => {
+                    Ok((data1, data2))
+                }
+                _ => Err(split_file_error(file_path)),
+            },
+            _ => Err(split_file_error(file_path)),
+        }
+    }
+
+    /// Reads a YAML file holding a sequence of exactly two mappings and returns the mappings
+    pub fn read_yaml_split_file(
+        file_path: &str,
+    ) -> Result<(serde_yaml::Mapping, serde_yaml::Mapping), DtfError> {
+        let data: serde_yaml::Value = serde_yaml::from_str(&read_data_file(file_path)?)
+            .map_err(|e| DtfError::parse_error(file_path, e))?;
+        match data {
+            serde_yaml::Value::Sequence(values) => match <[serde_yaml::Value; 2]>::try_from(values)
+            {
+                Ok([serde_yaml::Value::Mapping(data1), serde_yaml::Value::Mapping(data2)]) => {
+                    Ok((data1, data2))
+                }
+                _ => Err(split_file_error(file_path)),
+            },
+            _ => Err(split_file_error(file_path)),
+        }
+    }
+
+    /// Reads the regular expressions to ignore from a file.
+    /// Every line is an expression, empty lines and lines starting with `#` are skipped.
+    pub fn read_ignore_file(file_path: &str) -> Result<Vec<String>, DtfError> {
+        let content =
+            fs::read_to_string(file_path).map_err(|e| DtfError::read_error(file_path, e))?;
+        Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_owned)
+            .collect())
+    }
+
+    /// Reads a theme from a JSON or YAML file, based on the file extension.
+    /// Colors missing from the file keep their default.
+    pub fn read_theme_file(file_path: &str) -> Result<Theme, DtfError> {
+        let content =
+            fs::read_to_string(file_path).map_err(|e| DtfError::read_error(file_path, e))?;
+        let theme = if is_yaml_file(file_path) {
+            serde_yaml::from_str(&content).map_err(|e| e.to_string())
+        } else {
+            serde_json::from_str(&content).map_err(|e| e.to_string())
+        };
+        theme.map_err(|e| DtfError::parse_error(file_path, e))
     }
 
     /// Writes the diff results to a JSON file
     pub fn write_to_file(&self, diffs: DiffCollection) -> Result<(), DtfError> {
+        let config = &self.user_config;
+        if config.write_to_file.is_none() {
+            panic!("File write path is missing!")
+        }
+        let saved_context = self.create_saved_context(diffs);
+
+        let path = Path::new(config.write_to_file.as_ref().unwrap());
+        check_write_target(path, config.force)?;
+        write_atomically(path, |file| {
+            match (config.save_format, config.pretty_save) {
+                (SaveFormat::Full, false) => serde_json::to_writer(file, &saved_context),
+                (SaveFormat::Full, true) => serde_json::to_writer_pretty(file, &saved_context),
+                (SaveFormat::Compact, false) => {
+                    serde_json::to_writer(file, &CompactSavedContext::from(saved_context))
+                }
+                (SaveFormat::Compact, true) => {
+                    serde_json::to_writer_pretty(file, &CompactSavedContext::from(saved_context))
+                }
+            }
+            .map_err(|e| DtfError::IoError(e.into()))
+        })
+    }
+
+    /// Writes the diff results to a YAML file, with the same content as the JSON file, so it can be read with `-r` as well
+    pub fn write_yaml_file(&self, diffs: DiffCollection, path: &str) -> Result<(), DtfError> {
+        let config = &self.user_config;
+        let saved_context = self.create_saved_context(diffs);
+
+        let path = Path::new(path);
+        check_write_target(path, config.force)?;
+        write_atomically(path, |file| {
+            match config.save_format {
+                SaveFormat::Full => serde_yaml::to_writer(file, &saved_context),
+                SaveFormat::Compact => {
+                    serde_yaml::to_writer(file, &CompactSavedContext::from(saved_context))
+                }
+            }
+            .map_err(|e| DtfError::DiffError(e.to_string()))
+        })
+    }
+
+    /// Creates the content of a saved file from the differences and the checks of the run
+    fn create_saved_context(&self, diffs: DiffCollection) -> SavedContext {
         let (key_diff_option, type_diff_option, value_diff_option, array_diff_option) = diffs;
         let key_diff = key_diff_option.unwrap_or_default();
         let type_diff = type_diff_option.unwrap_or_default();
@@ -42,100 +299,1229 @@ impl FileHandler {
         let array_diff = array_diff_option.unwrap_or_default();
 
         let config = &self.user_config;
-        if config.write_to_file.is_none() {
-            panic!("File write path is missing!")
-        }
-        let file = File::create(config.write_to_file.as_ref().unwrap());
-
-        match serde_json::to_writer(
-            &mut file.unwrap(),
-            &SavedContext::new(
-                key_diff,
-                type_diff,
-                value_diff,
-                array_diff,
-                SavedConfig::new(
-                    config.check_for_key_diffs,
-                    config.check_for_type_diffs,
-                    config.check_for_value_diffs,
-                    config.check_for_array_diffs,
-                    config.file_a.clone().unwrap(),
-                    config.file_b.clone().unwrap(),
-                    config.array_same_order,
-                ),
+        SavedContext::new(
+            key_diff,
+            type_diff,
+            value_diff,
+            array_diff,
+            SavedConfig::new(
+                config.check_for_key_diffs,
+                config.check_for_type_diffs,
+                config.check_for_value_diffs,
+                config.check_for_array_diffs,
+                config.file_a.clone().unwrap(),
+                config.file_b.clone().unwrap(),
+                config.array_same_order,
             ),
-        ) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(DtfError::IoError(e.into())),
-        }
+        )
     }
 
     /// Loads the saved results from a JSON file
-    pub fn load_saved_results(
-        &mut self,
-    ) -> Result<(DiffCollection, WorkingContext), Box<dyn Error>> {
-        let saved_data = match self.read_from_file(&self.user_config.read_from_file) {
-            Ok(data) => data,
-            Err(e) => return Err(Box::new(DtfError::IoError(e.into()))),
-        };
+    pub fn load_saved_results(&mut self) -> Result<(DiffCollection, WorkingContext), DtfError> {
+        let saved_data = FileHandler::read_from_file(&self.user_config.read_from_file)?;
         self.saved_config = Some(saved_data.config);
 
+        let working_context = self.build_working_context_from_loaded_data()?;
+        let config = &working_context.config;
         let diff_collection = (
-            Some(saved_data.key_diff),
-            Some(saved_data.type_diff),
-            Some(saved_data.value_diff),
-            Some(saved_data.array_diff),
+            config.check_for_key_diffs.then_some(saved_data.key_diff),
+            config.check_for_type_diffs.then_some(saved_data.type_diff),
+            config
+                .check_for_value_diffs
+                .then_some(saved_data.value_diff),
+            config
+                .check_for_array_diffs
+                .then_some(saved_data.array_diff),
         );
 
-        let working_context = self.build_working_context_from_loaded_data();
-
         Ok((diff_collection, working_context))
     }
 
-    /// Builds a working context object based on the loaded data
-    fn build_working_context_from_loaded_data(&self) -> WorkingContext {
+    /// Loads the differences of a saved file to compare the current differences against.
+    /// The file is only parsed again if it changed since it was last loaded.
+    pub fn load_baseline(file_path: &str) -> Result<DiffCollection, DtfError> {
+        BASELINE_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_load(file_path, |path| {
+                let saved_data = FileHandler::read_from_file(path)?;
+                Ok((
+                    Some(saved_data.key_diff),
+                    Some(saved_data.type_diff),
+                    Some(saved_data.value_diff),
+                    Some(saved_data.array_diff),
+                ))
+            })
+    }
+
+    /// Builds a working context object based on the loaded data.
+    /// If the user selects kinds of differences on the command line, only those of the saved kinds are loaded, otherwise every saved kind is.
+    /// Fails if none of the checks are turned on, e.g. because the file was edited by hand.
+    fn build_working_context_from_loaded_data(&self) -> Result<WorkingContext, DtfError> {
         if self.saved_config.is_none() {
             panic!("Saved data is corrupted! Config options not present!")
         }
 
         let saved_config = self.saved_config.as_ref().unwrap();
         let user_config = &self.user_config;
+        let any_check_given = user_config.check_for_key_diffs
+            || user_config.check_for_type_diffs
+            || user_config.check_for_value_diffs
+            || user_config.check_for_array_diffs;
+        let is_checked = |saved: bool, given: bool| saved && (given || !any_check_given);
 
-        let file_a = WorkingFile::new(saved_config.file_a.clone());
-        let file_b = WorkingFile::new(saved_config.file_b.clone());
-        let lib_working_context = LibWorkingContext::new(
-            file_a,
-            file_b,
-            LibConfig::new(saved_config.array_same_order),
-        );
-        WorkingContext::new(
-            lib_working_context,
-            ConfigBuilder::new()
-                .check_for_key_diffs(saved_config.check_for_key_diffs)
-                .check_for_type_diffs(saved_config.check_for_type_diffs)
-                .check_for_value_diffs(saved_config.check_for_value_diffs)
-                .check_for_array_diffs(saved_config.check_for_array_diffs)
+        create_working_context(
+            &ConfigBuilder::new()
+                .check_for_key_diffs(is_checked(
+                    saved_config.check_for_key_diffs,
+                    user_config.check_for_key_diffs,
+                ))
+                .check_for_type_diffs(is_checked(
+                    saved_config.check_for_type_diffs,
+                    user_config.check_for_type_diffs,
+                ))
+                .check_for_value_diffs(is_checked(
+                    saved_config.check_for_value_diffs,
+                    user_config.check_for_value_diffs,
+                ))
+                .check_for_array_diffs(is_checked(
+                    saved_config.check_for_array_diffs,
+                    user_config.check_for_array_diffs,
+                ))
                 .render_key_diffs(user_config.render_key_diffs)
                 .render_type_diffs(user_config.render_type_diffs)
                 .render_value_diffs(user_config.render_value_diffs)
                 .render_array_diffs(user_config.render_array_diffs)
                 .read_from_file(user_config.read_from_file.clone())
                 .write_to_file(user_config.write_to_file.clone())
+                .yaml(user_config.yaml.clone())
                 .file_a(Some(saved_config.file_a.clone()))
                 .file_b(Some(saved_config.file_b.clone()))
                 .array_same_order(saved_config.array_same_order)
                 .browser_view(user_config.browser_view.clone())
+                .output_dir(user_config.output_dir.clone())
                 .printer_friendly(user_config.printer_friendly)
                 .no_browser_show(user_config.no_browser_show)
+                .quiet(user_config.quiet)
+                .compact_values(user_config.compact_values)
+                .fail_on(user_config.fail_on.clone())
+                .verbose(user_config.verbose)
+                .interactive(user_config.interactive)
+                .theme(user_config.theme.clone())
+                .has_symbol(user_config.has_symbol.clone())
+                .misses_symbol(user_config.misses_symbol.clone())
+                .max_width(user_config.max_width)
+                .abbreviate_keys(user_config.abbreviate_keys)
+                .force(user_config.force)
+                .show_delta(user_config.show_delta)
+                .thousands_sep(user_config.thousands_sep.clone())
+                .min_severity(user_config.min_severity)
+                .severity_overrides(user_config.severity_overrides.clone())
+                .count_only(user_config.count_only)
+                .stacked(user_config.stacked)
+                .max_array_diffs(user_config.max_array_diffs)
                 .build(),
         )
     }
 
-    /// Reads the saved results from a JSON file
-    fn read_from_file(&self, file_path: &str) -> serde_json::Result<SavedContext> {
-        let file =
-            File::open(file_path).unwrap_or_else(|_| panic!("Could not open file {}", file_path));
+    /// Reads the saved results from a JSON or a YAML file, saved in either the full or the compact format.
+    /// The version is checked first, so files of newer versions get rejected before their content is parsed.
+    fn read_from_file(file_path: &str) -> Result<SavedContext, DtfError> {
+        let file = File::open(file_path).map_err(|e| DtfError::read_error(file_path, e))?;
         let reader = BufReader::new(file);
-        serde_json::from_reader(reader)
+        let data: serde_json::Value = if is_yaml_file(file_path) {
+            serde_yaml::from_reader::<_, serde_yaml::Value>(reader)
+                .map_err(|e| DtfError::parse_error(file_path, e))
+                .and_then(|data| {
+                    serde_json::to_value(data).map_err(|e| DtfError::parse_error(file_path, e))
+                })?
+        } else {
+            serde_json::from_reader(reader).map_err(|e| DtfError::parse_error(file_path, e))?
+        };
+
+        // Files saved before the version was introduced count as version 0, which has the same content as version 1
+        let version = data
+            .get("version")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(0);
+        if version > u64::from(SAVED_FILE_VERSION) {
+            return Err(DtfError::parse_error(
+                file_path,
+                format!("saved file version {} is not supported", version),
+            ));
+        }
+
+        let is_compact =
+            data.get("format").and_then(serde_json::Value::as_str) == Some(COMPACT_SAVE_FORMAT);
+        if is_compact {
+            serde_json::from_value::<CompactSavedContext>(data)
+                .map(SavedContext::from)
+                .map_err(|e| DtfError::parse_error(file_path, e))
+        } else {
+            serde_json::from_value(data).map_err(|e| DtfError::parse_error(file_path, e))
+        }
+    }
+}
+
+/// Reads data, trying again up to `retries` times if it can't be read or parsed, e.g. because another process is still writing the file.
+/// The pause between the attempts doubles every time, up to two seconds. Missing files are not waited for.
+pub(crate) fn read_with_retry<T>(
+    retries: usize,
+    mut read: impl FnMut() -> Result<T, DtfError>,
+) -> Result<T, DtfError> {
+    let mut delay = READ_RETRY_DELAY;
+    for _ in 0..retries {
+        match read() {
+            Err(DtfError::IoError(_) | DtfError::ParseError { .. } | DtfError::EmptyFile(_)) => {
+                thread::sleep(delay);
+                delay = (delay * 2).min(MAX_READ_RETRY_DELAY);
+            }
+            result => return result,
+        }
+    }
+    read()
+}
+
+/// Makes sure an existing file is only overwritten if the user wants it to: with `--force`, or by confirming it when asked.
+/// The user is only asked if the app runs in a terminal, otherwise writing to an existing file fails.
+pub(crate) fn check_write_target(path: &Path, force: bool) -> Result<(), DtfError> {
+    guard_write_target(path, force, confirm_overwrite)
+}
+
+/// Fails if the file exists, unless overwriting it is forced or confirmed
+fn guard_write_target(
+    path: &Path,
+    force: bool,
+    confirm: impl FnOnce(&Path) -> bool,
+) -> Result<(), DtfError> {
+    if force || !path.exists() || confirm(path) {
+        return Ok(());
+    }
+
+    Err(DtfError::DiffError(format!(
+        "{} already exists, use --force to overwrite it",
+        path.display()
+    )))
+}
+
+/// Asks the user in the terminal whether the file should be overwritten. Without a terminal the answer is no
+fn confirm_overwrite(path: &Path) -> bool {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return false;
+    }
+
+    eprint!("{} already exists. Overwrite it? [y/N] ", path.display());
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).is_ok()
+        && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Writes a file through a temporary file in the same directory, which is renamed to the target path on success.
+/// This way the target is either fully written or left untouched, never half-written.
+pub(crate) fn write_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> Result<(), DtfError>,
+) -> Result<(), DtfError> {
+    let temp_path = get_temp_path(path);
+    let result = File::create(&temp_path)
+        .map_err(DtfError::IoError)
+        .and_then(|mut file| {
+            write(&mut file)?;
+            file.flush().map_err(DtfError::IoError)?;
+            file.sync_all().map_err(DtfError::IoError)
+        })
+        .and_then(|_| fs::rename(&temp_path, path).map_err(DtfError::IoError));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Gets the path of the temporary file used while writing the given path
+fn get_temp_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.tmp", file_name))
+}
+
+/// The error of a split file not holding exactly two objects
+fn split_file_error(file_path: &str) -> DtfError {
+    DtfError::parse_error(file_path, "must contain an array of exactly two objects")
+}
+
+/// Fetches JSON data from a URL. Any response other than 200 OK is an error
+#[cfg(feature = "http")]
+fn fetch_json(url: &str) -> Result<JsonMap, DtfError> {
+    let response = match ureq::get(url).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(status, _)) => {
+            return Err(DtfError::DiffError(format!(
+                "Could not fetch {}: HTTP status {}",
+                url, status
+            )))
+        }
+        Err(e) => {
+            return Err(DtfError::DiffError(format!(
+                "Could not fetch {}: {}",
+                url, e
+            )))
+        }
+    };
+    if response.status() != 200 {
+        return Err(DtfError::DiffError(format!(
+            "Could not fetch {}: HTTP status {}",
+            url,
+            response.status()
+        )));
+    }
+
+    serde_json::from_reader(response.into_reader()).map_err(|e| DtfError::parse_error(url, e))
+}
+
+/// Fetches JSON data from a URL
+#[cfg(not(feature = "http"))]
+fn fetch_json(url: &str) -> Result<JsonMap, DtfError> {
+    Err(DtfError::DiffError(format!(
+        "Could not fetch {}: reading URLs needs dtfterminal to be built with the http feature",
+        url
+    )))
+}
+
+#[cfg(not(feature = "xml"))]
+fn xml_to_json(_: &str) -> Result<JsonMap, String> {
+    Err("reading XML files needs dtfterminal to be built with the xml feature".to_owned())
+}
+
+/// Parses JSON5 data, dropping its comments
+#[cfg(feature = "json5")]
+fn json5_to_json(content: &str) -> Result<JsonMap, String> {
+    json5::from_str(content).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "json5"))]
+fn json5_to_json(_: &str) -> Result<JsonMap, String> {
+    Err("reading JSON5 files needs dtfterminal to be built with the json5 feature".to_owned())
+}
+
+/// Decodes MessagePack data into a JSON map
+#[cfg(feature = "msgpack")]
+fn msgpack_to_json(content: &[u8]) -> Result<JsonMap, String> {
+    rmp_serde::from_slice(content).map_err(|e| e.to_string())
+}
+
+/// Decodes MessagePack data into a JSON map
+#[cfg(not(feature = "msgpack"))]
+fn msgpack_to_json(_: &[u8]) -> Result<JsonMap, String> {
+    Err(
+        "reading MessagePack files needs dtfterminal to be built with the msgpack feature"
+            .to_owned(),
+    )
+}
+
+/// Gets the content of a file at a revision with `git show`.
+/// Git is run in the directory of the file, so the file can be in any repository, not just the one of the current directory.
+#[cfg(feature = "git")]
+fn git_show(revision: &str, path: &str) -> Result<Vec<u8>, DtfError> {
+    let path = Path::new(path);
+    let directory = path
+        .parent()
+        .filter(|directory| !directory.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let name = get_git_revision_name(&path.to_string_lossy(), revision);
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .arg("show")
+        .arg(format!("{}:./{}", revision, file_name))
+        .output()
+        .map_err(|e| DtfError::DiffError(format!("Could not run git to read {}: {}", name, e)))?;
+    if !output.status.success() {
+        return Err(DtfError::DiffError(format!(
+            "Could not read {}: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(output.stdout)
+}
+
+/// Gets the content of a file at a revision with `git show`
+#[cfg(not(feature = "git"))]
+fn git_show(revision: &str, path: &str) -> Result<Vec<u8>, DtfError> {
+    Err(DtfError::DiffError(format!(
+        "Could not read {}: reading git revisions needs dtfterminal to be built with the git feature",
+        get_git_revision_name(path, revision)
+    )))
+}
+
+/// Parses data in the given format, converting it to a JSON map if it's in another format.
+/// The content of text formats is decoded first, see `decode_text`, while binary formats are parsed as they are.
+/// The name is the one shown in the errors, e.g. the path of the file.
+fn parse_data_as_json(
+    content: &[u8],
+    name: &str,
+    format: DataFormat,
+    array_key: Option<&str>,
+) -> Result<JsonMap, DtfError> {
+    let text = || decode_text(content, name);
+    match format {
+        DataFormat::Json => parse_json(&text()?, name),
+        DataFormat::Jsonl => parse_json_lines(&text()?, name),
+        DataFormat::Yaml => yaml_to_json(
+            &serde_yaml::from_str(&text()?).map_err(|e| DtfError::parse_error(name, e))?,
+        ),
+        DataFormat::Csv => parse_csv(&text()?, name, array_key),
+        DataFormat::Xml => xml_to_json(&text()?).map_err(|e| DtfError::parse_error(name, e)),
+        DataFormat::Json5 => json5_to_json(&text()?).map_err(|e| DtfError::parse_error(name, e)),
+        DataFormat::MessagePack => {
+            msgpack_to_json(content).map_err(|e| DtfError::parse_error(name, e))
+        }
+    }
+}
+
+/// Parses JSON data, explaining the error if the data has numbers JSON doesn't support
+fn parse_json<T: serde::de::DeserializeOwned>(content: &str, name: &str) -> Result<T, DtfError> {
+    serde_json::from_str(content)
+        .map_err(|e| DtfError::parse_error(name, describe_json_error(content, e)))
+}
+
+/// Describes the error of parsing JSON data.
+/// Some producers write `NaN`, `Infinity` and `-Infinity` for numbers JSON can't represent, which serde_json only reports as an invalid value.
+fn describe_json_error(content: &str, e: serde_json::Error) -> String {
+    let line = content
+        .lines()
+        .nth(e.line().saturating_sub(1))
+        .unwrap_or_default();
+    let start = e.column().saturating_sub(1);
+    let token = ["NaN", "Infinity"].into_iter().find(|token| {
+        line.get(start..)
+            .is_some_and(|rest| rest.starts_with(token))
+    });
+    match token {
+        Some(token) => {
+            let sign = if line
+                .get(..start)
+                .is_some_and(|before| before.ends_with('-'))
+            {
+                "-"
+            } else {
+                ""
+            };
+            format!(
+                "non-finite number {}{} at line {} column {} is not supported, as JSON can't represent it. Write it as a string or null instead",
+                sign,
+                token,
+                e.line(),
+                e.column()
+            )
+        }
+        None => e.to_string(),
+    }
+}
+
+/// Parses JSON Lines data, see `FileHandler::read_json_lines_file`
+fn parse_json_lines(content: &str, name: &str) -> Result<JsonMap, DtfError> {
+    let mut records = JsonMap::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let key = format!("line {}", index + 1);
+        let record = serde_json::from_str(line).map_err(|e| {
+            DtfError::parse_error(name, format!("{}: {}", key, describe_json_error(line, e)))
+        })?;
+        records.insert(key, record);
+    }
+    Ok(records)
+}
+
+/// Parses CSV data, see `FileHandler::read_csv_file`
+fn parse_csv(content: &str, name: &str, array_key: Option<&str>) -> Result<JsonMap, DtfError> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| DtfError::parse_error(name, e))?
+        .clone();
+
+    let mut records = vec![];
+    for record in reader.records() {
+        let record = record.map_err(|e| DtfError::parse_error(name, e))?;
+        records.push(
+            headers
+                .iter()
+                .zip(record.iter())
+                .map(|(field, value)| {
+                    (
+                        field.to_owned(),
+                        serde_json::Value::String(value.to_owned()),
+                    )
+                })
+                .collect::<JsonMap>(),
+        );
+    }
+
+    let keyed_records = array_key.and_then(|array_key| key_csv_records(&records, array_key));
+    Ok(keyed_records.unwrap_or_else(|| {
+        records
+            .into_iter()
+            .enumerate()
+            .map(|(index, record)| {
+                (
+                    format!("row {}", index + 1),
+                    serde_json::Value::Object(record),
+                )
+            })
+            .collect()
+    }))
+}
+
+/// Stores the records of a CSV file under `[array_key=value]` keys.
+/// Returns None if any of the records misses the field, or if a value is present multiple times.
+fn key_csv_records(records: &[JsonMap], array_key: &str) -> Option<JsonMap> {
+    let mut keyed_records = JsonMap::new();
+    for record in records {
+        let id = record.get(array_key)?.as_str()?;
+        let key = format!("[{}={}]", array_key, id);
+        if keyed_records.contains_key(&key) {
+            return None;
+        }
+        keyed_records.insert(key, serde_json::Value::Object(record.clone()));
+    }
+    Some(keyed_records)
+}
+
+/// Reads the content of a data file as text, see `decode_text`
+fn read_data_file(file_path: &str) -> Result<String, DtfError> {
+    decode_text(&read_data_bytes(file_path)?, file_path)
+}
+
+/// Decodes the content of a text format.
+/// A leading UTF-8 byte order mark is skipped and content, that is not valid UTF-8 is an error.
+fn decode_text(bytes: &[u8], name: &str) -> Result<String, DtfError> {
+    let content = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+    String::from_utf8(content.to_vec()).map_err(|e| {
+        DtfError::parse_error(
+            name,
+            format!(
+                "not valid UTF-8: invalid byte at offset {}",
+                e.utf8_error().valid_up_to() + bytes.len() - content.len()
+            ),
+        )
+    })
+}
+
+/// Reads the content of a data file as bytes
+fn read_data_bytes(file_path: &str) -> Result<Vec<u8>, DtfError> {
+    let mut bytes = vec![];
+    open_data_file(file_path)
+        .and_then(|mut reader| reader.read_to_end(&mut bytes))
+        .map_err(|e| DtfError::read_error(file_path, e))?;
+    Ok(bytes)
+}
+
+/// Opens a data file for reading.
+/// Gzip compressed files are decompressed on the fly while being read.
+fn open_data_file(file_path: &str) -> io::Result<Box<dyn Read>> {
+    let reader = BufReader::new(File::open(file_path)?);
+    if is_gzip_file(file_path) {
+        Ok(Box::new(GzDecoder::new(reader)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Walks through JSON data without storing it and collects the keys, that appear multiple times in an object
+struct DuplicateKeyFinder<'a> {
+    path: String,
+    duplicate_keys: &'a mut Vec<String>,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for DuplicateKeyFinder<'a> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for DuplicateKeyFinder<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut index = 0;
+        while seq
+            .next_element_seed(DuplicateKeyFinder {
+                path: format!("{}[{}]", self.path, index),
+                duplicate_keys: &mut *self.duplicate_keys,
+            })?
+            .is_some()
+        {
+            index += 1;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut keys = HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let path = if self.path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", self.path, key)
+            };
+            if !keys.insert(key) {
+                self.duplicate_keys.push(path.clone());
+            }
+            map.next_value_seed(DuplicateKeyFinder {
+                path,
+                duplicate_keys: &mut *self.duplicate_keys,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use colored::Color;
+    use libdtf::core::diff_types::{ArrayDiff, ArrayDiffDesc, KeyDiff, ValueDiff};
+
+    use super::*;
+
+    #[test]
+    fn test_find_duplicate_json_keys() {
+        let duplicate_keys =
+            FileHandler::find_duplicate_json_keys("test_data/json/duplicate_keys.json").unwrap();
+
+        assert_eq!(duplicate_keys, vec!["a", "b.c", "d[1].e"]);
+    }
+
+    #[test]
+    fn test_find_duplicate_json_keys_without_duplicates() {
+        let duplicate_keys =
+            FileHandler::find_duplicate_json_keys("test_data/json/person1.json").unwrap();
+
+        assert!(duplicate_keys.is_empty());
+    }
+
+    #[test]
+    fn test_compact_save_format_round_trip() {
+        let diffs: DiffCollection = (
+            Some(vec![KeyDiff {
+                key: "discount".to_owned(),
+                has: "a.json".to_owned(),
+                misses: "b.json".to_owned(),
+            }]),
+            Some(vec![]),
+            Some(vec![ValueDiff {
+                key: "name".to_owned(),
+                value1: "\"Pen\"".to_owned(),
+                value2: "\"Pencil\"".to_owned(),
+            }]),
+            Some(vec![ArrayDiff {
+                key: "tags".to_owned(),
+                descriptor: ArrayDiffDesc::BHas,
+                value: "\"sale\"".to_owned(),
+            }]),
+        );
+        let full_path = env::temp_dir().join("dtfterminal_saved_full.json");
+        let compact_path = env::temp_dir().join("dtfterminal_saved_compact.json");
+
+        write_diffs(&full_path, SaveFormat::Full, diffs.clone());
+        write_diffs(&compact_path, SaveFormat::Compact, diffs.clone());
+        let full = fs::read_to_string(&full_path).unwrap();
+        let compact = fs::read_to_string(&compact_path).unwrap();
+        let loaded = FileHandler::load_baseline(&compact_path.to_string_lossy()).unwrap();
+        fs::remove_file(&full_path).unwrap();
+        fs::remove_file(&compact_path).unwrap();
+
+        assert!(compact.len() < full.len());
+        assert!(!compact.contains("\"t\":[]"));
+        assert_eq!(loaded, diffs);
+    }
+
+    #[test]
+    fn test_yaml_save_round_trip() {
+        let diffs: DiffCollection = (
+            Some(vec![KeyDiff {
+                key: "discount".to_owned(),
+                has: "a.json".to_owned(),
+                misses: "b.json".to_owned(),
+            }]),
+            Some(vec![]),
+            Some(vec![ValueDiff {
+                key: "name".to_owned(),
+                value1: "\"Pen\"".to_owned(),
+                value2: "\"Pencil\"".to_owned(),
+            }]),
+            Some(vec![ArrayDiff {
+                key: "tags".to_owned(),
+                descriptor: ArrayDiffDesc::AHas,
+                value: "\"sale\"".to_owned(),
+            }]),
+        );
+        let path = env::temp_dir().join("dtfterminal_saved.yaml");
+        let config = ConfigBuilder::new()
+            .file_a(Some("a.json".to_owned()))
+            .file_b(Some("b.json".to_owned()))
+            .check_for_key_diffs(true)
+            .check_for_type_diffs(true)
+            .check_for_value_diffs(true)
+            .check_for_array_diffs(true)
+            .force(true)
+            .build();
+        FileHandler::new(config, None)
+            .write_yaml_file(diffs.clone(), &path.to_string_lossy())
+            .unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let mut file_handler = FileHandler::new(
+            ConfigBuilder::new()
+                .read_from_file(path.to_string_lossy().to_string())
+                .build(),
+            None,
+        );
+        let (loaded, context) = file_handler.load_saved_results().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(content.contains("key_diff:"));
+        assert_eq!(loaded, diffs);
+        assert_eq!(context.config.file_a, Some("a.json".to_owned()));
+    }
+
+    fn write_diffs(path: &Path, save_format: SaveFormat, diffs: DiffCollection) {
+        let config = ConfigBuilder::new()
+            .write_to_file(Some(path.to_string_lossy().to_string()))
+            .file_a(Some("a.json".to_owned()))
+            .file_b(Some("b.json".to_owned()))
+            .save_format(save_format)
+            .build();
+        FileHandler::new(config, None).write_to_file(diffs).unwrap();
+    }
+
+    #[test]
+    fn test_load_saved_results_with_unsupported_version() {
+        let mut file_handler = FileHandler::new(
+            ConfigBuilder::new()
+                .read_from_file("test_data/saved_unsupported_version.json".to_owned())
+                .build(),
+            None,
+        );
+
+        let error = file_handler.load_saved_results().err().unwrap();
+
+        assert_eq!(
+            error.to_string(),
+            "Could not parse test_data/saved_unsupported_version.json: saved file version 99 is not supported"
+        );
+    }
+
+    #[test]
+    fn test_load_saved_results_without_version() {
+        let mut file_handler = FileHandler::new(
+            ConfigBuilder::new()
+                .read_from_file("test_data/saved_legacy.json".to_owned())
+                .build(),
+            None,
+        );
+
+        let ((key_diffs, _, value_diffs, _), context) = file_handler.load_saved_results().unwrap();
+
+        assert_eq!(key_diffs.unwrap()[0].key, "discount");
+        assert_eq!(value_diffs.unwrap()[0].key, "name");
+        assert_eq!(context.get_file_names(), ("a.json", "b.json"));
+    }
+
+    #[test]
+    fn test_load_saved_results_with_checks_given() {
+        let mut file_handler = FileHandler::new(
+            ConfigBuilder::new()
+                .check_for_value_diffs(true)
+                .render_value_diffs(true)
+                .read_from_file("test_data/saved_all_checks.json".to_owned())
+                .build(),
+            None,
+        );
+
+        let ((key_diffs, type_diffs, value_diffs, array_diffs), context) =
+            file_handler.load_saved_results().unwrap();
+
+        assert!(key_diffs.is_none() && type_diffs.is_none() && array_diffs.is_none());
+        assert_eq!(value_diffs.unwrap()[0].key, "name");
+        assert!(context.config.check_for_value_diffs && !context.config.check_for_key_diffs);
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_load_saved_results_without_checks() {
+        let mut file_handler = FileHandler::new(
+            ConfigBuilder::new()
+                .read_from_file("test_data/saved_no_checks.json".to_owned())
+                .build(),
+            None,
+        );
+
+        let error = file_handler.load_saved_results().err().unwrap();
+
+        assert_eq!(error.to_string(), "Diff error: no diff categories enabled");
+    }
+
+    #[test]
+    fn test_read_json_data_from_url() {
+        let url = serve_once("200 OK", r#"{"name": "John Doe", "age": 42}"#);
+
+        let data = FileHandler::read_json_data(&url).unwrap();
+
+        assert_eq!(data["name"], "John Doe");
+        assert_eq!(data["age"], 42);
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_read_json_data_from_url_not_found() {
+        let url = serve_once("404 Not Found", "");
+
+        let error = FileHandler::read_json_data(&url).err().unwrap();
+
+        assert_eq!(
+            error.to_string(),
+            format!("Diff error: Could not fetch {}: HTTP status 404", url)
+        );
+    }
+
+    /// Starts a server answering a single request with the given status and body, and returns its URL
+    #[cfg(feature = "http")]
+    fn serve_once(status: &'static str, body: &'static str) -> String {
+        use std::{io::BufRead, net::TcpListener};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/data.json", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                &stream,
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn test_read_theme_file() {
+        let theme = FileHandler::read_theme_file("test_data/theme.json").unwrap();
+
+        assert_eq!(theme.has, Color::Blue);
+        assert_eq!(theme.title, Some(Color::BrightCyan));
+        assert_eq!(theme.types.number, Color::Red);
+        assert_eq!(theme.misses, Theme::default().misses);
+        assert_eq!(theme.types.string, Theme::default().types.string);
+    }
+
+    #[test]
+    fn test_read_json_file_with_bom() {
+        let bom_path = std::env::temp_dir().join("dtfterminal_person1_bom.json");
+        let mut content = UTF8_BOM.to_vec();
+        content.extend(fs::read("test_data/json/person1.json").unwrap());
+        fs::write(&bom_path, content).unwrap();
+
+        let data = FileHandler::read_json_file(&bom_path.to_string_lossy());
+        fs::remove_file(bom_path).unwrap();
+
+        assert_eq!(
+            data.unwrap(),
+            FileHandler::read_json_file("test_data/json/person1.json").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_read_json_file_missing() {
+        let error = FileHandler::read_json_file("test_data/json/missing.json").unwrap_err();
+
+        assert!(
+            matches!(error, DtfError::FileNotFound(file) if file == "test_data/json/missing.json")
+        );
+    }
+
+    #[test]
+    fn test_read_json_file_malformed() {
+        let malformed_path = std::env::temp_dir().join("dtfterminal_malformed.json");
+        let malformed_path = malformed_path.to_string_lossy().to_string();
+        fs::write(&malformed_path, "{\"name\": ").unwrap();
+
+        let error = FileHandler::read_json_file(&malformed_path).unwrap_err();
+        fs::remove_file(&malformed_path).unwrap();
+
+        assert!(matches!(error, DtfError::ParseError { file, .. } if file == malformed_path));
+    }
+
+    #[test]
+    fn test_read_json_file_with_non_finite_number() {
+        let error = FileHandler::read_json_file("test_data/json/non_finite.json").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Could not parse test_data/json/non_finite.json: non-finite number Infinity at line 3 column 14 is not supported, as JSON can't represent it. Write it as a string or null instead"
+        );
+
+        let error = parse_data_as_json(b"{\"min\": -Infinity}", "a.json", DataFormat::Json, None)
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("non-finite number -Infinity at line 1 column 10"));
+
+        let error = parse_json_lines("{\"avg\": NaN}", "a.ndjson").unwrap_err();
+        assert!(error.to_string().contains("line 1: non-finite number NaN"));
+
+        let error = parse_json_lines("{\"avg\": 1}\n\n{\"avg\": NaN}", "a.ndjson").unwrap_err();
+        assert!(
+            error.to_string().contains("line 3: non-finite number NaN"),
+            "the blank line is counted"
+        );
+    }
+
+    #[test]
+    fn test_load_saved_results_missing() {
+        let mut file_handler = FileHandler::new(
+            ConfigBuilder::new()
+                .read_from_file("test_data/missing_saved.json".to_owned())
+                .build(),
+            None,
+        );
+
+        let error = file_handler.load_saved_results().err().unwrap();
+
+        assert!(matches!(error, DtfError::FileNotFound(_)));
+    }
+
+    #[test]
+    fn test_read_json_file_not_utf8() {
+        let latin1_path = std::env::temp_dir().join("dtfterminal_latin1.json");
+        let latin1_path = latin1_path.to_string_lossy().to_string();
+        fs::write(&latin1_path, b"{\"name\": \"Jos\xE9\"}").unwrap();
+
+        let error = FileHandler::read_json_file(&latin1_path).unwrap_err();
+        fs::remove_file(&latin1_path).unwrap();
+
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Could not parse {}: not valid UTF-8: invalid byte at offset 13",
+                latin1_path
+            )
+        );
+    }
+
+    #[test]
+    fn test_read_json_lines_file() {
+        let records = FileHandler::read_json_lines_file("test_data/json/log1.ndjson").unwrap();
+
+        assert_eq!(
+            records.keys().collect::<Vec<&String>>(),
+            vec!["line 1", "line 2", "line 3"]
+        );
+        assert_eq!(records["line 2"]["level"], "warn");
+
+        let records = parse_json_lines("{\"a\": 1}\n\n{\"a\": 2}\n", "a.ndjson").unwrap();
+        assert_eq!(
+            records.keys().collect::<Vec<&String>>(),
+            vec!["line 1", "line 3"]
+        );
+    }
+
+    #[cfg(feature = "json5")]
+    #[test]
+    fn test_read_json5_file() {
+        let data =
+            FileHandler::read_data_as_json("test_data/json/person1.jsonc", DataFormat::Json5, None)
+                .unwrap();
+
+        assert_eq!(
+            data,
+            FileHandler::read_json_data("test_data/json/person1.json").unwrap()
+        );
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_file_has_no_diffs_against_json_file() {
+        let data = FileHandler::read_data_as_json(
+            "test_data/msgpack/person1.msgpack",
+            DataFormat::MessagePack,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            FileHandler::read_json_data("test_data/json/person1.json").unwrap()
+        );
+
+        let (key_diffs, type_diffs, value_diffs, array_diffs) =
+            crate::diff_runner::DiffRunner::from_files(
+                "test_data/msgpack/person1.msgpack",
+                "test_data/json/person1.json",
+            )
+            .run()
+            .unwrap();
+        assert!(key_diffs.unwrap().is_empty() && type_diffs.unwrap().is_empty());
+        assert!(value_diffs.unwrap().is_empty() && array_diffs.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_parse_msgpack_content_that_is_not_valid_utf8() {
+        let content = std::fs::read("test_data/msgpack/person1.msgpack").unwrap();
+        assert!(String::from_utf8(content.clone()).is_err());
+
+        let data = parse_data_as_json(&content, "a.msgpack", DataFormat::MessagePack, None);
+        assert_eq!(
+            data.unwrap(),
+            FileHandler::read_json_data("test_data/json/person1.json").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_read_csv_file() {
+        let records = FileHandler::read_csv_file("test_data/csv/products1.csv", None).unwrap();
+
+        assert_eq!(
+            records.keys().collect::<Vec<&String>>(),
+            vec!["row 1", "row 2", "row 3"]
+        );
+        assert_eq!(records["row 2"]["name"], "Paper, A4");
+        assert_eq!(records["row 2"]["price"], "5");
+    }
+
+    #[test]
+    fn test_read_csv_file_with_array_key() {
+        let records =
+            FileHandler::read_csv_file("test_data/csv/products1.csv", Some("id")).unwrap();
+        assert_eq!(
+            records.keys().collect::<Vec<&String>>(),
+            vec!["[id=1]", "[id=2]", "[id=3]"]
+        );
+
+        let records =
+            FileHandler::read_csv_file("test_data/csv/products1.csv", Some("missing")).unwrap();
+        assert!(records.contains_key("row 1"));
+    }
+
+    #[test]
+    fn test_read_json_split_file() {
+        let (data1, data2) =
+            FileHandler::read_json_split_file("test_data/json/split.json").unwrap();
+
+        assert_eq!(data1.get("a"), Some(&serde_json::json!(1)));
+        assert_eq!(data2.get("a"), Some(&serde_json::json!(2)));
+    }
+
+    #[test]
+    fn test_read_split_file_without_two_objects() {
+        assert!(FileHandler::read_json_split_file("test_data/json/person1.json").is_err());
+        assert!(FileHandler::read_json_split_file("test_data/json/split_three.json").is_err());
+        assert!(FileHandler::read_yaml_split_file("test_data/yaml/split_scalar.yaml").is_err());
+    }
+
+    #[test]
+    fn test_read_yaml_split_file() {
+        let (data1, data2) =
+            FileHandler::read_yaml_split_file("test_data/yaml/split.yaml").unwrap();
+
+        assert_eq!(data1.get("a"), Some(&serde_yaml::Value::from(1)));
+        assert_eq!(data2.get("a"), Some(&serde_yaml::Value::from(2)));
+    }
+
+    #[test]
+    fn test_file_cache_parses_unchanged_file_once() {
+        let path = env::temp_dir().join("dtfterminal_cached_baseline.json");
+        fs::write(&path, "{}").unwrap();
+        let path = path.to_string_lossy().to_string();
+        let mut cache = FileCache::new();
+        let mut loads = 0;
+        let mut load = |path: &str| {
+            cache.get_or_load(path, |path| {
+                loads += 1;
+                Ok(fs::read_to_string(path).unwrap())
+            })
+        };
+
+        for _ in 0..3 {
+            assert_eq!(load(&path).unwrap(), "{}");
+        }
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() + std::time::Duration::from_secs(60))
+            .unwrap();
+        load(&path).unwrap();
+        assert!(load("missing_baseline.json").is_err());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            loads, 2,
+            "the file should only be parsed again after it changed"
+        );
+    }
+
+    #[test]
+    fn test_pretty_save() {
+        let write = |pretty_save| {
+            let path = env::temp_dir().join(format!("dtfterminal_pretty_{}.json", pretty_save));
+            let config = ConfigBuilder::new()
+                .write_to_file(Some(path.to_string_lossy().to_string()))
+                .file_a(Some("a.json".to_owned()))
+                .file_b(Some("b.json".to_owned()))
+                .pretty_save(pretty_save)
+                .force(true)
+                .build();
+            FileHandler::new(config, None)
+                .write_to_file((Some(vec![]), None, None, None))
+                .unwrap();
+            let content = fs::read_to_string(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+            content
+        };
+
+        assert!(write(true).contains('\n'));
+        assert!(!write(false).contains('\n'));
+    }
+
+    #[test]
+    fn test_read_with_retry() {
+        let mut attempts = 0;
+        let data = read_with_retry(2, || {
+            attempts += 1;
+            if attempts == 1 {
+                // The file is only half-written on the first attempt
+                FileHandler::read_json_file("test_data/json/half_written.json")
+            } else {
+                FileHandler::read_json_file("test_data/json/person1.json")
+            }
+        });
+        assert!(data.is_ok());
+        assert_eq!(attempts, 2);
+
+        attempts = 0;
+        let error = read_with_retry(1, || {
+            attempts += 1;
+            FileHandler::read_json_file("test_data/json/half_written.json")
+        });
+        assert!(matches!(error, Err(DtfError::ParseError { .. })));
+        assert_eq!(attempts, 2);
+
+        attempts = 0;
+        let error = read_with_retry(3, || {
+            attempts += 1;
+            FileHandler::read_json_file("test_data/json/missing.json")
+        });
+        assert!(matches!(error, Err(DtfError::FileNotFound(_))));
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn test_existing_write_target() {
+        let path = env::temp_dir().join("dtfterminal_existing_target.json");
+        fs::write(&path, "previous report").unwrap();
+        let config = ConfigBuilder::new()
+            .write_to_file(Some(path.to_string_lossy().to_string()))
+            .file_a(Some("a.json".to_owned()))
+            .file_b(Some("b.json".to_owned()))
+            .force(true)
+            .build();
+
+        let declined = guard_write_target(&path, false, |_| false);
+        let confirmed = guard_write_target(&path, false, |_| true);
+        let forced = FileHandler::new(config, None).write_to_file((None, None, None, None));
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            declined.unwrap_err().to_string(),
+            format!(
+                "Diff error: {} already exists, use --force to overwrite it",
+                path.display()
+            )
+        );
+        assert!(confirmed.is_ok());
+        assert!(forced.is_ok());
+        assert_ne!(written, "previous report");
+        assert!(guard_write_target(&path, false, |_| false).is_ok());
+    }
+
+    #[test]
+    fn test_write_atomically_keeps_target_on_failure() {
+        let path = std::env::temp_dir().join("dtfterminal_atomic_failure.json");
+        fs::write(&path, "original").unwrap();
+
+        let result = write_atomically(&path, |file| {
+            file.write_all(b"half-writ").map_err(DtfError::IoError)?;
+            Err(DtfError::DiffError("serialization failed".to_owned()))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        assert!(!get_temp_path(&path).exists());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_write_atomically_replaces_target() {
+        let path = std::env::temp_dir().join("dtfterminal_atomic_success.json");
+        fs::write(&path, "original").unwrap();
+
+        write_atomically(&path, |file| {
+            file.write_all(b"replaced").map_err(DtfError::IoError)
+        })
+        .unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "replaced");
+        assert!(!get_temp_path(&path).exists());
+        fs::remove_file(path).unwrap();
     }
 }
@@ -1,11 +1,13 @@
 use std::fmt::Write;
 
 use html_builder::{Buffer, Html5};
-use libdtf::core::diff_types::{ArrayDiff, ArrayDiffDesc};
+use libdtf::core::diff_types::ArrayDiff;
 
 use crate::{
     dtfterminal_types::{DtfError, WorkingContext},
-    utils::{get_display_values_by_column, group_by_key, is_yaml_file},
+    utils::{
+        cap_array_diffs, get_display_values_by_file, group_by_key, is_yaml_file, with_hidden_count,
+    },
 };
 
 struct Classes {
@@ -367,8 +369,8 @@ impl<'a> HtmlRenderer<'a> {
 
         let mut tbody = table.tbody();
         for (key, values) in map {
-            let val1 = get_display_values_by_column(self.context, &values, ArrayDiffDesc::AHas);
-            let val2 = get_display_values_by_column(self.context, &values, ArrayDiffDesc::BHas);
+            let (values, hidden) = cap_array_diffs(&self.context.config, values);
+            let (val1, val2) = get_display_values_by_file(self.context, &values);
 
             let mut tr = tbody.tr();
             self.write_line(
@@ -376,7 +378,7 @@ impl<'a> HtmlRenderer<'a> {
                     .th()
                     .attr(&format!("class='{}'", CLASSES.code))
                     .attr("scope='row'"),
-                key,
+                &with_hidden_count(key.to_owned(), hidden),
             )?;
             self.write_line(
                 &mut tr.td().pre().attr(&format!("class='{}'", CLASSES.original)),
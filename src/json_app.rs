@@ -1,6 +1,8 @@
 use crate::{
-    dtfterminal_types::{DiffCollection, WorkingContext},
-    file_handler::FileHandler,
+    dtfterminal_types::{merge_diffs, DiffCollection, DtfError, MovedElement, WorkingContext},
+    file_handler::{read_with_retry, FileHandler},
+    preprocessor::{prepare_json_data, prepare_typed_data, unescape_key_dots, TypedData},
+    utils::{remove_ignored_value_types, remove_small_array_diffs, time_phase},
 };
 
 use libdtf::{
@@ -14,6 +16,12 @@ pub struct JsonApp {
     data1: Map<String, Value>,
     data2: Map<String, Value>,
     context: WorkingContext,
+    /// Array differences found while preparing the data, which the check itself can't find
+    prepared_array_diffs: Vec<ArrayDiff>,
+    /// The array items moved to another position, found while preparing the data
+    moved_elements: Vec<MovedElement>,
+    /// The data prepared with the original types of the values, if the checked data has other ones
+    typed_data: Option<TypedData>,
 }
 
 impl JsonApp {
@@ -21,69 +29,211 @@ impl JsonApp {
     /// 1. Parses the command line arguments
     /// 2. Checks for differences and stores them
     pub fn new(path1: String, path2: String, context: WorkingContext) -> JsonApp {
-        let data1 = FileHandler::read_json_file(&path1).expect("Could not read JSON file");
-        let data2 = FileHandler::read_json_file(&path2).expect("Could not read JSON file");
-        JsonApp {
+        let (data1, data2) = time_phase(&context.config, "Reading files", || {
+            (
+                read_with_retry(context.config.read_retry, || {
+                    FileHandler::read_json_data(&path1)
+                })
+                .expect("Could not read JSON file"),
+                read_with_retry(context.config.read_retry, || {
+                    FileHandler::read_json_data(&path2)
+                })
+                .expect("Could not read JSON file"),
+            )
+        });
+        JsonApp::from_data(data1, data2, context)
+    }
+
+    /// Creates a new App instance from data already in memory
+    pub fn from_data(
+        data1: Map<String, Value>,
+        data2: Map<String, Value>,
+        context: WorkingContext,
+    ) -> JsonApp {
+        JsonApp::try_from_data(data1, data2, context).expect("Could not prepare JSON data")
+    }
+
+    /// Creates a new App instance from data already in memory, returning the error if the data can't be prepared for checking
+    pub fn try_from_data(
+        mut data1: Map<String, Value>,
+        mut data2: Map<String, Value>,
+        context: WorkingContext,
+    ) -> Result<JsonApp, DtfError> {
+        let typed_data = prepare_typed_data(&data1, &data2, &context.config)?;
+        let (prepared_array_diffs, moved_elements) =
+            prepare_json_data(&mut data1, &mut data2, &context.config)?;
+        Ok(JsonApp {
             data1,
             data2,
             context,
-        }
+            prepared_array_diffs,
+            moved_elements,
+            typed_data,
+        })
+    }
+
+    /// Returns the data of both files, as it was checked, with the dots in keys restored
+    pub fn data_as_json(&self) -> (Value, Value) {
+        let (mut data1, mut data2) = (self.data1.clone(), self.data2.clone());
+        unescape_key_dots(&mut data1);
+        unescape_key_dots(&mut data2);
+        (Value::Object(data1), Value::Object(data2))
+    }
+
+    /// Returns the data of both files, as it was checked, but with the original types of the values.
+    /// The dots in keys are escaped like in the keys of the differences, before they are processed.
+    pub fn typed_data_as_json(&self) -> (Value, Value) {
+        let (data1, data2) = match &self.typed_data {
+            Some((data1, data2)) => (data1, data2),
+            None => (&self.data1, &self.data2),
+        };
+        (Value::Object(data1.clone()), Value::Object(data2.clone()))
+    }
+
+    /// Returns the array items moved to another position, if the user asked for them
+    pub fn moved_elements(&self) -> &[MovedElement] {
+        &self.moved_elements
     }
 
     /// Checks for differences between the two files
     pub fn perform_new_check(&self) -> DiffCollection {
-        self.check_for_diffs(&self.data1, &self.data2)
+        let mut diffs = (None, None, None, None);
+        self.perform_streaming_check(|checked| merge_diffs(&mut diffs, checked));
+        diffs
+    }
+
+    /// Checks for differences between the two files, handing over the differences of every kind as soon as the kind is checked
+    pub fn perform_streaming_check(&self, on_checked: impl FnMut(DiffCollection)) {
+        self.check_for_diffs(&self.data1, &self.data2, on_checked)
     }
 
     /// Checks for differences between the two files
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     fn check_for_diffs(
         &self,
         data1: &Map<String, Value>,
         data2: &Map<String, Value>,
-    ) -> DiffCollection {
-        let key_diff = if self.context.config.check_for_key_diffs {
+        mut on_checked: impl FnMut(DiffCollection),
+    ) {
+        if self.context.config.check_for_key_diffs {
             let mut checking_data: CheckingData<KeyDiff> =
                 CheckingData::new("", data1, data2, &self.context.lib_working_context);
-            checking_data.check();
-            Some(checking_data.diffs()).cloned()
-        } else {
-            None
-        };
-        let type_diff = if self.context.config.check_for_type_diffs {
+            time_phase(&self.context.config, "Checking for key differences", || {
+                checking_data.check()
+            });
+            on_checked((Some(checking_data.diffs().clone()), None, None, None));
+        }
+
+        if self.context.config.check_for_type_diffs {
             let mut checking_data: CheckingData<TypeDiff> =
                 CheckingData::new("", data1, data2, &self.context.lib_working_context);
-            checking_data.check();
-            Some(checking_data.diffs()).cloned()
-        } else {
-            None
-        };
-        let value_diff = if self.context.config.check_for_value_diffs {
+            time_phase(
+                &self.context.config,
+                "Checking for type differences",
+                || checking_data.check(),
+            );
+            on_checked((None, Some(checking_data.diffs().clone()), None, None));
+        }
+
+        if self.context.config.check_for_value_diffs {
             let mut checking_data: CheckingData<ValueDiff> =
                 CheckingData::new("", data1, data2, &self.context.lib_working_context);
-            checking_data.check();
-            Some(checking_data.diffs()).cloned()
-        } else {
-            None
-        };
-        let array_diff = if self.context.config.check_for_array_diffs {
+            time_phase(
+                &self.context.config,
+                "Checking for value differences",
+                || checking_data.check(),
+            );
+            let mut value_diffs = checking_data.diffs().clone();
+            if !self.context.config.ignore_types.is_empty() {
+                let (data1, data2) = self.typed_data_as_json();
+                value_diffs = remove_ignored_value_types(
+                    value_diffs,
+                    (&data1, &data2),
+                    &self.context.config.ignore_types,
+                );
+            }
+            on_checked((None, None, Some(value_diffs), None));
+        }
+
+        if self.context.config.check_for_array_diffs {
             let mut checking_data: CheckingData<ArrayDiff> =
                 CheckingData::new("", data1, data2, &self.context.lib_working_context);
-            checking_data.check();
-            Some(checking_data.diffs()).cloned()
-        } else {
-            None
-        };
-
-        (key_diff, type_diff, value_diff, array_diff)
+            time_phase(
+                &self.context.config,
+                "Checking for array differences",
+                || checking_data.check(),
+            );
+            let mut array_diffs = checking_data.diffs().clone();
+            array_diffs.extend(self.prepared_array_diffs.iter().cloned());
+            if let Some(min_array_size) = self.context.config.min_array_size {
+                let (data1, data2) = self.typed_data_as_json();
+                array_diffs =
+                    remove_small_array_diffs(array_diffs, (&data1, &data2), min_array_size);
+            }
+            on_checked((None, None, None, Some(array_diffs)));
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::dtfterminal_types::ConfigBuilder;
+    use clap::Parser;
+
+    use crate::{
+        dtfterminal_types::{ConfigBuilder, ValueType},
+        utils::find_value_types,
+    };
 
     use super::*;
 
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_check_traced() {
+        use std::sync::{Arc, Mutex};
+
+        use tracing::{span, Subscriber};
+        use tracing_subscriber::{
+            layer::{Context, SubscriberExt},
+            registry::LookupSpan,
+            Layer,
+        };
+
+        /// Records every span as the name of its parent and its own name, e.g. `check_for_diffs/phase`
+        struct SpanRecorder(Arc<Mutex<Vec<String>>>);
+
+        impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanRecorder {
+            fn on_new_span(&self, _: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+                let span = ctx.span(id).unwrap();
+                let parent = span.parent().map_or("", |parent| parent.name());
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{}/{}", parent, span.name()));
+            }
+        }
+
+        let spans = Arc::new(Mutex::new(vec![]));
+        let subscriber = tracing_subscriber::registry().with(SpanRecorder(spans.clone()));
+        let json_app = JsonApp::new(
+            "test_data/json/person3.json".to_string(),
+            "test_data/json/person4.json".to_string(),
+            get_working_context(true, true, false, false),
+        );
+
+        tracing::subscriber::with_default(subscriber, || json_app.perform_new_check());
+
+        let spans = spans.lock().unwrap();
+        assert_eq!(
+            *spans,
+            vec![
+                "/check_for_diffs",
+                "check_for_diffs/phase",
+                "check_for_diffs/phase"
+            ],
+            "every checked kind should be a phase of the check"
+        );
+    }
+
     #[test]
     fn test_only_key_diffs_turned_on() {
         let working_context = get_working_context(true, false, false, false);
@@ -175,6 +325,280 @@ mod tests {
         assert_eq!(diffs.3.is_none(), true);
     }
 
+    #[test]
+    fn test_array_same_order_value_diffs_have_index_in_key() {
+        let mut working_context = get_working_context(false, false, true, false);
+        working_context.lib_working_context.config.array_same_order = true;
+        let json_app = JsonApp::from_data(
+            serde_json::json!({ "a": [1, 2, 3] })
+                .as_object()
+                .unwrap()
+                .clone(),
+            serde_json::json!({ "a": [1, 9, 3] })
+                .as_object()
+                .unwrap()
+                .clone(),
+            working_context,
+        );
+        let value_diffs = json_app.perform_new_check().2.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "a[1]");
+        assert_eq!(value_diffs[0].value1, "2");
+        assert_eq!(value_diffs[0].value2, "9");
+    }
+
+    #[test]
+    fn test_min_array_size_skips_small_arrays() {
+        let mut working_context = get_working_context(false, false, false, true);
+        working_context.config.min_array_size = Some(3);
+        let json_app = JsonApp::from_data(
+            serde_json::from_str(r#"{ "tags": ["a", "b"], "items": [1, 2, 3] }"#).unwrap(),
+            serde_json::from_str(r#"{ "tags": ["b", "c"], "items": [1, 2, 4] }"#).unwrap(),
+            working_context,
+        );
+
+        let array_diffs = json_app.perform_new_check().3.unwrap();
+
+        assert!(!array_diffs.is_empty());
+        assert!(array_diffs.iter().all(|diff| diff.key == "items"));
+    }
+
+    #[test]
+    fn test_min_array_size_finds_arrays_under_keys_with_dots() {
+        let mut working_context = get_working_context(false, false, false, true);
+        working_context.config.min_array_size = Some(3);
+        let json_app = JsonApp::from_data(
+            serde_json::from_str(r#"{ "v1.tags": ["a", "b"], "v1.items": [1, 2, 3] }"#).unwrap(),
+            serde_json::from_str(r#"{ "v1.tags": ["b", "c"], "v1.items": [1, 2, 4] }"#).unwrap(),
+            working_context,
+        );
+
+        let array_diffs = json_app.perform_new_check().3.unwrap();
+
+        assert!(!array_diffs.is_empty());
+        assert!(array_diffs
+            .iter()
+            .all(|diff| crate::preprocessor::unescape_dots(&diff.key) == "v1.items"));
+    }
+
+    #[test]
+    fn test_ignore_types_by_the_types_of_the_values() {
+        let mut working_context = get_working_context(false, false, true, false);
+        working_context.config.ignore_types = vec![ValueType::Bool];
+        let json_app = JsonApp::from_data(
+            serde_json::from_str(r#"{ "active": true, "label": "true", "count": 1 }"#).unwrap(),
+            serde_json::from_str(r#"{ "active": false, "label": "false", "count": 2 }"#).unwrap(),
+            working_context,
+        );
+
+        let value_diffs = json_app.perform_new_check().2.unwrap();
+
+        let keys: Vec<&str> = value_diffs.iter().map(|diff| diff.key.as_str()).collect();
+        assert_eq!(keys, vec!["label", "count"]);
+    }
+
+    #[test]
+    fn test_value_types_before_numbers_as_text() {
+        let mut working_context = get_working_context(false, false, true, false);
+        working_context.config.numbers_as_text = true;
+        working_context.config.ignore_types = vec![ValueType::String];
+        let json_app = JsonApp::from_data(
+            serde_json::from_str(r#"{ "v1.0": { "price": 42 }, "name": "Pen" }"#).unwrap(),
+            serde_json::from_str(r#"{ "v1.0": { "price": 43 }, "name": "Ink" }"#).unwrap(),
+            working_context.clone(),
+        );
+
+        let value_diffs = json_app.perform_new_check().2.unwrap();
+        let (data1, data2) = json_app.typed_data_as_json();
+        let value_types = find_value_types(&data1, &data2, &value_diffs, &working_context.config);
+
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(
+            value_types["v1.0.price"],
+            (ValueType::Number, ValueType::Number)
+        );
+    }
+
+    #[test]
+    fn test_structure_only_ignores_values() {
+        let args = crate::Arguments::try_parse_from([
+            "dtfterminal",
+            "-c",
+            "a.json",
+            "b.json",
+            "--structure-only",
+        ])
+        .unwrap();
+        let (_, _, config) = crate::app::App::from_arguments(args);
+        let working_context = WorkingContext::new(
+            libdtf::core::diff_types::WorkingContext::new(
+                libdtf::core::diff_types::WorkingFile::new("a.json".to_owned()),
+                libdtf::core::diff_types::WorkingFile::new("b.json".to_owned()),
+                libdtf::core::diff_types::Config {
+                    array_same_order: false,
+                },
+            ),
+            config,
+        );
+        let json_app = JsonApp::from_data(
+            serde_json::from_str(r#"{ "name": "Pen", "meta": { "tags": ["a"], "price": 1 } }"#)
+                .unwrap(),
+            serde_json::from_str(r#"{ "name": "Pencil", "meta": { "tags": ["b"], "price": 2 } }"#)
+                .unwrap(),
+            working_context,
+        );
+
+        let (key_diffs, type_diffs, value_diffs, array_diffs) = json_app.perform_new_check();
+
+        assert!(key_diffs.unwrap().is_empty() && type_diffs.unwrap().is_empty());
+        assert!(value_diffs.is_none() && array_diffs.is_none());
+    }
+
+    #[test]
+    fn test_diffs_keep_source_order() {
+        let working_context = get_working_context(false, false, true, false);
+        let json_app = JsonApp::from_data(
+            serde_json::from_str(r#"{ "zeta": 1, "alpha": 2, "mid": 3 }"#).unwrap(),
+            serde_json::from_str(r#"{ "zeta": 2, "alpha": 3, "mid": 4 }"#).unwrap(),
+            working_context,
+        );
+        let value_diffs = json_app.perform_new_check().2.unwrap();
+        let keys: Vec<&str> = value_diffs.iter().map(|vd| vd.key.as_str()).collect();
+        assert_eq!(keys, vec!["zeta", "alpha", "mid"]);
+    }
+
+    #[test]
+    fn test_gzip_file_has_no_diffs_against_plain_file() {
+        let gzip_path = std::env::temp_dir().join("dtfterminal_person3.json.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&gzip_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        std::io::Write::write_all(
+            &mut encoder,
+            &std::fs::read("test_data/json/person3.json").unwrap(),
+        )
+        .unwrap();
+        encoder.finish().unwrap();
+
+        let working_context = get_working_context(true, true, true, true);
+        let json_app = JsonApp::new(
+            gzip_path.to_string_lossy().to_string(),
+            "test_data/json/person3.json".to_string(),
+            working_context,
+        );
+        let (key_diffs, type_diffs, value_diffs, array_diffs) = json_app.perform_new_check();
+        std::fs::remove_file(gzip_path).unwrap();
+
+        assert!(key_diffs.unwrap().is_empty());
+        assert!(type_diffs.unwrap().is_empty());
+        assert!(value_diffs.unwrap().is_empty());
+        assert!(array_diffs.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_split_file_objects_checked_against_each_other() {
+        let working_context = get_working_context(true, true, true, true);
+        let (data1, data2) =
+            FileHandler::read_json_split_file("test_data/json/split.json").unwrap();
+        let json_app = JsonApp::from_data(data1, data2, working_context);
+
+        let (key_diffs, type_diffs, value_diffs, array_diffs) = json_app.perform_new_check();
+
+        assert!(key_diffs.unwrap().is_empty());
+        assert!(type_diffs.unwrap().is_empty());
+        assert!(array_diffs.unwrap().is_empty());
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "a");
+        assert_eq!(value_diffs[0].value1, "1");
+        assert_eq!(value_diffs[0].value2, "2");
+    }
+
+    #[test]
+    fn test_json_lines_files_checked_record_by_record() {
+        let working_context = get_working_context(true, false, true, false);
+        let json_app = JsonApp::from_data(
+            FileHandler::read_json_lines_file("test_data/json/log1.ndjson").unwrap(),
+            FileHandler::read_json_lines_file("test_data/json/log2.ndjson").unwrap(),
+            working_context,
+        );
+
+        let (key_diffs, _, value_diffs, _) = json_app.perform_new_check();
+
+        assert!(key_diffs.unwrap().is_empty());
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "line 2.message");
+    }
+
+    #[test]
+    fn test_json_lines_files_with_extra_records() {
+        let working_context = get_working_context(true, false, false, false);
+        let json_app = JsonApp::from_data(
+            FileHandler::read_json_lines_file("test_data/json/log1.ndjson").unwrap(),
+            FileHandler::read_json_lines_file("test_data/json/log3.ndjson").unwrap(),
+            working_context,
+        );
+
+        let key_diffs = json_app.perform_new_check().0.unwrap();
+
+        assert_eq!(key_diffs.len(), 1);
+        assert_eq!(key_diffs[0].key, "line 5");
+        assert_eq!(key_diffs[0].has, "FileB.yaml");
+    }
+
+    #[test]
+    fn test_subtree_pointer_checks_only_the_subtree() {
+        let mut working_context = get_working_context(true, true, true, true);
+        working_context.config.subtree_pointer = Some("/data".to_owned());
+        let json_app = JsonApp::new(
+            "test_data/json/envelope1.json".to_string(),
+            "test_data/json/envelope2.json".to_string(),
+            working_context,
+        );
+
+        let (key_diffs, type_diffs, value_diffs, array_diffs) = json_app.perform_new_check();
+
+        assert!(key_diffs.unwrap().is_empty());
+        assert!(type_diffs.unwrap().is_empty());
+        assert!(array_diffs.unwrap().is_empty());
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "price");
+    }
+
+    #[test]
+    fn test_streaming_check_hands_over_every_kind_when_checked() {
+        let working_context = get_working_context(true, true, true, true);
+        let json_app = JsonApp::new(
+            "test_data/json/large1.json".to_string(),
+            "test_data/json/large2.json".to_string(),
+            working_context,
+        );
+
+        let mut handed_over = vec![];
+        json_app.perform_streaming_check(|checked| {
+            handed_over.push((
+                checked.0.is_some(),
+                checked.1.is_some(),
+                checked.2.is_some(),
+                checked.3.is_some(),
+            ))
+        });
+
+        assert_eq!(
+            handed_over,
+            vec![
+                (true, false, false, false),
+                (false, true, false, false),
+                (false, false, true, false),
+                (false, false, false, true),
+            ],
+            "the key differences should be handed over before the other kinds are checked"
+        );
+    }
+
     fn get_working_context(
         key_diffs: bool,
         type_diffs: bool,
@@ -0,0 +1,176 @@
+use serde_json::{json, Value};
+
+use crate::{
+    dtfterminal_types::{iter_diffs, AnyDiff, DiffCollection, DtfError},
+    postprocessor::to_json_pointer,
+};
+
+/// Renders the differences as a JSON Patch (RFC 6902), that turns the data of the first file into the data of the second one.
+/// * Key differences become `add` operations if the key is only in the second file, `remove` operations otherwise
+/// * Type and Value differences become `replace` operations
+/// * Array differences replace the whole array, as the order of the items is not checked
+///
+/// The values are taken from the data of the second file. Operations under a path, that is already replaced, are left out.
+pub fn render_json_patch(
+    diffs: &DiffCollection,
+    data: (&Value, &Value),
+    json_pointer_keys: bool,
+) -> Result<Value, DtfError> {
+    let (data_a, data_b) = data;
+    let mut operations: Vec<(&str, String)> = vec![];
+    for diff in iter_diffs(diffs) {
+        let path = if json_pointer_keys {
+            diff.key().to_owned()
+        } else {
+            to_json_pointer(diff.key())
+        };
+        let op = match diff {
+            AnyDiff::Key(_) if data_b.pointer(&path).is_some() => "add",
+            AnyDiff::Key(_) if data_a.pointer(&path).is_some() => "remove",
+            AnyDiff::Key(_) => return Err(missing_path_error(&path)),
+            _ => "replace",
+        };
+        if !operations.iter().any(|(_, listed)| *listed == path) {
+            operations.push((op, path));
+        }
+    }
+
+    let replaced_paths = operations
+        .iter()
+        .filter(|(op, _)| *op == "replace")
+        .map(|(_, path)| path.clone())
+        .collect::<Vec<String>>();
+    operations
+        .into_iter()
+        .filter(|(_, path)| {
+            !replaced_paths
+                .iter()
+                .any(|replaced| path.starts_with(&format!("{}/", replaced)))
+        })
+        .map(|(op, path)| match op {
+            "remove" => Ok(json!({ "op": op, "path": path })),
+            _ => data_b
+                .pointer(&path)
+                .map(|value| json!({ "op": op, "path": path, "value": value }))
+                .ok_or_else(|| missing_path_error(&path)),
+        })
+        .collect::<Result<Vec<Value>, DtfError>>()
+        .map(Value::Array)
+}
+
+/// The error of a difference pointing to a path, that is not in the checked data
+fn missing_path_error(path: &str) -> DtfError {
+    DtfError::DiffError(format!(
+        "Could not create the JSON Patch: {} is not in the checked data",
+        path
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use json_patch::{patch, Patch};
+    use libdtf::core::diff_types::{ArrayDiff, ArrayDiffDesc, KeyDiff, TypeDiff, ValueDiff};
+
+    use super::*;
+
+    #[test]
+    fn test_render_json_patch() {
+        let data_a = json!({
+            "name": "Pen",
+            "discount": 5,
+            "price": "10",
+            "tags": ["sale", "office"],
+            "meta": { "color": "blue", "size": 1 }
+        });
+        let data_b = json!({
+            "name": "Pencil",
+            "price": 10,
+            "tags": ["office", "school"],
+            "meta": { "color": "red", "weight": 2 },
+            "stock": { "count": 3 }
+        });
+        let diffs: DiffCollection = (
+            Some(vec![
+                key_diff("discount", "file_a.json", "file_b.json"),
+                key_diff("meta.size", "file_a.json", "file_b.json"),
+                key_diff("meta.weight", "file_b.json", "file_a.json"),
+                key_diff("stock", "file_b.json", "file_a.json"),
+            ]),
+            Some(vec![TypeDiff {
+                key: "price".to_owned(),
+                type1: "string".to_owned(),
+                type2: "number".to_owned(),
+            }]),
+            Some(vec![
+                value_diff("name", "\"Pen\"", "\"Pencil\""),
+                value_diff("meta.color", "\"blue\"", "\"red\""),
+            ]),
+            Some(vec![
+                array_diff("tags", ArrayDiffDesc::AHas, "sale"),
+                array_diff("tags", ArrayDiffDesc::BHas, "school"),
+            ]),
+        );
+
+        let rendered = render_json_patch(&diffs, (&data_a, &data_b), false).unwrap();
+
+        assert_eq!(
+            rendered.as_array().unwrap().len(),
+            8,
+            "the array should only be replaced once"
+        );
+        let mut patched = data_a.clone();
+        patch(
+            &mut patched,
+            &serde_json::from_value::<Patch>(rendered).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(patched, data_b);
+    }
+
+    #[test]
+    fn test_render_json_patch_skips_nested_operations() {
+        let data_a = json!({ "item": "none" });
+        let data_b = json!({ "item": { "name": "Pen" } });
+        let diffs: DiffCollection = (
+            Some(vec![key_diff("/item/name", "file_b.json", "file_a.json")]),
+            Some(vec![TypeDiff {
+                key: "/item".to_owned(),
+                type1: "string".to_owned(),
+                type2: "object".to_owned(),
+            }]),
+            None,
+            None,
+        );
+
+        let rendered = render_json_patch(&diffs, (&data_a, &data_b), true).unwrap();
+
+        assert_eq!(
+            rendered,
+            json!([{ "op": "replace", "path": "/item", "value": { "name": "Pen" } }])
+        );
+    }
+
+    fn key_diff(key: &str, has: &str, misses: &str) -> KeyDiff {
+        KeyDiff {
+            key: key.to_owned(),
+            has: has.to_owned(),
+            misses: misses.to_owned(),
+        }
+    }
+
+    fn value_diff(key: &str, value1: &str, value2: &str) -> ValueDiff {
+        ValueDiff {
+            key: key.to_owned(),
+            value1: value1.to_owned(),
+            value2: value2.to_owned(),
+        }
+    }
+
+    fn array_diff(key: &str, descriptor: ArrayDiffDesc, value: &str) -> ArrayDiff {
+        ArrayDiff {
+            key: key.to_owned(),
+            descriptor,
+            value: value.to_owned(),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt::Write;
+
+use crate::{
+    dtfterminal_types::{iter_diffs, AnyDiff, DiffCollection, DiffKind, WorkingContext},
+    utils::describe_array_diff,
+};
+
+/// The categories of differences, each rendered as a test suite
+const SUITES: [(DiffKind, &str); 4] = [
+    (DiffKind::Key, "Key Differences"),
+    (DiffKind::Type, "Type Differences"),
+    (DiffKind::Value, "Value Differences"),
+    (DiffKind::Array, "Array Differences"),
+];
+
+/// Renders the differences as a JUnit XML report for CI test runners.
+/// Every category checked is a test suite and every difference is a failed test case named after its key.
+pub fn render_junit(diffs: &DiffCollection, context: &WorkingContext) -> String {
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = diffs;
+    let checked = [
+        key_diffs.is_some(),
+        type_diffs.is_some(),
+        value_diffs.is_some(),
+        array_diffs.is_some(),
+    ];
+    let all_diffs = iter_diffs(diffs).collect::<Vec<AnyDiff>>();
+
+    let mut suites = String::new();
+    let mut total = 0;
+    for ((kind, name), _) in SUITES.iter().zip(checked).filter(|(_, checked)| *checked) {
+        let suite_diffs = all_diffs
+            .iter()
+            .filter(|diff| diff.is_kind(*kind))
+            .collect::<Vec<&AnyDiff>>();
+        total += suite_diffs.len();
+
+        writeln!(
+            suites,
+            r#"  <testsuite name="{}" tests="{}" failures="{}">"#,
+            name,
+            suite_diffs.len(),
+            suite_diffs.len()
+        )
+        .unwrap();
+        for diff in suite_diffs {
+            writeln!(
+                suites,
+                r#"    <testcase name="{}" classname="{}">"#,
+                escape_xml(diff.key()),
+                name
+            )
+            .unwrap();
+            writeln!(
+                suites,
+                r#"      <failure message="{}"/>"#,
+                escape_xml(&describe_diff(diff, context))
+            )
+            .unwrap();
+            writeln!(suites, "    </testcase>").unwrap();
+        }
+        writeln!(suites, "  </testsuite>").unwrap();
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"dtf\" tests=\"{}\" failures=\"{}\">\n{}</testsuites>\n",
+        total, total, suites
+    )
+}
+
+/// Describes the change a difference stands for in a single sentence
+fn describe_diff(diff: &AnyDiff, context: &WorkingContext) -> String {
+    let (file_a, file_b) = context.get_file_names();
+    match diff {
+        AnyDiff::Key(kd) => format!("{} is only present in {}", kd.key, kd.has),
+        AnyDiff::Type(td) => format!(
+            "{} is {} in {} but {} in {}",
+            td.key, td.type1, file_a, td.type2, file_b
+        ),
+        AnyDiff::Value(vd) => format!(
+            "{} is {} in {} but {} in {}",
+            vd.key, vd.value1, file_a, vd.value2, file_b
+        ),
+        AnyDiff::Array(ad) => format!(
+            "{}: {} is {}",
+            ad.key,
+            ad.value,
+            describe_array_diff(&ad.descriptor, (file_a, file_b))
+        ),
+    }
+}
+
+/// Escapes the characters, that have a special meaning in XML
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use libdtf::core::diff_types::{ArrayDiff, ArrayDiffDesc, KeyDiff, ValueDiff};
+
+    use crate::dtfterminal_types::ConfigBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_render_junit() {
+        let diffs: DiffCollection = (
+            Some(vec![KeyDiff {
+                key: "item.discount".to_owned(),
+                has: "file_a.json".to_owned(),
+                misses: "file_b.json".to_owned(),
+            }]),
+            None,
+            Some(vec![ValueDiff {
+                key: "item.name".to_owned(),
+                value1: "<Pen>".to_owned(),
+                value2: "\"Pencil\"".to_owned(),
+            }]),
+            Some(vec![ArrayDiff {
+                key: "item.tags".to_owned(),
+                descriptor: ArrayDiffDesc::BHas,
+                value: "sale & more".to_owned(),
+            }]),
+        );
+
+        let xml = render_junit(&diffs, &get_working_context());
+
+        let document = roxmltree::Document::parse(&xml).unwrap();
+        let suites = document
+            .descendants()
+            .filter(|node| node.has_tag_name("testsuite"))
+            .map(|node| node.attribute("name").unwrap())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            suites,
+            vec!["Key Differences", "Value Differences", "Array Differences"]
+        );
+        let failures = document
+            .descendants()
+            .filter(|node| node.has_tag_name("failure"))
+            .map(|node| node.attribute("message").unwrap())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            failures,
+            vec![
+                "item.discount is only present in file_a.json",
+                "item.name is <Pen> in file_a.json but \"Pencil\" in file_b.json",
+                "item.tags: sale & more is only in file_b.json",
+            ]
+        );
+        assert_eq!(document.root_element().attribute("failures"), Some("3"));
+    }
+
+    #[test]
+    fn test_render_junit_without_diffs() {
+        let diffs: DiffCollection = (Some(vec![]), None, None, None);
+
+        let xml = render_junit(&diffs, &get_working_context());
+
+        let document = roxmltree::Document::parse(&xml).unwrap();
+        assert_eq!(document.root_element().attribute("failures"), Some("0"));
+        assert!(!document
+            .descendants()
+            .any(|node| node.has_tag_name("testcase")));
+    }
+
+    fn get_working_context() -> WorkingContext {
+        let working_file_a = libdtf::core::diff_types::WorkingFile::new("file_a.json".to_string());
+        let working_file_b = libdtf::core::diff_types::WorkingFile::new("file_b.json".to_string());
+        let lib_working_context = libdtf::core::diff_types::WorkingContext::new(
+            working_file_a,
+            working_file_b,
+            libdtf::core::diff_types::Config {
+                array_same_order: false,
+            },
+        );
+        WorkingContext::new(lib_working_context, ConfigBuilder::new().build())
+    }
+}
@@ -1,8 +1,5 @@
-use crate::{
-    dtfterminal_types::{TableContext, TermTable, WorkingContext},
-    utils::{CHECKMARK, MULTIPLY},
-};
-use colored::{Color, ColoredString, Colorize};
+use crate::dtfterminal_types::{DiffKind, TableContext, TermTable, WorkingContext};
+use colored::{ColoredString, Colorize};
 use libdtf::core::diff_types::KeyDiff;
 use term_table::{
     row::Row,
@@ -29,6 +26,7 @@ impl<'a> TermTable<KeyDiff> for KeyTable<'a> {
         let file_name_a = file_name_a_str.to_owned();
         let file_name_b = file_name_b_str.to_owned();
         self.add_title_row();
+        self.context.add_explanation_row(DiffKind::Key);
         self.add_file_name_row(file_name_a, file_name_b);
     }
 
@@ -36,15 +34,19 @@ impl<'a> TermTable<KeyDiff> for KeyTable<'a> {
         let (file_name_a_str, file_name_b_str) = self.context.working_context().get_file_names();
         let file_name_a = file_name_a_str.to_owned();
         let file_name_b = file_name_b_str.to_owned();
-        for kd in data {
+        for kd in data.iter().take(self.context.row_limit()) {
             let a_has = self.check_has(file_name_a.as_str(), kd);
             let b_has = self.check_has(file_name_b.as_str(), kd);
             self.context.add_row(Row::new(vec![
-                TableCell::new(&kd.key),
+                TableCell::new(
+                    self.context
+                        .key_text(&kd.key, || format!("missing from {}", kd.misses)),
+                ),
                 TableCell::new(a_has),
                 TableCell::new(b_has),
             ]));
         }
+        self.context.add_truncated_row(data.len());
     }
 }
 
@@ -59,19 +61,25 @@ impl<'a> KeyTable<'a> {
 
     /// Check if the key is present in the file
     fn check_has(&self, file_name: &str, key_diff: &KeyDiff) -> ColoredString {
+        let config = &self.context.working_context().config;
         if key_diff.has == file_name {
-            CHECKMARK.color(Color::Green)
+            config.has_symbol.color(config.theme.has)
         } else {
-            MULTIPLY.color(Color::Red)
+            config.misses_symbol.color(config.theme.misses)
         }
     }
 
     /// Adds the header row to the table
     fn add_title_row(&mut self) {
-        self.context
-            .add_row(Row::new(vec![TableCell::builder("Key Differences")
-                .col_span(3)
-                .alignment(Alignment::Center)]));
+        let title = self
+            .context
+            .working_context()
+            .config
+            .theme
+            .color_title("Key Differences");
+        self.context.add_row(Row::new(vec![TableCell::builder(title)
+            .col_span(3)
+            .alignment(Alignment::Center)]));
     }
 
     /// Adds the file names row to the table
@@ -86,7 +94,13 @@ impl<'a> KeyTable<'a> {
 
 #[cfg(test)]
 mod tests {
-    use crate::dtfterminal_types::ConfigBuilder;
+    use colored::Color;
+
+    use crate::{
+        dtfterminal_types::ConfigBuilder,
+        file_handler::FileHandler,
+        utils::{CHECKMARK, MULTIPLY},
+    };
 
     use super::*;
 
@@ -103,6 +117,77 @@ mod tests {
         assert_eq!(result, CHECKMARK.color(Color::Green));
     }
 
+    #[test]
+    fn test_check_has_with_symbols() {
+        let mut working_context = get_working_context();
+        working_context.config.has_symbol = "yes".to_owned();
+        working_context.config.misses_symbol = "no".to_owned();
+        let key_diff = KeyDiff {
+            key: "key1".to_owned(),
+            has: "file_a.json".to_owned(),
+            misses: "file_b.json".to_owned(),
+        };
+
+        let rendered = KeyTable::new(&[key_diff], &working_context).render();
+
+        let row = rendered.lines().find(|line| line.contains("key1")).unwrap();
+        assert!(row.contains("yes"));
+        assert!(row.contains("no"));
+        assert!(!row.contains(CHECKMARK));
+    }
+
+    #[test]
+    fn test_check_has_with_theme() {
+        let mut working_context = get_working_context();
+        working_context.config.theme =
+            FileHandler::read_theme_file("test_data/theme.json").unwrap();
+        let key_diff = KeyDiff {
+            key: "key1".to_owned(),
+            has: "file_a.json".to_owned(),
+            misses: "file_b.json".to_owned(),
+        };
+        let key_table = KeyTable::new(&[], &working_context);
+
+        assert_eq!(
+            key_table.check_has("file_a.json", &key_diff),
+            CHECKMARK.color(Color::Blue)
+        );
+        assert_eq!(
+            key_table.check_has("file_b.json", &key_diff),
+            MULTIPLY.color(Color::Red)
+        );
+    }
+
+    #[test]
+    fn test_abbreviated_keys_saved_in_full() {
+        let mut working_context = get_working_context();
+        working_context.config.abbreviate_keys = true;
+        let key_diff = KeyDiff {
+            key: "order.customer.address.billing.zip".to_owned(),
+            has: "file_a.json".to_owned(),
+            misses: "file_b.json".to_owned(),
+        };
+        let saved_path = std::env::temp_dir().join("dtfterminal_abbreviated_keys.json");
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .write_to_file(Some(saved_path.to_string_lossy().to_string()))
+            .file_a(Some("file_a.json".to_owned()))
+            .file_b(Some("file_b.json".to_owned()))
+            .abbreviate_keys(true)
+            .build();
+
+        let rendered = KeyTable::new(std::slice::from_ref(&key_diff), &working_context).render();
+        FileHandler::new(config, None)
+            .write_to_file((Some(vec![key_diff]), None, None, None))
+            .unwrap();
+        let saved = std::fs::read_to_string(&saved_path).unwrap();
+        std::fs::remove_file(&saved_path).unwrap();
+
+        assert!(rendered.contains("order\u{2026}billing.zip"));
+        assert!(!rendered.contains("order.customer"));
+        assert!(saved.contains("order.customer.address.billing.zip"));
+    }
+
     fn get_working_context() -> WorkingContext {
         let working_file_a = libdtf::core::diff_types::WorkingFile::new("file_a.json".to_string());
         let working_file_b = libdtf::core::diff_types::WorkingFile::new("file_b.json".to_string());
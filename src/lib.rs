@@ -1,17 +1,41 @@
 use app::App;
+use batch_app::BatchApp;
 use clap::{ArgGroup, Parser};
-use dtfterminal_types::DtfError;
+use dtfterminal_types::{
+    DataFormat, DiffKind, DtfError, EnvExpansion, KeyNormalization, SaveFormat, Severity,
+    SeverityOverride, ValueType,
+};
+use file_handler::MAX_READ_RETRIES;
+use std::{process::ExitCode, time::Duration};
+#[cfg(feature = "watch")]
+use watcher::watch;
 
 mod app;
 mod array_table;
+mod batch_app;
+pub mod diff_runner;
 pub mod dtfterminal_types;
 mod file_handler;
 mod html_renderer;
 mod json_app;
+mod json_patch_renderer;
+mod junit_renderer;
 mod key_table;
+mod move_table;
+mod plain_renderer;
+mod postprocessor;
+mod preprocessor;
+mod profile;
+mod side_by_side_renderer;
+#[cfg(feature = "tui")]
+mod tui_app;
 mod type_table;
 mod utils;
 mod value_table;
+#[cfg(feature = "watch")]
+mod watcher;
+#[cfg(feature = "xml")]
+mod xml_converter;
 mod yaml_app;
 
 /// Command line arguments are handled here by clap
@@ -23,12 +47,12 @@ mod yaml_app;
         ArgGroup::new("diff-options")
             .required(true)
             .multiple(true)
-            .args(&["key_diffs", "type_diffs", "value_diffs", "array_diffs"]),
+            .args(&["key_diffs", "type_diffs", "value_diffs", "array_diffs", "structure_only", "profile"]),
     ),
     group(
         ArgGroup::new("file-options")
         .required(true)
-        .args(&["check_files", "read_from_file"])
+        .args(&["check_files", "read_from_file", "check_dirs", "split_file", "self_compare", "git"])
     ),
     group(
         ArgGroup::new("browser-options")
@@ -46,13 +70,16 @@ struct Arguments {
     /// Read from a JSON file created on previous check instead of checking again
     #[clap(short, default_value_t = String::new())]
     read_from_file: String,
+    /// Check every file in the first directory against the file with the same name in the second one. Can't be used with the output files
+    #[clap(short = 'd', value_delimiter = ' ', num_args = 2, conflicts_with_all = ["write_to_file", "browser_view", "junit", "json_patch", "yaml"])]
+    check_dirs: Vec<String>,
 
-    /// Output to json file instead of rendering tables in the terminal
-    #[clap(short)]
+    /// Output to json file instead of rendering tables in the terminal. The name can be left out to write diff.json
+    #[clap(short, num_args = 0..=1, default_missing_value = "")]
     write_to_file: Option<String>,
 
-    /// Browser View: Output to an HTML file instead of rendering tables in the terminal
-    #[clap(short)]
+    /// Browser View: Output to an HTML file instead of rendering tables in the terminal. The name can be left out to write diff.html
+    #[clap(short, num_args = 0..=1, default_missing_value = "")]
     browser_view: Option<String>,
 
     /// Printer friendly HTML output
@@ -79,9 +106,642 @@ struct Arguments {
     /// Do you want arrays to be the same order? If defined you will get Value differences with indexes, otherwise you will get array differences, that tell you which object contains or misses values.
     #[clap(short = 'o', default_value_t = false)]
     array_same_order: bool,
+
+    /// Treat keys with a null value as if they were missing. Such keys will not produce Type or Value differences, only a Key difference against a key that has an actual value or is missing.
+    #[clap(long, default_value_t = false)]
+    null_is_missing: bool,
+
+    /// Don't show progress indicators
+    #[clap(long, default_value_t = false)]
+    quiet: bool,
+
+    /// Ignore differences with keys matching the regular expression. Can be used multiple times
+    #[clap(long)]
+    ignore: Vec<String>,
+    /// Ignore differences with keys matching any of the regular expressions in the file. One expression per line, lines starting with # are comments
+    #[clap(long)]
+    ignore_file: Option<String>,
+    /// Ignore differences with keys matching the glob pattern, where * matches a single key segment and ** matches any number of them. Can be used multiple times
+    #[clap(long)]
+    ignore_glob: Vec<String>,
+    /// Only show differences with keys matching the regular expression. Can be used multiple times. Ignore rules are applied after this
+    #[clap(long)]
+    only: Vec<String>,
+
+    /// Warn about keys present multiple times in the same JSON object. Only the last value of such keys gets checked
+    #[clap(long, default_value_t = false)]
+    warn_duplicate_keys: bool,
+
+    /// Only check keys present in both files. Key differences will not be reported
+    #[clap(long, default_value_t = false)]
+    intersection_only: bool,
+
+    /// Render values in Value and Array tables as single line JSON, truncated to the column width
+    #[clap(long, default_value_t = false)]
+    compact_values: bool,
+
+    /// Exit with a non-zero code if differences of the given kinds are found. Defaults to all kinds if no value is given
+    #[clap(long, value_enum, value_delimiter = ',', num_args = 0.., default_missing_value = "all")]
+    fail_on: Vec<DiffKind>,
+
+    /// Only validate the arguments, without reading or writing any file, e.g. the data files, the theme or the profiles. Prints "arguments OK" if they are valid
+    #[clap(long, default_value_t = false)]
+    check_args: bool,
+
+    /// Don't use colors in the terminal output
+    #[clap(long, default_value_t = false)]
+    no_color: bool,
+
+    /// Compare numbers by their text instead of their value, so 1 and 1.0 are different. Only numbers present in both files at the same place are affected
+    #[clap(long, default_value_t = false)]
+    numbers_as_text: bool,
+
+    /// Match the elements of arrays of objects by the value of this field instead of their position. Matched elements are checked field by field, the rest are reported as Array differences
+    #[clap(long)]
+    array_key: Option<String>,
+
+    /// Show the keys of the differences as JSON Pointers (RFC 6901), e.g. /nested/items/0
+    #[clap(long, default_value_t = false)]
+    json_pointer: bool,
+
+    /// Check the two objects of a file containing an array of exactly two objects against each other
+    #[clap(long)]
+    split_file: Option<String>,
+
+    /// Check the parts of a JSON file two JSON Pointers point to against each other, e.g. --self-compare config.json /staging /production
+    #[clap(long, num_args = 3, value_names = ["FILE", "POINTER_A", "POINTER_B"])]
+    self_compare: Vec<String>,
+
+    /// Check a file at two revisions of its git repository against each other, e.g. --git main HEAD config.json. Only available with the git feature
+    #[clap(long, num_args = 3, value_names = ["REV_A", "REV_B", "PATH"])]
+    git: Vec<String>,
+
+    /// Print the time each phase took and the number of differences found to stderr
+    #[clap(long, default_value_t = false)]
+    verbose: bool,
+
+    /// Show up to this many unchanged keys of the objects containing Value differences in the Value table
+    #[clap(long = "context", default_value_t = 0)]
+    context_size: usize,
+
+    /// Browse the differences in an interactive view instead of rendering tables. Needs the tui feature
+    #[clap(long, default_value_t = false)]
+    interactive: bool,
+
+    /// Write the differences into a JUnit XML file, every difference being a failed test case. The name can be left out to write diff.xml
+    #[clap(long, num_args = 0..=1, default_missing_value = "")]
+    junit: Option<String>,
+
+    /// Only show differences, that are not present in the saved file that follows. The file must be written with -w before
+    #[clap(long)]
+    baseline: Option<String>,
+
+    /// Use the colors of the JSON or YAML theme file that follows in the terminal tables
+    #[clap(long)]
+    theme: Option<String>,
+
+    /// Keep differences reported multiple times, instead of showing them once
+    #[clap(long, default_value_t = false)]
+    no_dedup: bool,
+
+    /// Print a single line summary of the number of differences per kind to stderr, e.g. `datadiff: 3 key, 0 type, 5 value, 1 array`
+    #[clap(long, default_value_t = false)]
+    print_summary: bool,
+
+    /// Only check the part of the files the JSON Pointer that follows points to, e.g. /data/items. Keys are shown relative to it
+    #[clap(long = "at")]
+    subtree_pointer: Option<String>,
+
+    /// Print the differences as plain text lines instead of tables
+    #[clap(long, default_value_t = false)]
+    plain: bool,
+
+    /// Render at most this many rows per table. Saved files still get every difference
+    #[clap(long)]
+    limit: Option<usize>,
+
+    /// Swap the two files after checking, as if they were given in the opposite order
+    #[clap(long, default_value_t = false)]
+    swap: bool,
+
+    /// Print the differences as an outline with a section for every top level key, instead of tables
+    #[clap(long, default_value_t = false)]
+    group_by_key: bool,
+
+    /// Use the settings saved with --save-profile under the name that follows. Settings given on the command line replace the saved ones, but flags saved in the profile stay on
+    #[clap(long)]
+    profile: Option<String>,
+    /// Save the settings of this run, except for the file paths and the theme, under the name that follows
+    #[clap(long)]
+    save_profile: Option<String>,
+    /// Explain what every kind of difference means above its table, and why each difference was reported below its key
+    #[clap(long, default_value_t = false)]
+    explain: bool,
+    /// Keep running and check the files again every time one of them changes. Needs the watch feature
+    #[clap(long, default_value_t = false, requires = "check_files")]
+    watch: bool,
+    /// Shorten the values of Value and Array differences to this many characters, both in the tables and in saved files
+    #[clap(long)]
+    max_value_len: Option<usize>,
+    /// The symbol marking the file, that has the key, in the Key table
+    #[clap(long, default_value = utils::CHECKMARK)]
+    has_symbol: String,
+    /// The symbol marking the file, that misses the key, in the Key table
+    #[clap(long, default_value = utils::MULTIPLY)]
+    misses_symbol: String,
+    /// Write the differences into a JSON Patch (RFC 6902) file, that turns the data of the first file into the data of the second one. The name can be left out to write diff.patch.json
+    #[clap(long, num_args = 0..=1, default_missing_value = "", conflicts_with_all = ["read_from_file", "array_key", "array_same_order", "subtree_pointer", "numbers_as_text", "null_is_missing", "normalize_keys", "round"])]
+    json_patch: Option<String>,
+
+    /// Compare keys after normalizing them: trim removes surrounding whitespace, lower ignores case, both does both. The keys of the first file are shown
+    #[clap(long, value_enum)]
+    normalize_keys: Option<KeyNormalization>,
+
+    /// Print the differences of every kind as plain text lines as soon as the kind is checked, instead of waiting for every check to finish
+    #[clap(long, default_value_t = false, conflicts_with_all = ["read_from_file", "group_by_key", "interactive", "watch"])]
+    stream: bool,
+
+    /// Show the Type differences as rows of the Value table, with the values of both files and the change of the type below the key
+    #[clap(long, default_value_t = false)]
+    combine_type_value: bool,
+
+    /// Round numbers to this many decimal places before comparing them, e.g. 3.14159 and 3.1416 are the same with 3. Value differences show the original values, Array differences the rounded ones
+    #[clap(long, value_name = "DIGITS")]
+    round: Option<u32>,
+
+    /// The format of the file written with -w. compact uses short field names and leaves out kinds without differences, for smaller files
+    #[clap(long, value_enum, default_value_t = SaveFormat::Full, requires = "write_to_file")]
+    save_format: SaveFormat,
+
+    /// Show the keys with the same value in both files in the Value table too, greyed out and marked with =
+    #[clap(long, default_value_t = false)]
+    include_equal: bool,
+
+    /// The width of the terminal tables in characters. Defaults to the width of the terminal, or 80 if the output is not a terminal
+    #[clap(long)]
+    max_width: Option<usize>,
+
+    /// Fail if either of the files holds no data, e.g. an empty object, instead of reporting everything in the other file as missing
+    #[clap(long, default_value_t = false)]
+    require_non_empty: bool,
+
+    /// Ignore Type and Value differences involving any of the given types of values, e.g. null,bool
+    #[clap(long, value_enum, value_delimiter = ',')]
+    ignore_types: Vec<ValueType>,
+
+    /// The format of the first file, instead of the one its extension suggests. Files with unknown extensions are read as JSON
+    #[clap(long, value_enum)]
+    format_a: Option<DataFormat>,
+
+    /// The format of the second file, instead of the one its extension suggests. Files with unknown extensions are read as JSON
+    #[clap(long, value_enum)]
+    format_b: Option<DataFormat>,
+
+    /// Print the differences in sections by the direction of the change: added in the second file, removed from it, and modified, instead of tables
+    #[clap(long, default_value_t = false, conflicts_with_all = ["group_by_key", "stream"])]
+    by_direction: bool,
+
+    /// Shorten keys with more than three segments in the tables to the first segment and the last two, e.g. a…e.name. Saved files keep the full keys
+    #[clap(long, default_value_t = false)]
+    abbreviate_keys: bool,
+
+    /// Overwrite the files written with -w, -b, --junit and --json-patch if they already exist, without asking
+    #[clap(long, default_value_t = false)]
+    force: bool,
+
+    /// Show the relative change of numbers in the Value table, e.g. (+12.5%), or their difference if the first number is zero
+    #[clap(long, default_value_t = false)]
+    show_delta: bool,
+
+    /// Indent the file written with -w, so it's easier to read and to keep in version control, at the cost of a larger file
+    #[clap(long, default_value_t = false, requires = "write_to_file")]
+    pretty_save: bool,
+
+    /// Group the digits of numbers in the tables by thousands with the separator that follows, or a comma if none is given, e.g. 1,000,000. The separator can't be the decimal point
+    #[clap(long, num_args = 0..=1, default_missing_value = ",", value_parser = parse_thousands_sep)]
+    thousands_sep: Option<String>,
+
+    /// Replace ${VAR} placeholders in string values with the environment variables before checking. Placeholders of variables, that are not set, are kept, unless "error" follows
+    #[clap(long, value_enum, num_args = 0..=1, default_missing_value = "keep")]
+    expand_env: Option<EnvExpansion>,
+
+    /// Fail the check on values of YAML files, that can't be compared as they are, like tagged values and keys, that are not strings, instead of comparing them in a generic way.
+    /// Keys colliding after --normalize-keys fail the check too, instead of a warning
+    #[clap(long, default_value_t = false)]
+    strict: bool,
+
+    /// Treat strings holding the same instant as RFC 3339 timestamps as equal, e.g. 2023-01-01T00:00:00Z and 2023-01-01T00:00:00.000Z. Needs the dates feature
+    #[clap(long, default_value_t = false)]
+    smart_dates: bool,
+
+    /// Only render the kinds of differences of at least the given severity, while still checking and saving every kind. Key differences are low, Type differences are high, Value and Array differences are medium, unless changed with --severity
+    #[clap(long, value_enum)]
+    min_severity: Option<Severity>,
+
+    /// Change the severity of a kind of differences for --min-severity, e.g. key=high. Can be given multiple times
+    #[clap(long, value_name = "KIND=SEVERITY")]
+    severity: Vec<SeverityOverride>,
+
+    /// Only print the number of differences per kind to stdout, one kind per line like `key: 3`, instead of any other output. Can't be used with the output files
+    #[clap(long, default_value_t = false, conflicts_with_all = ["write_to_file", "browser_view", "junit", "json_patch", "yaml"])]
+    count_only: bool,
+
+    /// Show every Value difference with its key as a full-width row, and the values of the files stacked below it, labelled with the file names
+    #[clap(long, default_value_t = false)]
+    stacked: bool,
+
+    /// Show at most this many Array differences of a single array, followed by how many more there are
+    #[clap(long)]
+    max_array_diffs: Option<usize>,
+
+    /// Treat null and the empty string as equal, when one file has one of them and the other file has the other at the same place
+    #[clap(long, default_value_t = false)]
+    empty_equals_null: bool,
+
+    /// Write the output files into the directory that follows, creating it if needed. Output files given without a name get their default name there, e.g. diff.json
+    #[clap(long)]
+    output_dir: Option<String>,
+
+    /// Print both files pretty-printed side by side instead of rendering tables, coloring the lines of the values with differences
+    #[clap(long, default_value_t = false, conflicts_with_all = ["read_from_file", "plain", "group_by_key", "by_direction", "interactive", "stream", "count_only"])]
+    side_by_side: bool,
+
+    /// Skip the Array differences of arrays shorter than this many items in both files, as reordering tiny arrays is usually noise
+    #[clap(long, conflicts_with = "read_from_file")]
+    min_array_size: Option<usize>,
+
+    /// Only compare the structure of the files: a shortcut for checking Key and Type differences, while values and arrays are not checked at all
+    #[clap(long, default_value_t = false, conflicts_with_all = ["value_diffs", "array_diffs"])]
+    structure_only: bool,
+
+    /// Try reading a file again up to this many times, at most 20, with a short pause growing between the attempts up to 2 seconds, if it can't be read or parsed. Helps with files still being written by another process
+    #[clap(long, default_value_t = 0, value_parser = parse_read_retry)]
+    read_retry: usize,
+
+    /// Write the differences into the YAML file that follows, with the same content as the JSON file of -w, e.g. for GitOps tooling. The name can be left out to write diff.yaml
+    #[clap(long, num_args = 0..=1, default_missing_value = "")]
+    yaml: Option<String>,
+
+    /// Only render the tables of the file pairs with differences in directory mode. The summary still lists every pair
+    #[clap(long, default_value_t = false, requires = "check_dirs")]
+    only_changed_files: bool,
+
+    /// The separator between the segments of nested keys, instead of `.`, e.g. `/` or `::`. Dots in the keys of the data are kept as they are, so keys containing dots can be told apart
+    #[clap(long, conflicts_with_all = ["json_pointer", "json_patch", "context_size", "include_equal", "group_by_key", "abbreviate_keys", "side_by_side"])]
+    key_separator: Option<String>,
+
+    /// Fail if checking the files takes longer than this many seconds, e.g. `2.5`, to catch inputs that are too large or pathological for CI
+    #[clap(long, value_parser = parse_timeout, conflicts_with = "stream")]
+    timeout: Option<Duration>,
+
+    /// Report the array items present in both files, but moved to another position, as moved items. Arrays of scalars are matched by value, arrays of objects by the field of --array-key
+    #[clap(long, default_value_t = false, conflicts_with_all = ["read_from_file", "array_same_order", "json_pointer", "key_separator", "group_by_key", "by_direction", "stream", "side_by_side", "interactive"])]
+    report_moves: bool,
+}
+
+/// Runs the application.
+/// The exit code is non-zero if differences of the kinds selected with `--fail-on` were found.
+pub fn run() -> Result<ExitCode, DtfError> {
+    #[cfg(feature = "tracing")]
+    init_tracing();
+
+    let (path1, path2, config) = App::parse_args();
+    if config.no_color {
+        colored::control::set_override(false);
+    }
+
+    if config.check_args {
+        println!("arguments OK");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if config.watch {
+        // Both paths are there, as watching requires -c
+        watch(path1.unwrap_or_default(), path2.unwrap_or_default(), config)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let has_failing_diffs = if config.dir_a.is_some() && config.dir_b.is_some() {
+        BatchApp::new(config).execute()?
+    } else {
+        let app = App::with_timeout(path1, path2, config)?;
+        app.execute()?;
+        app.has_failing_diffs()
+    };
+
+    if has_failing_diffs {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Parses the seconds of `--timeout`, which can have a fraction, e.g. `0.5`
+fn parse_timeout(value: &str) -> Result<Duration, String> {
+    let seconds: f64 = value.parse().map_err(|e| format!("{}", e))?;
+    Duration::try_from_secs_f64(seconds).map_err(|e| format!("{}", e))
+}
+
+/// Parses the attempts of `--read-retry`, which can't be more than `MAX_READ_RETRIES`
+fn parse_read_retry(value: &str) -> Result<usize, String> {
+    let retries: usize = value.parse().map_err(|e| format!("{}", e))?;
+    if retries > MAX_READ_RETRIES {
+        return Err(format!("can't be more than {}", MAX_READ_RETRIES));
+    }
+    Ok(retries)
+}
+
+/// Parses the separator of `--thousands-sep`, which can't contain the decimal point, as grouped numbers with fractions could not be read
+fn parse_thousands_sep(value: &str) -> Result<String, String> {
+    if value.contains('.') {
+        return Err("can't contain the decimal point".to_owned());
+    }
+    Ok(value.to_owned())
+}
+
+/// Writes the traces of the check to stderr, so the output stays the same. The traces to write are chosen with `RUST_LOG`, e.g. `RUST_LOG=dtfterminal=trace`
+#[cfg(feature = "tracing")]
+fn init_tracing() {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/// Checks the files again every time one of them changes
+#[cfg(not(feature = "watch"))]
+fn watch(_: String, _: String, _: dtfterminal_types::Config) -> Result<(), DtfError> {
+    Err(DtfError::DiffError(
+        "Watch mode needs dtfterminal to be built with the watch feature".to_string(),
+    ))
 }
 
-/// Runs the application
-pub fn run() -> Result<(), DtfError> {
-    App::new().execute()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fail_on_arguments() {
+        let args = Arguments::try_parse_from([
+            "dtfterminal",
+            "-c",
+            "a.json",
+            "b.json",
+            "-k",
+            "--fail-on",
+            "value,array",
+        ])
+        .unwrap();
+        assert_eq!(args.fail_on, vec![DiffKind::Value, DiffKind::Array]);
+
+        let args =
+            Arguments::try_parse_from(["dtfterminal", "-c", "a.json", "b.json", "-k", "--fail-on"])
+                .unwrap();
+        assert_eq!(args.fail_on, vec![DiffKind::All]);
+
+        let args =
+            Arguments::try_parse_from(["dtfterminal", "-c", "a.json", "b.json", "-k"]).unwrap();
+        assert!(args.fail_on.is_empty());
+    }
+
+    #[test]
+    fn test_severity_arguments() {
+        let args = Arguments::try_parse_from([
+            "dtfterminal",
+            "-c",
+            "a.json",
+            "b.json",
+            "-k",
+            "--min-severity",
+            "medium",
+            "--severity",
+            "key=high",
+        ])
+        .unwrap();
+        assert_eq!(args.min_severity, Some(Severity::Medium));
+        assert_eq!(
+            args.severity,
+            vec![SeverityOverride {
+                kind: DiffKind::Key,
+                severity: Severity::High
+            }]
+        );
+
+        assert!(Arguments::try_parse_from([
+            "dtfterminal",
+            "-c",
+            "a.json",
+            "b.json",
+            "-k",
+            "--severity",
+            "key",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_check_args_with_conflicting_arguments() {
+        let error = Arguments::try_parse_from([
+            "dtfterminal",
+            "-c",
+            "a.json",
+            "b.json",
+            "-r",
+            "diffs.json",
+            "-k",
+            "--check-args",
+        ])
+        .unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_structure_only_arguments() {
+        let args = Arguments::try_parse_from([
+            "dtfterminal",
+            "-c",
+            "a.json",
+            "b.json",
+            "--structure-only",
+        ])
+        .unwrap();
+        assert!(args.structure_only);
+
+        let error = Arguments::try_parse_from([
+            "dtfterminal",
+            "-c",
+            "a.json",
+            "b.json",
+            "--structure-only",
+            "-v",
+        ])
+        .unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_count_only_with_output_files() {
+        for output in ["-w", "-b", "--junit", "--json-patch", "--yaml"] {
+            let error = Arguments::try_parse_from([
+                "dtfterminal",
+                "-c",
+                "a.json",
+                "b.json",
+                "--count-only",
+                output,
+            ])
+            .unwrap_err();
+            assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+        }
+    }
+
+    #[test]
+    fn test_check_dirs_with_output_files() {
+        for output in ["-w", "-b", "--junit", "--json-patch", "--yaml"] {
+            let error =
+                Arguments::try_parse_from(["dtfterminal", "-d", "dir_a", "dir_b", "-k", output])
+                    .unwrap_err();
+            assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+        }
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_timeout("0.5"), Ok(Duration::from_millis(500)));
+        assert!(parse_timeout("-1").is_err());
+        assert!(parse_timeout("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_read_retry() {
+        assert_eq!(parse_read_retry("3"), Ok(3));
+        assert_eq!(parse_read_retry("20"), Ok(20));
+        assert!(parse_read_retry("21").is_err());
+        assert!(parse_read_retry("-1").is_err());
+    }
+
+    #[test]
+    fn test_parse_thousands_sep() {
+        assert_eq!(parse_thousands_sep(" "), Ok(" ".to_owned()));
+        assert_eq!(parse_thousands_sep("'"), Ok("'".to_owned()));
+        assert!(parse_thousands_sep(".").is_err());
+    }
+
+    #[test]
+    fn test_check_args_with_valid_arguments() {
+        let args = Arguments::try_parse_from([
+            "dtfterminal",
+            "-c",
+            "missing_a.json",
+            "missing_b.json",
+            "-k",
+            "--check-args",
+        ])
+        .unwrap();
+        assert!(args.check_args);
+    }
+
+    #[test]
+    fn test_profile_instead_of_diff_options() {
+        let args =
+            Arguments::try_parse_from(["dtfterminal", "-c", "a.json", "b.json", "--profile", "ci"])
+                .unwrap();
+        assert_eq!(args.profile, Some("ci".to_owned()));
+
+        let error =
+            Arguments::try_parse_from(["dtfterminal", "-c", "a.json", "b.json"]).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            clap::error::ErrorKind::MissingRequiredArgument
+        );
+    }
+
+    #[test]
+    fn test_self_compare_arguments() {
+        let args = Arguments::try_parse_from([
+            "dtfterminal",
+            "--self-compare",
+            "config.json",
+            "/staging",
+            "/production",
+            "-v",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.self_compare,
+            vec!["config.json", "/staging", "/production"]
+        );
+    }
+
+    #[test]
+    fn test_git_arguments() {
+        let args = Arguments::try_parse_from([
+            "dtfterminal",
+            "--git",
+            "main",
+            "HEAD",
+            "config.json",
+            "-v",
+        ])
+        .unwrap();
+        assert_eq!(args.git, vec!["main", "HEAD", "config.json"]);
+
+        let args = Arguments::try_parse_from([
+            "dtfterminal",
+            "--git",
+            "main",
+            "HEAD",
+            "my config.json",
+            "-v",
+        ])
+        .unwrap();
+        assert_eq!(args.git, vec!["main", "HEAD", "my config.json"]);
+    }
+
+    #[test]
+    fn test_write_to_file_and_browser_view_together() {
+        let args = Arguments::try_parse_from([
+            "dtfterminal",
+            "-c",
+            "a.json",
+            "b.json",
+            "-k",
+            "-w",
+            "diffs.json",
+            "-b",
+            "diffs.html",
+        ])
+        .unwrap();
+
+        assert_eq!(args.write_to_file, Some("diffs.json".to_owned()));
+        assert_eq!(args.browser_view, Some("diffs.html".to_owned()));
+    }
+
+    #[test]
+    fn test_json_patch_with_reshaped_data() {
+        let args = Arguments::try_parse_from([
+            "dtfterminal",
+            "-c",
+            "a.json",
+            "b.json",
+            "-v",
+            "--json-patch",
+            "patch.json",
+        ]);
+        assert!(args.is_ok());
+
+        let args = Arguments::try_parse_from([
+            "dtfterminal",
+            "-c",
+            "a.json",
+            "b.json",
+            "-v",
+            "--json-patch",
+            "patch.json",
+            "--array-key",
+            "id",
+        ]);
+        assert!(args.is_err());
+    }
 }
@@ -1,5 +1,7 @@
+use std::process::ExitCode;
+
 use dtfterminal::{dtfterminal_types::DtfError, run};
 
-fn main() -> Result<(), DtfError> {
+fn main() -> Result<ExitCode, DtfError> {
     run()
 }
@@ -0,0 +1,71 @@
+use colored::Colorize;
+use term_table::{
+    row::Row,
+    table_cell::{Alignment, TableCell},
+};
+
+use crate::dtfterminal_types::{MovedElement, TableContext, WorkingContext};
+
+/// What a moved array item means, shown if the user asked for explanations
+const MOVE_EXPLANATION: &str =
+    "The item is present in the arrays at the same key in both files, but at a different position.";
+
+/// Table to display the moved array items in the terminal
+pub struct MoveTable<'a> {
+    context: TableContext<'a>,
+}
+
+impl<'a> MoveTable<'a> {
+    pub fn new(data: &[MovedElement], working_context: &'a WorkingContext) -> MoveTable<'a> {
+        let mut table = MoveTable {
+            context: TableContext::new(working_context),
+        };
+        table.add_header();
+        table.add_rows(data);
+        table
+    }
+
+    /// Returns the built terminal table string
+    pub fn render(&self) -> String {
+        self.context.render()
+    }
+
+    fn add_header(&mut self) {
+        let (file_name_a, file_name_b) = self.context.working_context().get_file_names();
+        let file_names = format!("{} \u{2192} {}", file_name_a, file_name_b);
+        let title = self
+            .context
+            .working_context()
+            .config
+            .theme
+            .color_title("Moved Array Items");
+        self.context.add_row(Row::new(vec![TableCell::builder(title)
+            .col_span(3)
+            .alignment(Alignment::Center)]));
+        if self.context.working_context().config.explain {
+            self.context.add_row(Row::new(vec![TableCell::builder(
+                MOVE_EXPLANATION.italic(),
+            )
+            .col_span(3)
+            .alignment(Alignment::Center)]));
+        }
+        self.context.add_row(Row::new(vec![
+            TableCell::new("Key"),
+            TableCell::new("Item"),
+            TableCell::new(file_names),
+        ]));
+    }
+
+    fn add_rows(&mut self, data: &[MovedElement]) {
+        for moved in data.iter().take(self.context.row_limit()) {
+            self.context.add_row(Row::new(vec![
+                TableCell::new(self.context.key_text(&moved.key, || {
+                    format!("moved from index {} to {}", moved.from, moved.to)
+                })),
+                TableCell::new(&moved.value),
+                TableCell::new(format!("[{}] \u{2192} [{}]", moved.from, moved.to)),
+            ]));
+        }
+        self.context.add_truncated_row(data.len());
+    }
+}
@@ -0,0 +1,332 @@
+use colored::Colorize;
+use indexmap::IndexMap;
+use libdtf::core::diff_types::ArrayDiffDesc;
+
+use crate::{
+    dtfterminal_types::{
+        iter_diffs, AnyDiff, DiffCollection, DiffKind, MovedElement, WorkingContext,
+    },
+    utils::{compact_data, describe_array_diff, split_key},
+};
+
+/// Renders the differences as plain text, one line per difference under a heading per kind.
+/// Only the kinds the user wants to see are rendered, and kinds without differences are skipped.
+pub fn render_plain(diffs: &DiffCollection, context: &WorkingContext) -> Vec<String> {
+    let config = &context.config;
+    let sections = [
+        (DiffKind::Key, "Key Differences", config.render_key_diffs),
+        (DiffKind::Type, "Type Differences", config.render_type_diffs),
+        (
+            DiffKind::Value,
+            "Value Differences",
+            config.render_value_diffs,
+        ),
+        (
+            DiffKind::Array,
+            "Array Differences",
+            config.render_array_diffs,
+        ),
+    ];
+    let all_diffs = iter_diffs(diffs).collect::<Vec<AnyDiff>>();
+
+    sections
+        .into_iter()
+        .filter(|(_, _, render)| *render)
+        .filter_map(|(kind, heading, _)| {
+            let lines = all_diffs
+                .iter()
+                .filter(|diff| diff.is_kind(kind))
+                .map(|diff| format_line(diff, context))
+                .collect::<Vec<String>>();
+            if lines.is_empty() {
+                return None;
+            }
+            Some(format!("{}\n{}\n", heading.bold(), lines.join("\n")))
+        })
+        .collect()
+}
+
+/// Renders the moved array items as plain text, one line per item under a heading
+pub fn render_moves_plain(moved_elements: &[MovedElement]) -> String {
+    let lines = moved_elements
+        .iter()
+        .map(|moved| {
+            format!(
+                "[moved] {}: {}: [{}] -> [{}]",
+                moved.key,
+                compact_data(&moved.value, usize::MAX),
+                moved.from,
+                moved.to
+            )
+        })
+        .collect::<Vec<String>>();
+    format!(
+        "{}
+{}
+",
+        "Moved Array Items".bold(),
+        lines.join("\n")
+    )
+}
+
+/// Renders the differences as an outline, with a section for every top level key holding differences.
+/// The sections follow the order the keys were first found in, and list the differences of every kind under the key.
+pub fn render_grouped(diffs: &DiffCollection, context: &WorkingContext) -> Vec<String> {
+    let config = &context.config;
+    let mut sections: IndexMap<String, Vec<String>> = IndexMap::new();
+    let rendered_diffs = iter_diffs(diffs).filter(|diff| match diff {
+        AnyDiff::Key(_) => config.render_key_diffs,
+        AnyDiff::Type(_) => config.render_type_diffs,
+        AnyDiff::Value(_) => config.render_value_diffs,
+        AnyDiff::Array(_) => config.render_array_diffs,
+    });
+    for diff in rendered_diffs {
+        let top_level_key = split_key(diff.key(), config.json_pointer)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        sections
+            .entry(top_level_key)
+            .or_default()
+            .push(format!("  {}", format_line(&diff, context)));
+    }
+
+    sections
+        .into_iter()
+        .map(|(key, lines)| format!("{}\n{}\n", key.bold(), lines.join("\n")))
+        .collect()
+}
+
+/// Renders the differences in three sections by the direction of the change: added in the second file, removed from it, and modified.
+/// * Key differences are added if the second file has the key, removed otherwise
+/// * Type and Value differences are modified
+/// * Array differences are added if the second file has the item, removed otherwise
+///
+/// Sections without differences are skipped.
+pub fn render_by_direction(diffs: &DiffCollection, context: &WorkingContext) -> Vec<String> {
+    let config = &context.config;
+    let (_, file_b) = context.get_file_names();
+    let mut added = vec![];
+    let mut removed = vec![];
+    let mut modified = vec![];
+    for diff in iter_diffs(diffs) {
+        let (render, section) = match &diff {
+            AnyDiff::Key(kd) if kd.has == file_b => (config.render_key_diffs, &mut added),
+            AnyDiff::Key(_) => (config.render_key_diffs, &mut removed),
+            AnyDiff::Type(_) => (config.render_type_diffs, &mut modified),
+            AnyDiff::Value(_) => (config.render_value_diffs, &mut modified),
+            AnyDiff::Array(ad) => match ad.descriptor {
+                ArrayDiffDesc::BHas | ArrayDiffDesc::AMisses => {
+                    (config.render_array_diffs, &mut added)
+                }
+                ArrayDiffDesc::AHas | ArrayDiffDesc::BMisses => {
+                    (config.render_array_diffs, &mut removed)
+                }
+            },
+        };
+        if render {
+            section.push(format!("  {}", format_line(&diff, context)));
+        }
+    }
+
+    [
+        (format!("Added in {}", file_b), added),
+        (format!("Removed from {}", file_b), removed),
+        ("Modified".to_owned(), modified),
+    ]
+    .into_iter()
+    .filter(|(_, lines)| !lines.is_empty())
+    .map(|(heading, lines)| format!("{}\n{}\n", heading.bold(), lines.join("\n")))
+    .collect()
+}
+
+/// Formats a difference as a single line, starting with its kind in brackets
+fn format_line(diff: &AnyDiff, context: &WorkingContext) -> String {
+    match diff {
+        AnyDiff::Key(kd) => format!("[key] {}: only in {}", kd.key, kd.has),
+        AnyDiff::Type(td) => format!("[type] {}: {} -> {}", td.key, td.type1, td.type2),
+        AnyDiff::Value(vd) => format!(
+            "[value] {}: {} -> {}",
+            vd.key,
+            compact_data(&vd.value1, usize::MAX),
+            compact_data(&vd.value2, usize::MAX)
+        ),
+        AnyDiff::Array(ad) => format!(
+            "[array] {}: {}: {}",
+            ad.key,
+            describe_array_diff(&ad.descriptor, context.get_file_names()),
+            compact_data(&ad.value, usize::MAX)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libdtf::core::diff_types::{ArrayDiff, KeyDiff, TypeDiff, ValueDiff};
+
+    use crate::dtfterminal_types::ConfigBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_render_plain() {
+        let diffs: DiffCollection = (
+            Some(vec![KeyDiff {
+                key: "address.zip".to_owned(),
+                has: "file_b.json".to_owned(),
+                misses: "file_a.json".to_owned(),
+            }]),
+            Some(vec![TypeDiff {
+                key: "age".to_owned(),
+                type1: "string".to_owned(),
+                type2: "number".to_owned(),
+            }]),
+            Some(vec![ValueDiff {
+                key: "nested.diff_string".to_owned(),
+                value1: "\"a\"".to_owned(),
+                value2: "\"b\"".to_owned(),
+            }]),
+            Some(vec![ArrayDiff {
+                key: "tags".to_owned(),
+                descriptor: ArrayDiffDesc::BMisses,
+                value: "{\n  \"name\": \"sale\"\n}".to_owned(),
+            }]),
+        );
+
+        let rendered = render_plain(&diffs, &get_working_context());
+
+        assert_eq!(
+            rendered,
+            vec![
+                format!(
+                    "{}\n[key] address.zip: only in file_b.json\n",
+                    "Key Differences".bold()
+                ),
+                format!(
+                    "{}\n[type] age: string -> number\n",
+                    "Type Differences".bold()
+                ),
+                format!(
+                    "{}\n[value] nested.diff_string: \"a\" -> \"b\"\n",
+                    "Value Differences".bold()
+                ),
+                format!(
+                    "{}\n[array] tags: missing from file_b.json: {{\"name\":\"sale\"}}\n",
+                    "Array Differences".bold()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_plain_skips_empty_kinds() {
+        let diffs: DiffCollection = (Some(vec![]), None, None, None);
+
+        assert!(render_plain(&diffs, &get_working_context()).is_empty());
+    }
+
+    #[test]
+    fn test_render_grouped() {
+        let diffs: DiffCollection = (
+            Some(vec![KeyDiff {
+                key: "order.discount".to_owned(),
+                has: "file_a.json".to_owned(),
+                misses: "file_b.json".to_owned(),
+            }]),
+            None,
+            Some(vec![
+                ValueDiff {
+                    key: "user.name".to_owned(),
+                    value1: "Sarah".to_owned(),
+                    value2: "John".to_owned(),
+                },
+                ValueDiff {
+                    key: "order.total".to_owned(),
+                    value1: "10".to_owned(),
+                    value2: "12".to_owned(),
+                },
+            ]),
+            Some(vec![ArrayDiff {
+                key: "user.roles".to_owned(),
+                descriptor: ArrayDiffDesc::AHas,
+                value: "admin".to_owned(),
+            }]),
+        );
+
+        let rendered = render_grouped(&diffs, &get_working_context());
+
+        assert_eq!(
+            rendered,
+            vec![
+                format!(
+                    "{}\n  [key] order.discount: only in file_a.json\n  [value] order.total: 10 -> 12\n",
+                    "order".bold()
+                ),
+                format!(
+                    "{}\n  [value] user.name: Sarah -> John\n  [array] user.roles: only in file_a.json: admin\n",
+                    "user".bold()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_by_direction() {
+        let diffs: DiffCollection = (
+            Some(vec![KeyDiff {
+                key: "discount".to_owned(),
+                has: "file_b.json".to_owned(),
+                misses: "file_a.json".to_owned(),
+            }]),
+            None,
+            Some(vec![ValueDiff {
+                key: "name".to_owned(),
+                value1: "Pen".to_owned(),
+                value2: "Pencil".to_owned(),
+            }]),
+            Some(vec![ArrayDiff {
+                key: "tags".to_owned(),
+                descriptor: ArrayDiffDesc::AHas,
+                value: "sale".to_owned(),
+            }]),
+        );
+
+        let rendered = render_by_direction(&diffs, &get_working_context());
+
+        assert_eq!(
+            rendered,
+            vec![
+                format!(
+                    "{}\n  [key] discount: only in file_b.json\n",
+                    "Added in file_b.json".bold()
+                ),
+                format!(
+                    "{}\n  [array] tags: only in file_a.json: sale\n",
+                    "Removed from file_b.json".bold()
+                ),
+                format!("{}\n  [value] name: Pen -> Pencil\n", "Modified".bold()),
+            ]
+        );
+    }
+
+    fn get_working_context() -> WorkingContext {
+        let working_file_a = libdtf::core::diff_types::WorkingFile::new("file_a.json".to_string());
+        let working_file_b = libdtf::core::diff_types::WorkingFile::new("file_b.json".to_string());
+        let lib_working_context = libdtf::core::diff_types::WorkingContext::new(
+            working_file_a,
+            working_file_b,
+            libdtf::core::diff_types::Config {
+                array_same_order: false,
+            },
+        );
+        WorkingContext::new(
+            lib_working_context,
+            ConfigBuilder::new()
+                .render_key_diffs(true)
+                .render_type_diffs(true)
+                .render_value_diffs(true)
+                .render_array_diffs(true)
+                .build(),
+        )
+    }
+}
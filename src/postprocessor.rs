@@ -0,0 +1,837 @@
+use libdtf::core::diff_types::{ArrayDiff, ArrayDiffDesc, KeyDiff, TypeDiff, ValueDiff};
+use regex::Regex;
+
+use crate::{
+    dtfterminal_types::{Config, DiffCollection, DtfError, ValueType},
+    file_handler::FileHandler,
+    preprocessor::{unescape_dots, ESCAPED_DOT},
+    utils::{escape_json_pointer_segment, split_index_segments},
+};
+
+/// Modifies the found differences according to the user configuration, before they get rendered or saved
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+pub fn process_diffs(diffs: DiffCollection, config: &Config) -> Result<DiffCollection, DtfError> {
+    let mut diffs = diffs;
+
+    if !config.no_dedup {
+        diffs = remove_duplicates(diffs);
+    }
+
+    if config.swap {
+        diffs = swap_sides(diffs);
+    }
+
+    if config.smart_dates {
+        diffs = remove_equal_instants(diffs)?;
+    }
+
+    if let Some(separator) = &config.key_separator {
+        diffs = map_keys(diffs, |key| separate_key(key, separator, config));
+    }
+    diffs = map_values(diffs, |value| value.replace(ESCAPED_DOT, "."));
+
+    if !config.only_patterns.is_empty() {
+        let patterns = compile_patterns(&config.only_patterns)?;
+        diffs = filter_by_key(diffs, |key| {
+            let key = unescape_dots(key);
+            patterns.iter().any(|pattern| pattern.is_match(&key))
+        });
+    }
+
+    if !config.ignore_patterns.is_empty() || !config.ignore_globs.is_empty() {
+        let mut patterns = compile_patterns(&config.ignore_patterns)?;
+        let separator = config.key_separator.as_deref().unwrap_or(".");
+        patterns.extend(compile_globs(&config.ignore_globs, separator)?);
+        diffs = filter_by_key(diffs, |key| {
+            let key = unescape_dots(key);
+            !patterns.iter().any(|pattern| pattern.is_match(&key))
+        });
+    }
+
+    if !config.ignore_types.is_empty() {
+        diffs = remove_ignored_types(diffs, &config.ignore_types);
+    }
+
+    diffs = map_keys(diffs, |key| finish_key(key, config));
+
+    if let Some(max_value_len) = config.max_value_len {
+        diffs = truncate_values(diffs, max_value_len);
+    }
+
+    if let Some(baseline) = &config.baseline {
+        diffs = remove_baseline_diffs(diffs, &FileHandler::load_baseline(baseline)?);
+    }
+
+    Ok(diffs)
+}
+
+/// Returns the key of a difference found by the check, as it's shown after the differences are processed
+pub(crate) fn display_key(key: &str, config: &Config) -> String {
+    match &config.key_separator {
+        Some(separator) => finish_key(&separate_key(key, separator, config), config),
+        None => finish_key(key, config),
+    }
+}
+
+/// Joins the segments of a key with the separator given by the user, restoring the dots in the names of keys
+fn separate_key(key: &str, separator: &str, config: &Config) -> String {
+    let key = if is_array_matched(config) {
+        key.replace(".[", "[")
+    } else {
+        key.to_owned()
+    };
+    key.replace('.', separator).replace(ESCAPED_DOT, ".")
+}
+
+/// Brings a key to the form it's shown in, after the differences are filtered by their keys
+fn finish_key(key: &str, config: &Config) -> String {
+    let key = if is_array_matched(config) {
+        key.replace(".[", "[")
+    } else {
+        key.to_owned()
+    };
+    if config.json_pointer {
+        to_json_pointer(&key)
+    } else {
+        unescape_dots(&key).into_owned()
+    }
+}
+
+/// Checks if the items of arrays are matched by the user, so their keys follow the key of the array without a separator
+fn is_array_matched(config: &Config) -> bool {
+    config.array_key.is_some() || config.array_same_order
+}
+
+/// Compiles the regular expressions given by the user
+fn compile_patterns(patterns: &[String]) -> Result<Vec<Regex>, DtfError> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Regex::new(pattern).map_err(|e| {
+                DtfError::DiffError(format!("Invalid regular expression {}: {}", pattern, e))
+            })
+        })
+        .collect()
+}
+
+/// Compiles the glob patterns given by the user into regular expressions matching whole keys, with their segments joined by the separator
+fn compile_globs(globs: &[String], separator: &str) -> Result<Vec<Regex>, DtfError> {
+    globs
+        .iter()
+        .map(|glob| {
+            Regex::new(&glob_to_regex(glob, separator))
+                .map_err(|e| DtfError::DiffError(format!("Invalid glob pattern {}: {}", glob, e)))
+        })
+        .collect()
+}
+
+/// Converts a glob pattern to a regular expression.
+/// `**` matches anything, `*` matches anything within a single key segment and everything else matches itself.
+/// A segment ends before the separator, or before its first character if the separator is longer.
+fn glob_to_regex(glob: &str, separator: &str) -> String {
+    let segment = match separator.chars().next() {
+        Some(first) => format!("[^{}]*", regex::escape(&first.to_string())),
+        None => ".*".to_owned(),
+    };
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '*' {
+            regex.push_str(&regex::escape(&c.to_string()));
+        } else if chars.next_if_eq(&'*').is_some() {
+            regex.push_str(".*");
+        } else {
+            regex.push_str(&segment);
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+/// Keeps only the differences with keys accepted by the predicate
+fn filter_by_key(diffs: DiffCollection, keep: impl Fn(&str) -> bool) -> DiffCollection {
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = diffs;
+    (
+        key_diffs.map(|kd| kd.into_iter().filter(|d| keep(&d.key)).collect()),
+        type_diffs.map(|td| td.into_iter().filter(|d| keep(&d.key)).collect()),
+        value_diffs.map(|vd| vd.into_iter().filter(|d| keep(&d.key)).collect()),
+        array_diffs.map(|ad| ad.into_iter().filter(|d| keep(&d.key)).collect()),
+    )
+}
+
+/// Removes the Value differences between timestamps of the same instant, e.g. written with a different precision or time zone
+#[cfg(feature = "dates")]
+fn remove_equal_instants(diffs: DiffCollection) -> Result<DiffCollection, DtfError> {
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = diffs;
+    Ok((
+        key_diffs,
+        type_diffs,
+        value_diffs.map(|vd| {
+            vd.into_iter()
+                .filter(|d| !is_same_instant(&d.value1, &d.value2))
+                .collect()
+        }),
+        array_diffs,
+    ))
+}
+
+#[cfg(not(feature = "dates"))]
+fn remove_equal_instants(_diffs: DiffCollection) -> Result<DiffCollection, DtfError> {
+    Err(DtfError::DiffError(
+        "Comparing dates needs dtfterminal to be built with the dates feature".to_owned(),
+    ))
+}
+
+/// Checks if both values are RFC 3339 timestamps of the same instant. Values, that are not timestamps, are never the same instant
+#[cfg(feature = "dates")]
+fn is_same_instant(value1: &str, value2: &str) -> bool {
+    let parse = |value: &str| {
+        let text = serde_json::from_str::<String>(value).unwrap_or_else(|_| value.to_owned());
+        chrono::DateTime::parse_from_rfc3339(&text).ok()
+    };
+    match (parse(value1), parse(value2)) {
+        (Some(instant1), Some(instant2)) => instant1 == instant2,
+        _ => false,
+    }
+}
+
+/// Removes the Type differences, where either of the values is of an ignored type.
+/// The Value differences hold no types, so they are removed while checking, where the values are known.
+fn remove_ignored_types(diffs: DiffCollection, ignored: &[ValueType]) -> DiffCollection {
+    let is_ignored = |name: &str| ValueType::from_name(name).is_some_and(|t| ignored.contains(&t));
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = diffs;
+    (
+        key_diffs,
+        type_diffs.map(|td| {
+            td.into_iter()
+                .filter(|d| !is_ignored(&d.type1) && !is_ignored(&d.type2))
+                .collect()
+        }),
+        value_diffs,
+        array_diffs,
+    )
+}
+
+/// Removes the differences reported multiple times, keeping the first occurrence.
+/// Equal Array differences are the items repeated in an array, e.g. both 1s of [1, 1] against [2], so they are all kept.
+fn remove_duplicates(diffs: DiffCollection) -> DiffCollection {
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = diffs;
+    (
+        key_diffs.map(dedup),
+        type_diffs.map(dedup),
+        value_diffs.map(dedup),
+        array_diffs,
+    )
+}
+
+/// Keeps only the first of the equal differences, in their original order
+fn dedup<T: PartialEq>(diffs: Vec<T>) -> Vec<T> {
+    let mut unique_diffs = Vec::with_capacity(diffs.len());
+    for diff in diffs {
+        if !unique_diffs.contains(&diff) {
+            unique_diffs.push(diff);
+        }
+    }
+    unique_diffs
+}
+
+/// Turns the differences around, as if the files were checked in the opposite order.
+/// Key differences name the files themselves instead of their position, so they stay as they are.
+fn swap_sides(diffs: DiffCollection) -> DiffCollection {
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = diffs;
+    (
+        key_diffs,
+        type_diffs.map(|td| {
+            td.into_iter()
+                .map(|d| TypeDiff {
+                    key: d.key,
+                    type1: d.type2,
+                    type2: d.type1,
+                })
+                .collect()
+        }),
+        value_diffs.map(|vd| {
+            vd.into_iter()
+                .map(|d| ValueDiff {
+                    key: d.key,
+                    value1: d.value2,
+                    value2: d.value1,
+                })
+                .collect()
+        }),
+        array_diffs.map(|ad| {
+            ad.into_iter()
+                .map(|d| ArrayDiff {
+                    descriptor: match d.descriptor {
+                        ArrayDiffDesc::AHas => ArrayDiffDesc::BHas,
+                        ArrayDiffDesc::AMisses => ArrayDiffDesc::BMisses,
+                        ArrayDiffDesc::BHas => ArrayDiffDesc::AHas,
+                        ArrayDiffDesc::BMisses => ArrayDiffDesc::AMisses,
+                    },
+                    ..d
+                })
+                .collect()
+        }),
+    )
+}
+
+/// Shortens the values of Value and Array differences to at most `max_len` characters, followed by a marker
+fn truncate_values(diffs: DiffCollection, max_len: usize) -> DiffCollection {
+    map_values(diffs, |value| truncate_value(value, max_len))
+}
+
+/// Shortens the value to at most `max_len` characters, followed by a marker if anything was cut off
+fn truncate_value(value: String, max_len: usize) -> String {
+    match value.char_indices().nth(max_len) {
+        Some((end, _)) => format!("{}\u{2026}[truncated]", &value[..end]),
+        None => value,
+    }
+}
+
+/// Removes the differences, that are present in the baseline too, leaving only the new ones.
+/// The keys are compared as they are shown, so the baseline has to be saved with the same key separator.
+fn remove_baseline_diffs(diffs: DiffCollection, baseline: &DiffCollection) -> DiffCollection {
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = diffs;
+    let (baseline_key_diffs, baseline_type_diffs, baseline_value_diffs, baseline_array_diffs) =
+        baseline;
+    (
+        key_diffs.map(|kd| remove_known(kd, baseline_key_diffs)),
+        type_diffs.map(|td| remove_known(td, baseline_type_diffs)),
+        value_diffs.map(|vd| remove_known(vd, baseline_value_diffs)),
+        array_diffs.map(|ad| remove_known(ad, baseline_array_diffs)),
+    )
+}
+
+/// Keeps the differences missing from the known ones
+fn remove_known<T: PartialEq>(diffs: Vec<T>, known: &Option<Vec<T>>) -> Vec<T> {
+    let known = known.as_deref().unwrap_or_default();
+    diffs.into_iter().filter(|d| !known.contains(d)).collect()
+}
+
+/// Converts a key like `nested.items[0]` to a JSON Pointer like `/nested/items/0`.
+/// Dots in the keys of the data are escaped while checking, so they stay in their segment, e.g. `/a.b/c`.
+pub fn to_json_pointer(key: &str) -> String {
+    key.split('.')
+        .flat_map(split_index_segments)
+        .map(|segment| format!("/{}", escape_json_pointer_segment(&unescape_dots(segment))))
+        .collect()
+}
+
+/// Replaces the values of every Value and Array difference with the result of the mapping
+fn map_values(diffs: DiffCollection, map: impl Fn(String) -> String) -> DiffCollection {
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = diffs;
+    (
+        key_diffs,
+        type_diffs,
+        value_diffs.map(|vd| {
+            vd.into_iter()
+                .map(|d| ValueDiff {
+                    value1: map(d.value1),
+                    value2: map(d.value2),
+                    ..d
+                })
+                .collect()
+        }),
+        array_diffs.map(|ad| {
+            ad.into_iter()
+                .map(|d| ArrayDiff {
+                    value: map(d.value),
+                    ..d
+                })
+                .collect()
+        }),
+    )
+}
+
+/// Replaces the key of every difference with the result of the mapping
+fn map_keys(diffs: DiffCollection, map: impl Fn(&str) -> String) -> DiffCollection {
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = diffs;
+    (
+        key_diffs.map(|kd| {
+            kd.into_iter()
+                .map(|d| KeyDiff {
+                    key: map(&d.key),
+                    ..d
+                })
+                .collect()
+        }),
+        type_diffs.map(|td| {
+            td.into_iter()
+                .map(|d| TypeDiff {
+                    key: map(&d.key),
+                    ..d
+                })
+                .collect()
+        }),
+        value_diffs.map(|vd| {
+            vd.into_iter()
+                .map(|d| ValueDiff {
+                    key: map(&d.key),
+                    ..d
+                })
+                .collect()
+        }),
+        array_diffs.map(|ad| {
+            ad.into_iter()
+                .map(|d| ArrayDiff {
+                    key: map(&d.key),
+                    ..d
+                })
+                .collect()
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{dtfterminal_types::ConfigBuilder, file_handler::FileHandler};
+
+    use super::*;
+
+    #[test]
+    fn test_ignore_patterns_from_file_and_arguments() {
+        let mut ignore_patterns = vec!["^age$".to_owned()];
+        ignore_patterns
+            .extend(FileHandler::read_ignore_file("test_data/ignore_rules.txt").unwrap());
+        let config = ConfigBuilder::new()
+            .ignore_patterns(ignore_patterns)
+            .build();
+
+        let (key_diffs, type_diffs, value_diffs, array_diffs) =
+            process_diffs(get_diffs(), &config).unwrap();
+
+        assert_eq!(
+            key_diffs.unwrap(),
+            vec![KeyDiff {
+                key: "employment.employer".to_owned(),
+                has: "a.json".to_owned(),
+                misses: "b.json".to_owned(),
+            }]
+        );
+        assert!(type_diffs.unwrap().is_empty());
+        assert_eq!(
+            value_diffs.unwrap(),
+            vec![ValueDiff {
+                key: "name".to_owned(),
+                value1: "Sarah".to_owned(),
+                value2: "John".to_owned(),
+            }]
+        );
+        assert_eq!(array_diffs.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_ignore_pattern() {
+        let config = ConfigBuilder::new()
+            .ignore_patterns(vec!["(".to_owned()])
+            .build();
+
+        assert!(process_diffs(get_diffs(), &config).is_err());
+    }
+
+    #[test]
+    fn test_only_patterns() {
+        let config = ConfigBuilder::new()
+            .only_patterns(vec!["price.*".to_owned()])
+            .build();
+        let diffs = (
+            Some(vec![KeyDiff {
+                key: "discount".to_owned(),
+                has: "a.json".to_owned(),
+                misses: "b.json".to_owned(),
+            }]),
+            Some(vec![TypeDiff {
+                key: "price_currency".to_owned(),
+                type1: "string".to_owned(),
+                type2: "null".to_owned(),
+            }]),
+            Some(vec![
+                ValueDiff {
+                    key: "name".to_owned(),
+                    value1: "Pen".to_owned(),
+                    value2: "Pencil".to_owned(),
+                },
+                ValueDiff {
+                    key: "price".to_owned(),
+                    value1: "10".to_owned(),
+                    value2: "12".to_owned(),
+                },
+            ]),
+            Some(vec![ArrayDiff {
+                key: "tags".to_owned(),
+                descriptor: ArrayDiffDesc::BHas,
+                value: "sale".to_owned(),
+            }]),
+        );
+
+        let (key_diffs, type_diffs, value_diffs, array_diffs) =
+            process_diffs(diffs, &config).unwrap();
+
+        assert!(key_diffs.unwrap().is_empty());
+        assert_eq!(type_diffs.unwrap()[0].key, "price_currency");
+        assert_eq!(
+            value_diffs.unwrap(),
+            vec![ValueDiff {
+                key: "price".to_owned(),
+                value1: "10".to_owned(),
+                value2: "12".to_owned(),
+            }]
+        );
+        assert!(array_diffs.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_ignore_wins_over_only() {
+        let config = ConfigBuilder::new()
+            .only_patterns(vec!["^phone_numbers".to_owned(), "^name$".to_owned()])
+            .ignore_patterns(vec!["description$".to_owned()])
+            .build();
+
+        let (key_diffs, _, value_diffs, _) = process_diffs(get_diffs(), &config).unwrap();
+
+        assert!(key_diffs.unwrap().is_empty());
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "name");
+    }
+
+    #[test]
+    fn test_ignore_globs() {
+        let config = ConfigBuilder::new()
+            .ignore_globs(vec!["user.*.token".to_owned()])
+            .build();
+        let diffs = (
+            None,
+            None,
+            Some(
+                ["user.a.token", "user.b.token", "user.a.name"]
+                    .into_iter()
+                    .map(|key| ValueDiff {
+                        key: key.to_owned(),
+                        value1: "x".to_owned(),
+                        value2: "y".to_owned(),
+                    })
+                    .collect(),
+            ),
+            None,
+        );
+
+        let (_, _, value_diffs, _) = process_diffs(diffs, &config).unwrap();
+
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "user.a.name");
+    }
+
+    #[cfg(feature = "dates")]
+    #[test]
+    fn test_smart_dates() {
+        let config = ConfigBuilder::new().smart_dates(true).build();
+        let diffs = (
+            None,
+            None,
+            Some(vec![
+                ValueDiff {
+                    key: "created".to_owned(),
+                    value1: "\"2023-01-01T00:00:00Z\"".to_owned(),
+                    value2: "\"2023-01-01T00:00:00.000Z\"".to_owned(),
+                },
+                ValueDiff {
+                    key: "updated".to_owned(),
+                    value1: "\"2023-01-01T02:00:00+02:00\"".to_owned(),
+                    value2: "\"2023-01-01T00:00:00Z\"".to_owned(),
+                },
+                ValueDiff {
+                    key: "deleted".to_owned(),
+                    value1: "\"2023-01-01T00:00:00Z\"".to_owned(),
+                    value2: "\"2023-01-02T00:00:00Z\"".to_owned(),
+                },
+                ValueDiff {
+                    key: "name".to_owned(),
+                    value1: "\"Pen\"".to_owned(),
+                    value2: "\"Pencil\"".to_owned(),
+                },
+            ]),
+            None,
+        );
+
+        let (_, _, value_diffs, _) = process_diffs(diffs, &config).unwrap();
+
+        let keys: Vec<String> = value_diffs.unwrap().into_iter().map(|d| d.key).collect();
+        assert_eq!(keys, vec!["deleted".to_owned(), "name".to_owned()]);
+    }
+
+    #[test]
+    fn test_ignore_types_of_either_side() {
+        let config = ConfigBuilder::new()
+            .ignore_types(vec![ValueType::Null])
+            .build();
+        let diffs = (
+            None,
+            Some(vec![
+                TypeDiff {
+                    key: "name".to_owned(),
+                    type1: "string".to_owned(),
+                    type2: "null".to_owned(),
+                },
+                TypeDiff {
+                    key: "age".to_owned(),
+                    type1: "string".to_owned(),
+                    type2: "number".to_owned(),
+                },
+            ]),
+            Some(vec![ValueDiff {
+                key: "price".to_owned(),
+                value1: "1".to_owned(),
+                value2: "2".to_owned(),
+            }]),
+            None,
+        );
+
+        let (_, type_diffs, value_diffs, _) = process_diffs(diffs, &config).unwrap();
+
+        let type_diffs = type_diffs.unwrap();
+        assert_eq!(type_diffs.len(), 1);
+        assert_eq!(type_diffs[0].key, "age");
+        assert_eq!(value_diffs.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_glob_to_regex() {
+        let single = Regex::new(&glob_to_regex("user.*.token", ".")).unwrap();
+        assert!(single.is_match("user.a.token"));
+        assert!(!single.is_match("user.a.b.token"));
+        assert!(!single.is_match("userXa.token"));
+
+        let any_depth = Regex::new(&glob_to_regex("user.**.token", ".")).unwrap();
+        assert!(any_depth.is_match("user.a.b.token"));
+        assert!(any_depth.is_match("user.members[0].token"));
+        assert!(!any_depth.is_match("user.a.name"));
+    }
+
+    #[test]
+    fn test_glob_to_regex_with_key_separator() {
+        let slash = Regex::new(&glob_to_regex("user/*/token", "/")).unwrap();
+        assert!(slash.is_match("user/v1.2/token"));
+        assert!(!slash.is_match("user/a/b/token"));
+
+        let colons = Regex::new(&glob_to_regex("user::*::token", "::")).unwrap();
+        assert!(colons.is_match("user::a.b::token"));
+        assert!(!colons.is_match("user::a::b::token"));
+    }
+
+    #[test]
+    fn test_baseline_equal_to_diffs() {
+        let baseline_path = std::env::temp_dir().join("dtfterminal_baseline_equal.json");
+        let baseline_path = baseline_path.to_string_lossy().to_string();
+        write_baseline(&baseline_path, get_diffs());
+        let config = ConfigBuilder::new()
+            .baseline(Some(baseline_path.clone()))
+            .build();
+
+        let (key_diffs, type_diffs, value_diffs, array_diffs) =
+            process_diffs(get_diffs(), &config).unwrap();
+
+        assert!(key_diffs.unwrap().is_empty());
+        assert!(type_diffs.unwrap().is_empty());
+        assert!(value_diffs.unwrap().is_empty());
+        assert!(array_diffs.unwrap().is_empty());
+        std::fs::remove_file(baseline_path).unwrap();
+    }
+
+    #[test]
+    fn test_baseline_keeps_new_diffs() {
+        let baseline_path = std::env::temp_dir().join("dtfterminal_baseline_new.json");
+        let baseline_path = baseline_path.to_string_lossy().to_string();
+        let (key_diffs, type_diffs, mut value_diffs, array_diffs) = get_diffs();
+        let new_diff = value_diffs.as_mut().unwrap().remove(0);
+        write_baseline(
+            &baseline_path,
+            (key_diffs, type_diffs, value_diffs, array_diffs),
+        );
+        let config = ConfigBuilder::new()
+            .baseline(Some(baseline_path.clone()))
+            .build();
+
+        let (key_diffs, _, value_diffs, _) = process_diffs(get_diffs(), &config).unwrap();
+
+        assert!(key_diffs.unwrap().is_empty());
+        assert_eq!(value_diffs.unwrap(), vec![new_diff]);
+        std::fs::remove_file(baseline_path).unwrap();
+    }
+
+    #[test]
+    fn test_duplicates_removed() {
+        let (key_diffs, type_diffs, mut value_diffs, array_diffs) = get_diffs();
+        let duplicate = value_diffs.as_ref().unwrap()[0].clone();
+        value_diffs.as_mut().unwrap().push(duplicate);
+        let diffs = (key_diffs, type_diffs, value_diffs, array_diffs);
+
+        let (_, _, value_diffs, _) =
+            process_diffs(diffs.clone(), &ConfigBuilder::new().build()).unwrap();
+        assert_eq!(value_diffs, get_diffs().2);
+
+        let (_, _, value_diffs, _) =
+            process_diffs(diffs, &ConfigBuilder::new().no_dedup(true).build()).unwrap();
+        assert_eq!(value_diffs.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_repeated_array_items_kept() {
+        let repeated = ArrayDiff::new("items".to_owned(), ArrayDiffDesc::AHas, "1".to_owned());
+        let diffs = (None, None, None, Some(vec![repeated.clone(), repeated]));
+
+        let (_, _, _, array_diffs) = process_diffs(diffs, &ConfigBuilder::new().build()).unwrap();
+
+        assert_eq!(array_diffs.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_to_json_pointer() {
+        assert_eq!(to_json_pointer("nested.diff_string"), "/nested/diff_string");
+        assert_eq!(to_json_pointer("members[0].email"), "/members/0/email");
+        assert_eq!(to_json_pointer("matrix[1][2]"), "/matrix/1/2");
+        assert_eq!(to_json_pointer("paths./usr/bin"), "/paths/~1usr~1bin");
+        assert_eq!(to_json_pointer("a~b"), "/a~0b");
+        assert_eq!(to_json_pointer("a\u{E000}b.c"), "/a.b/c");
+    }
+
+    #[test]
+    fn test_json_pointer_keys() {
+        let config = ConfigBuilder::new().json_pointer(true).build();
+
+        let (key_diffs, _, value_diffs, _) = process_diffs(get_diffs(), &config).unwrap();
+
+        assert_eq!(key_diffs.unwrap()[0].key, "/employment/employer");
+        assert_eq!(
+            value_diffs.unwrap()[1].key,
+            "/phone_numbers/0/notes/description"
+        );
+    }
+
+    #[test]
+    fn test_truncate_values() {
+        let config = ConfigBuilder::new().max_value_len(Some(5)).build();
+
+        let (_, _, value_diffs, array_diffs) = process_diffs(get_diffs(), &config).unwrap();
+
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs[0].value1, "Sarah");
+        assert_eq!(value_diffs[0].value2, "John");
+        assert_eq!(value_diffs[1].value1, "Do no\u{2026}[truncated]");
+        assert_eq!(value_diffs[1].value2, "Call \u{2026}[truncated]");
+        assert_eq!(array_diffs.unwrap()[0].value, "sarah\u{2026}[truncated]");
+    }
+
+    #[test]
+    fn test_truncated_values_saved() {
+        let path = std::env::temp_dir().join("dtfterminal_truncated.json");
+        let path = path.to_string_lossy().to_string();
+        let config = ConfigBuilder::new().max_value_len(Some(3)).build();
+
+        write_baseline(&path, process_diffs(get_diffs(), &config).unwrap());
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(saved.contains("Sar\u{2026}[truncated]"));
+        assert!(!saved.contains("Sarah"));
+    }
+
+    #[test]
+    fn test_key_separator() {
+        let config = ConfigBuilder::new()
+            .check_for_value_diffs(true)
+            .key_separator(Some("/".to_owned()))
+            .build();
+        let data_a = serde_json::json!({ "a": { "b": { "c": 1 } }, "x.y": "1.5" });
+        let data_b = serde_json::json!({ "a": { "b": { "c": 2 } }, "x.y": "2.5" });
+
+        let (_, _, value_diffs, _) = crate::diff_runner::DiffRunner::from_data(
+            "a.json",
+            data_a.as_object().unwrap().clone(),
+            "b.json",
+            data_b.as_object().unwrap().clone(),
+        )
+        .with_config(config)
+        .run()
+        .unwrap();
+
+        let value_diffs = value_diffs.unwrap();
+        let keys: Vec<&str> = value_diffs.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(keys, vec!["a/b/c", "x.y"]);
+        assert_eq!(value_diffs[1].value1, "1.5");
+    }
+
+    #[test]
+    fn test_json_pointer_keys_with_dots() {
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_value_diffs(true)
+            .json_pointer(true)
+            .only_patterns(vec!["^x\\.y".to_owned(), "^a".to_owned()])
+            .build();
+        let data_a = serde_json::json!({ "a": { "b": 1 }, "x.y": { "z": 1 }, "c": 1 });
+        let data_b = serde_json::json!({ "a": { "b": 2 }, "x.y": { "z": 2 }, "c": 2 });
+
+        let (_, _, value_diffs, _) = crate::diff_runner::DiffRunner::from_data(
+            "a.json",
+            data_a.as_object().unwrap().clone(),
+            "b.json",
+            data_b.as_object().unwrap().clone(),
+        )
+        .with_config(config)
+        .run()
+        .unwrap();
+
+        let keys: Vec<String> = value_diffs.unwrap().into_iter().map(|d| d.key).collect();
+        assert_eq!(keys, vec!["/a/b", "/x.y/z"]);
+    }
+
+    fn write_baseline(path: &str, diffs: DiffCollection) {
+        let config = ConfigBuilder::new()
+            .write_to_file(Some(path.to_owned()))
+            .file_a(Some("a.json".to_owned()))
+            .file_b(Some("b.json".to_owned()))
+            .build();
+        FileHandler::new(config, None).write_to_file(diffs).unwrap();
+    }
+
+    fn get_diffs() -> DiffCollection {
+        (
+            Some(vec![
+                KeyDiff {
+                    key: "employment.employer".to_owned(),
+                    has: "a.json".to_owned(),
+                    misses: "b.json".to_owned(),
+                },
+                KeyDiff {
+                    key: "address.zip".to_owned(),
+                    has: "b.json".to_owned(),
+                    misses: "a.json".to_owned(),
+                },
+            ]),
+            Some(vec![TypeDiff {
+                key: "age".to_owned(),
+                type1: "string".to_owned(),
+                type2: "number".to_owned(),
+            }]),
+            Some(vec![
+                ValueDiff {
+                    key: "name".to_owned(),
+                    value1: "Sarah".to_owned(),
+                    value2: "John".to_owned(),
+                },
+                ValueDiff {
+                    key: "phone_numbers[0].notes.description".to_owned(),
+                    value1: "Do not call after 9pm".to_owned(),
+                    value2: "Call between 9am and 5pm".to_owned(),
+                },
+            ]),
+            Some(vec![ArrayDiff {
+                key: "email_addresses".to_owned(),
+                descriptor: ArrayDiffDesc::AHas,
+                value: "sarah.smith@example.com".to_owned(),
+            }]),
+        )
+    }
+}
@@ -0,0 +1,1920 @@
+use std::{borrow::Cow, collections::HashMap, env};
+
+use colored::Colorize;
+use indexmap::IndexMap;
+use libdtf::core::diff_types::{ArrayDiff, ArrayDiffDesc};
+use serde_json::{Map, Value};
+use serde_yaml::{Mapping, Value as YamlValue};
+
+use crate::dtfterminal_types::{Config, DtfError, EnvExpansion, KeyNormalization, MovedElement};
+
+/// The Array differences and the moved array items found while preparing the data, which the check itself can't find
+pub type PreparedDiffs = (Vec<ArrayDiff>, Vec<MovedElement>);
+
+/// The data of both files prepared with the original types of the values
+pub type TypedData = (Map<String, Value>, Map<String, Value>);
+
+/// Checks if any of the options are turned on, that require the data to be modified before checking
+pub fn is_preprocessing_needed(config: &Config) -> bool {
+    config.subtree_pointer.is_some()
+        || config.null_is_missing
+        || config.intersection_only
+        || config.numbers_as_text
+        || config.array_key.is_some()
+        || config.array_same_order
+        || config.normalize_keys.is_some()
+        || config.round.is_some()
+        || config.require_non_empty
+        || config.expand_env.is_some()
+        || config.empty_equals_null
+        || config.key_separator.is_some()
+        || config.report_moves
+}
+
+/// Stands in for the dots in the keys of the data while checking, as libdtf joins the segments of nested keys with dots.
+/// It's a character of the private use area, so it doesn't appear in real keys.
+pub(crate) const ESCAPED_DOT: &str = "\u{E000}";
+
+/// Restores the dots of the keys in a text, e.g. the key of a difference
+pub(crate) fn unescape_dots(text: &str) -> Cow<'_, str> {
+    if text.contains(ESCAPED_DOT) {
+        Cow::Owned(text.replace(ESCAPED_DOT, "."))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Modifies the data of both files according to the user configuration, before checking for differences.
+/// Returns the Array differences found while modifying the data, which are not going to be found by the check, and the moved array items.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+pub fn prepare_json_data(
+    data1: &mut Map<String, Value>,
+    data2: &mut Map<String, Value>,
+    config: &Config,
+) -> Result<PreparedDiffs, DtfError> {
+    let file_a = config.file_a.as_deref().unwrap_or("the first file");
+    let file_b = config.file_b.as_deref().unwrap_or("the second file");
+    if config.require_non_empty {
+        if data1.is_empty() {
+            return Err(DtfError::EmptyFile(file_a.to_owned()));
+        }
+        if data2.is_empty() {
+            return Err(DtfError::EmptyFile(file_b.to_owned()));
+        }
+    }
+
+    if let Some(expansion) = config.expand_env {
+        for value in data1.values_mut() {
+            expand_env_vars(value, expansion, file_a)?;
+        }
+        for value in data2.values_mut() {
+            expand_env_vars(value, expansion, file_b)?;
+        }
+    }
+
+    if let Some(pointer) = &config.subtree_pointer {
+        select_subtree(data1, pointer, file_a)?;
+        select_subtree(data2, pointer, file_b)?;
+    }
+
+    if config.null_is_missing {
+        remove_null_values(data1, data2, config.array_same_order);
+    }
+
+    if let Some(normalization) = config.normalize_keys {
+        let (mut collisions1, mut collisions2) = (vec![], vec![]);
+        normalize_keys(
+            data1,
+            data2,
+            normalization,
+            &mut collisions1,
+            &mut collisions2,
+        );
+        report_key_collisions(collisions1, file_a, config.strict)?;
+        report_key_collisions(collisions2, file_b, config.strict)?;
+    }
+
+    if config.intersection_only {
+        keep_shared_keys(data1, data2);
+    }
+
+    if let Some(digits) = config.round {
+        for (key, value1) in data1.iter_mut() {
+            if let Some(value2) = data2.get_mut(key) {
+                match_rounded_numbers(value1, value2, digits, config.array_same_order);
+            }
+        }
+    }
+
+    if config.empty_equals_null {
+        for (key, value1) in data1.iter_mut() {
+            if let Some(value2) = data2.get_mut(key) {
+                match_empty_strings(value1, value2, config.array_same_order);
+            }
+        }
+    }
+
+    if config.numbers_as_text {
+        for (key, value1) in data1.iter_mut() {
+            if let Some(value2) = data2.get_mut(key) {
+                numbers_to_text(value1, value2, config.array_same_order);
+            }
+        }
+    }
+
+    let mut moved_elements = vec![];
+    if config.report_moves {
+        find_moves(
+            data1,
+            data2,
+            config.array_key.as_deref(),
+            "",
+            &mut moved_elements,
+        );
+    }
+
+    let mut array_diffs = vec![];
+    if let Some(array_key) = &config.array_key {
+        match_arrays_by_key(data1, data2, array_key, "", &mut array_diffs);
+    }
+
+    if config.array_same_order {
+        let report_gaps = config.check_for_array_diffs;
+        align_arrays(data1, data2, "", report_gaps, &mut array_diffs);
+    }
+
+    escape_key_dots(data1);
+    escape_key_dots(data2);
+    Ok((array_diffs, moved_elements))
+}
+
+/// Modifies the data of both YAML files according to the user configuration, before checking for differences.
+/// The data is converted to JSON for the modifications, so the same rules apply to both formats.
+pub fn prepare_yaml_data(
+    data1: &mut Mapping,
+    data2: &mut Mapping,
+    config: &Config,
+) -> Result<PreparedDiffs, DtfError> {
+    if config.strict {
+        check_supported_yaml_values(data1, config.file_a.as_deref().unwrap_or("the first file"))?;
+        check_supported_yaml_values(data2, config.file_b.as_deref().unwrap_or("the second file"))?;
+    }
+
+    if !is_preprocessing_needed(config) && !has_dotted_keys(data1) && !has_dotted_keys(data2) {
+        return Ok((vec![], vec![]));
+    }
+
+    let mut json_data1 = yaml_to_json(data1)?;
+    let mut json_data2 = yaml_to_json(data2)?;
+    let prepared_diffs = prepare_json_data(&mut json_data1, &mut json_data2, config)?;
+    *data1 = json_to_yaml(json_data1)?;
+    *data2 = json_to_yaml(json_data2)?;
+    Ok(prepared_diffs)
+}
+
+/// Prepares the data of both files like `prepare_json_data`, but keeps the types of the values,
+/// which `--numbers-as-text` and `--empty-equals-null` change to make the values equal.
+/// Returns None if neither of them is used, as the checked data has the original types then.
+pub fn prepare_typed_data(
+    data1: &Map<String, Value>,
+    data2: &Map<String, Value>,
+    config: &Config,
+) -> Result<Option<TypedData>, DtfError> {
+    if !changes_value_types(config) {
+        return Ok(None);
+    }
+
+    let mut typed_config = config.clone();
+    typed_config.numbers_as_text = false;
+    typed_config.empty_equals_null = false;
+    typed_config.report_moves = false;
+    let (mut typed_data1, mut typed_data2) = (data1.clone(), data2.clone());
+    prepare_json_data(&mut typed_data1, &mut typed_data2, &typed_config)?;
+    Ok(Some((typed_data1, typed_data2)))
+}
+
+/// Prepares the data of both YAML files converted to JSON, like `prepare_typed_data`
+pub fn prepare_typed_yaml_data(
+    data1: &Mapping,
+    data2: &Mapping,
+    config: &Config,
+) -> Result<Option<TypedData>, DtfError> {
+    if !changes_value_types(config) {
+        return Ok(None);
+    }
+    prepare_typed_data(&yaml_to_json(data1)?, &yaml_to_json(data2)?, config)
+}
+
+/// Checks if any of the options are turned on, that change the types of the values before checking
+fn changes_value_types(config: &Config) -> bool {
+    config.numbers_as_text || config.empty_equals_null
+}
+
+/// Makes sure the YAML data has no values, that can't be compared as they are.
+/// Without this, tagged values, e.g. `!secret abc`, are compared as a mapping of the tag to the value,
+/// and keys, that are not strings, e.g. `1:`, are compared as the same key as the string `"1"`.
+pub fn check_supported_yaml_values(data: &Mapping, file_name: &str) -> Result<(), DtfError> {
+    match find_unsupported_yaml_value(data, "") {
+        Some(unsupported) => Err(DtfError::DiffError(format!(
+            "Unsupported value in {}: {}",
+            file_name, unsupported
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Describes the first value of the mapping or the values nested in it, that can't be compared as it is
+fn find_unsupported_yaml_value(data: &Mapping, parent_key: &str) -> Option<String> {
+    data.iter().find_map(|(key, value)| {
+        let key = match key {
+            YamlValue::String(key) if parent_key.is_empty() => key.to_owned(),
+            YamlValue::String(key) => format!("{}.{}", parent_key, key),
+            key => {
+                let key = serde_yaml::to_string(key).unwrap_or_default();
+                return Some(format!(
+                    "the key {} at {} is not a string",
+                    key.trim(),
+                    display_parent_key(parent_key)
+                ));
+            }
+        };
+        find_unsupported_nested_value(value, &key)
+    })
+}
+
+/// Describes the first value nested in the value, that can't be compared as it is
+fn find_unsupported_nested_value(value: &YamlValue, key: &str) -> Option<String> {
+    match value {
+        YamlValue::Tagged(tagged) => Some(format!("the value at {} is tagged {}", key, tagged.tag)),
+        YamlValue::Mapping(mapping) => find_unsupported_yaml_value(mapping, key),
+        YamlValue::Sequence(values) => values.iter().enumerate().find_map(|(index, nested)| {
+            find_unsupported_nested_value(nested, &format!("{}[{}]", key, index))
+        }),
+        _ => None,
+    }
+}
+
+/// Names the key of a mapping in the messages, the top level having no key
+fn display_parent_key(key: &str) -> &str {
+    if key.is_empty() {
+        "the top level"
+    } else {
+        key
+    }
+}
+
+/// Replaces the data with the value the JSON Pointer points to, so only that part gets checked.
+/// Arrays are turned into objects with the indexes as keys, e.g. `[0]`, so their elements are checked in order.
+pub fn select_subtree(
+    data: &mut Map<String, Value>,
+    pointer: &str,
+    file_name: &str,
+) -> Result<(), DtfError> {
+    let mut value = Value::Object(std::mem::take(data));
+    match value.pointer_mut(pointer).map(Value::take) {
+        Some(Value::Object(map)) => *data = map,
+        Some(Value::Array(values)) => {
+            *data = values
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| (format!("[{}]", index), value))
+                .collect()
+        }
+        Some(_) => {
+            return Err(DtfError::DiffError(format!(
+                "{} points to a value, that is neither an object nor an array in {}",
+                pointer, file_name
+            )))
+        }
+        None => {
+            return Err(DtfError::DiffError(format!(
+                "{} is missing from {}",
+                pointer, file_name
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the `${VAR}` placeholders in every string of the value with the environment variables they name
+fn expand_env_vars(
+    value: &mut Value,
+    expansion: EnvExpansion,
+    file_name: &str,
+) -> Result<(), DtfError> {
+    match value {
+        Value::String(text) => *text = expand_env_placeholders(text, expansion, file_name)?,
+        Value::Array(values) => {
+            for value in values {
+                expand_env_vars(value, expansion, file_name)?;
+            }
+        }
+        Value::Object(map) => {
+            for value in map.values_mut() {
+                expand_env_vars(value, expansion, file_name)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Replaces the `${VAR}` placeholders in the text. Placeholders of variables, that are not set, are kept or fail the check, depending on the user configuration.
+/// A `${` without a closing `}` is not a placeholder.
+fn expand_env_placeholders(
+    text: &str,
+    expansion: EnvExpansion,
+    file_name: &str,
+) -> Result<String, DtfError> {
+    let mut expanded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        let placeholder = &rest[start..start + length + 1];
+        let name = &placeholder[2..placeholder.len() - 1];
+        expanded.push_str(&rest[..start]);
+        match (env::var(name), expansion) {
+            (Ok(value), _) => expanded.push_str(&value),
+            (Err(_), EnvExpansion::Keep) => expanded.push_str(placeholder),
+            (Err(_), EnvExpansion::Error) => {
+                return Err(DtfError::DiffError(format!(
+                    "{} uses the environment variable {}, which is not set",
+                    file_name, name
+                )))
+            }
+        }
+        rest = &rest[start + length + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Removes the keys holding a null value, where the other object holds another value at the same key,
+/// so they are reported as a Key difference, the same way as a missing key, instead of a Type difference.
+/// Null values under keys missing from the other object are kept, so they are still reported as a Key difference once.
+fn remove_null_values(
+    data1: &mut Map<String, Value>,
+    data2: &mut Map<String, Value>,
+    same_order: bool,
+) {
+    data1.retain(|key, value| !value.is_null() || data2.get(key).is_none_or(Value::is_null));
+    data2.retain(|key, value| !value.is_null() || data1.get(key).is_none_or(Value::is_null));
+
+    for (key, value1) in data1.iter_mut() {
+        if let Some(value2) = data2.get_mut(key) {
+            remove_null_values_in(value1, value2, same_order);
+        }
+    }
+}
+
+/// Looks for objects to remove null values from in nested values.
+/// Arrays are matched by index only if `same_order` is set, otherwise their elements can't be matched, so they are left as they are.
+fn remove_null_values_in(value1: &mut Value, value2: &mut Value, same_order: bool) {
+    match (value1, value2) {
+        (Value::Object(map1), Value::Object(map2)) => remove_null_values(map1, map2, same_order),
+        (Value::Array(values1), Value::Array(values2)) if same_order => values1
+            .iter_mut()
+            .zip(values2.iter_mut())
+            .for_each(|(nested1, nested2)| remove_null_values_in(nested1, nested2, same_order)),
+        _ => {}
+    }
+}
+
+/// Removes the keys, that are only present in one of the objects.
+/// Nested objects under shared keys get the same treatment, arrays are left as they are.
+fn keep_shared_keys(data1: &mut Map<String, Value>, data2: &mut Map<String, Value>) {
+    data1.retain(|key, _| data2.contains_key(key));
+    data2.retain(|key, _| data1.contains_key(key));
+
+    for (key, value1) in data1.iter_mut() {
+        if let (Value::Object(map1), Some(Value::Object(map2))) = (value1, data2.get_mut(key)) {
+            keep_shared_keys(map1, map2);
+        }
+    }
+}
+
+/// Replaces the dots in the keys of the object and the objects nested in it, so they are not taken for separators of nested keys
+fn escape_key_dots(data: &mut Map<String, Value>) {
+    replace_in_keys(data, ".", ESCAPED_DOT);
+}
+
+/// Restores the dots in the keys of the object and the objects nested in it, after they were escaped for the check
+pub(crate) fn unescape_key_dots(data: &mut Map<String, Value>) {
+    replace_in_keys(data, ESCAPED_DOT, ".");
+}
+
+/// Replaces the text in the keys of the object and the objects nested in it.
+/// Objects without the text in their keys are kept as they are.
+fn replace_in_keys(data: &mut Map<String, Value>, from: &str, to: &str) {
+    for value in data.values_mut() {
+        replace_in_nested_keys(value, from, to);
+    }
+    if data.keys().any(|key| key.contains(from)) {
+        *data = std::mem::take(data)
+            .into_iter()
+            .map(|(key, value)| (key.replace(from, to), value))
+            .collect();
+    }
+}
+
+/// Replaces the text in the keys of the objects nested in the value
+fn replace_in_nested_keys(value: &mut Value, from: &str, to: &str) {
+    match value {
+        Value::Object(map) => replace_in_keys(map, from, to),
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| replace_in_nested_keys(value, from, to)),
+        _ => {}
+    }
+}
+
+/// Checks if any key of the YAML mapping or the mappings nested in it contains a dot, which has to be escaped for the check
+fn has_dotted_keys(data: &Mapping) -> bool {
+    data.iter().any(|(key, value)| {
+        key.as_str().is_some_and(|key| key.contains('.')) || has_nested_dotted_keys(value)
+    })
+}
+
+/// Checks if any key of the YAML mappings nested in the value contains a dot
+fn has_nested_dotted_keys(value: &YamlValue) -> bool {
+    match value {
+        YamlValue::Mapping(mapping) => has_dotted_keys(mapping),
+        YamlValue::Sequence(values) => values.iter().any(has_nested_dotted_keys),
+        YamlValue::Tagged(tagged) => has_nested_dotted_keys(&tagged.value),
+        _ => false,
+    }
+}
+
+/// Renames the keys of the second object to the keys of the first one, that are the same after normalization, so they are checked against each other.
+/// Keys sharing their normalized form with another key of the same object are left as they are, as it can't be told which one to match.
+/// They are collected in the collisions of their object instead. Objects are matched by key, arrays by index.
+fn normalize_keys(
+    data1: &mut Map<String, Value>,
+    data2: &mut Map<String, Value>,
+    normalization: KeyNormalization,
+    collisions1: &mut Vec<Vec<String>>,
+    collisions2: &mut Vec<Vec<String>>,
+) {
+    let keys1 = unique_normalized_keys(data1, normalization, collisions1);
+    let renames = unique_normalized_keys(data2, normalization, collisions2)
+        .into_iter()
+        .filter_map(|(normalized, key2)| {
+            keys1
+                .get(&normalized)
+                .filter(|key1| **key1 != key2)
+                .map(|key1| (key2, key1.clone()))
+        })
+        .collect::<HashMap<String, String>>();
+    if !renames.is_empty() {
+        *data2 = std::mem::take(data2)
+            .into_iter()
+            .map(|(key, value)| (renames.get(&key).cloned().unwrap_or(key), value))
+            .collect();
+    }
+
+    for (key, value1) in data1.iter_mut() {
+        if let Some(value2) = data2.get_mut(key) {
+            normalize_nested_keys(value1, value2, normalization, collisions1, collisions2);
+        }
+    }
+}
+
+/// Normalizes the keys of the objects nested in both values at the same place
+fn normalize_nested_keys(
+    value1: &mut Value,
+    value2: &mut Value,
+    normalization: KeyNormalization,
+    collisions1: &mut Vec<Vec<String>>,
+    collisions2: &mut Vec<Vec<String>>,
+) {
+    match (value1, value2) {
+        (Value::Object(map1), Value::Object(map2)) => {
+            normalize_keys(map1, map2, normalization, collisions1, collisions2)
+        }
+        (Value::Array(values1), Value::Array(values2)) => {
+            for (nested1, nested2) in values1.iter_mut().zip(values2.iter_mut()) {
+                normalize_nested_keys(nested1, nested2, normalization, collisions1, collisions2);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects the keys of an object by their normalized form.
+/// The keys sharing it with another key are left out, and added to the collisions as a group instead.
+fn unique_normalized_keys(
+    data: &Map<String, Value>,
+    normalization: KeyNormalization,
+    collisions: &mut Vec<Vec<String>>,
+) -> HashMap<String, String> {
+    let mut keys: HashMap<String, Vec<String>> = HashMap::new();
+    for key in data.keys() {
+        keys.entry(normalization.normalize(key))
+            .or_default()
+            .push(key.clone());
+    }
+
+    let mut unique_keys = HashMap::new();
+    for (normalized, mut found) in keys {
+        if found.len() == 1 {
+            unique_keys.insert(normalized, found.remove(0));
+        } else {
+            collisions.push(found);
+        }
+    }
+    unique_keys
+}
+
+/// Warns about the keys of a file, that are the same after normalization, as they are checked without being normalized.
+/// In strict mode the check fails instead.
+fn report_key_collisions(
+    mut collisions: Vec<Vec<String>>,
+    file_name: &str,
+    strict: bool,
+) -> Result<(), DtfError> {
+    collisions.sort();
+    for keys in collisions {
+        let message = format!(
+            "{} has keys, that are the same after normalization: {}",
+            file_name,
+            keys.join(", ")
+        );
+        if strict {
+            return Err(DtfError::DiffError(message));
+        }
+        eprintln!(
+            "{} {}. They are checked without normalization",
+            "Warning:".yellow(),
+            message
+        );
+    }
+    Ok(())
+}
+
+/// Makes numbers present in both values at the same place equal, if they are the same after rounding to `digits` decimal places.
+/// Objects are matched by key. Arrays are matched by index only if `same_order` is set, otherwise their elements
+/// are compared regardless of position, so every number in them is rounded on its own and the Array differences show the rounded numbers.
+/// Numbers matched by place, that still differ after rounding, are left as they are.
+fn match_rounded_numbers(value1: &mut Value, value2: &mut Value, digits: u32, same_order: bool) {
+    match (value1, value2) {
+        (Value::Number(number1), Value::Number(number2)) => {
+            let same_rounded = match (rounded(number1, digits), rounded(number2, digits)) {
+                (Some(float1), Some(float2)) => float1 == float2,
+                _ => false,
+            };
+            if same_rounded {
+                *number2 = number1.clone();
+            }
+        }
+        (Value::Object(map1), Value::Object(map2)) => {
+            for (key, nested1) in map1.iter_mut() {
+                if let Some(nested2) = map2.get_mut(key) {
+                    match_rounded_numbers(nested1, nested2, digits, same_order);
+                }
+            }
+        }
+        (Value::Array(values1), Value::Array(values2)) if same_order => values1
+            .iter_mut()
+            .zip(values2.iter_mut())
+            .for_each(|(nested1, nested2)| {
+                match_rounded_numbers(nested1, nested2, digits, same_order)
+            }),
+        (Value::Array(values1), Value::Array(values2)) => values1
+            .iter_mut()
+            .chain(values2.iter_mut())
+            .for_each(|nested| round_all_numbers(nested, digits)),
+        _ => {}
+    }
+}
+
+/// Rounds every number in the value, that is not an integer, to `digits` decimal places
+fn round_all_numbers(value: &mut Value, digits: u32) {
+    match value {
+        Value::Number(number) if !is_integer(number) => {
+            let rounded = number
+                .as_f64()
+                .and_then(|float| serde_json::Number::from_f64(round(float, digits)));
+            if let Some(rounded) = rounded {
+                *number = rounded;
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|nested| round_all_numbers(nested, digits)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|nested| round_all_numbers(nested, digits)),
+        _ => {}
+    }
+}
+
+/// Returns the number rounded to `digits` decimal places.
+/// Integers are not rounded, and are only returned if f64 represents them exactly, so larger ones are compared exactly instead.
+fn rounded(number: &serde_json::Number, digits: u32) -> Option<f64> {
+    if let Some(integer) = number.as_i64() {
+        return (integer.unsigned_abs() <= MAX_EXACT_INTEGER).then_some(integer as f64);
+    }
+    if let Some(integer) = number.as_u64() {
+        return (integer <= MAX_EXACT_INTEGER).then_some(integer as f64);
+    }
+    number.as_f64().map(|float| round(float, digits))
+}
+
+/// Returns whether the number was written as an integer
+fn is_integer(number: &serde_json::Number) -> bool {
+    number.is_i64() || number.is_u64()
+}
+
+/// Replaces null with the empty string, where the other value at the same place is the empty string, so they are equal.
+/// Objects are matched by key. Arrays are matched by index only if `same_order` is set, otherwise their elements
+/// are compared regardless of position, so every null in them is replaced.
+fn match_empty_strings(value1: &mut Value, value2: &mut Value, same_order: bool) {
+    match (value1, value2) {
+        (null @ Value::Null, Value::String(text)) | (Value::String(text), null @ Value::Null)
+            if text.is_empty() =>
+        {
+            *null = Value::String(String::new());
+        }
+        (Value::Object(map1), Value::Object(map2)) => {
+            for (key, nested1) in map1.iter_mut() {
+                if let Some(nested2) = map2.get_mut(key) {
+                    match_empty_strings(nested1, nested2, same_order);
+                }
+            }
+        }
+        (Value::Array(values1), Value::Array(values2)) if same_order => values1
+            .iter_mut()
+            .zip(values2.iter_mut())
+            .for_each(|(nested1, nested2)| match_empty_strings(nested1, nested2, same_order)),
+        (Value::Array(values1), Value::Array(values2)) => values1
+            .iter_mut()
+            .chain(values2.iter_mut())
+            .for_each(all_nulls_to_empty_strings),
+        _ => {}
+    }
+}
+
+/// Replaces every null in the value with the empty string
+fn all_nulls_to_empty_strings(value: &mut Value) {
+    match value {
+        Value::Null => *value = Value::String(String::new()),
+        Value::Array(values) => values.iter_mut().for_each(all_nulls_to_empty_strings),
+        Value::Object(map) => map.values_mut().for_each(all_nulls_to_empty_strings),
+        _ => {}
+    }
+}
+
+/// The largest integer, that f64 represents exactly, along with every integer below it
+const MAX_EXACT_INTEGER: u64 = 1 << 53;
+
+/// Rounds a number to `digits` decimal places
+fn round(number: f64, digits: u32) -> f64 {
+    let factor = 10_f64.powi(digits as i32);
+    (number * factor).round() / factor
+}
+
+/// Replaces numbers present in both values at the same place with their text.
+/// Objects are matched by key. Arrays are matched by index only if `same_order` is set, otherwise their elements
+/// are compared regardless of position, so every number in them is replaced.
+/// Numbers compared against other types are left as they are, so they still produce Type differences.
+fn numbers_to_text(value1: &mut Value, value2: &mut Value, same_order: bool) {
+    if let (Value::Number(number1), Value::Number(number2)) = (&*value1, &*value2) {
+        let (text1, text2) = (number1.to_string(), number2.to_string());
+        *value1 = Value::String(text1);
+        *value2 = Value::String(text2);
+        return;
+    }
+
+    match (value1, value2) {
+        (Value::Object(map1), Value::Object(map2)) => {
+            for (key, nested1) in map1.iter_mut() {
+                if let Some(nested2) = map2.get_mut(key) {
+                    numbers_to_text(nested1, nested2, same_order);
+                }
+            }
+        }
+        (Value::Array(values1), Value::Array(values2)) if same_order => values1
+            .iter_mut()
+            .zip(values2.iter_mut())
+            .for_each(|(nested1, nested2)| numbers_to_text(nested1, nested2, same_order)),
+        (Value::Array(values1), Value::Array(values2)) => values1
+            .iter_mut()
+            .chain(values2.iter_mut())
+            .for_each(all_numbers_to_text),
+        _ => {}
+    }
+}
+
+/// Replaces every number in the value with its text
+fn all_numbers_to_text(value: &mut Value) {
+    match value {
+        Value::Number(number) => *value = Value::String(number.to_string()),
+        Value::Array(values) => values.iter_mut().for_each(all_numbers_to_text),
+        Value::Object(map) => map.values_mut().for_each(all_numbers_to_text),
+        _ => {}
+    }
+}
+
+/// Looks for arrays under the same key in both objects to match by the value of the `array_key` field
+fn match_arrays_by_key(
+    data1: &mut Map<String, Value>,
+    data2: &mut Map<String, Value>,
+    array_key: &str,
+    path: &str,
+    array_diffs: &mut Vec<ArrayDiff>,
+) {
+    for (key, value1) in data1.iter_mut() {
+        if let Some(value2) = data2.get_mut(key) {
+            let key_path = if path.is_empty() {
+                key.to_owned()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            match_values_by_key(value1, value2, array_key, &key_path, array_diffs);
+        }
+    }
+}
+
+/// Replaces arrays of objects with objects, that hold the elements under `[array_key=value]` keys.
+/// Only elements present in both arrays are kept, so they get checked field by field.
+/// The others are reported as Array differences.
+fn match_values_by_key(
+    value1: &mut Value,
+    value2: &mut Value,
+    array_key: &str,
+    path: &str,
+    array_diffs: &mut Vec<ArrayDiff>,
+) {
+    if let (Value::Object(map1), Value::Object(map2)) = (&mut *value1, &mut *value2) {
+        return match_arrays_by_key(map1, map2, array_key, path, array_diffs);
+    }
+
+    let (Value::Array(values1), Value::Array(values2)) = (&*value1, &*value2) else {
+        return;
+    };
+    let (Some(elements1), Some(mut elements2)) = (
+        key_elements(values1, array_key),
+        key_elements(values2, array_key),
+    ) else {
+        return;
+    };
+
+    let mut matched1 = Map::new();
+    let mut matched2 = Map::new();
+    for (id, mut element1) in elements1 {
+        let Some(mut element2) = elements2.shift_remove(&id) else {
+            array_diffs.push(ArrayDiff {
+                key: path.to_owned(),
+                descriptor: ArrayDiffDesc::AHas,
+                value: element1.to_string(),
+            });
+            continue;
+        };
+
+        let element_key = format!("[{}={}]", array_key, id);
+        let element_path = format!("{}{}", path, element_key);
+        match_values_by_key(
+            &mut element1,
+            &mut element2,
+            array_key,
+            &element_path,
+            array_diffs,
+        );
+        matched1.insert(element_key.clone(), element1);
+        matched2.insert(element_key, element2);
+    }
+    for element2 in elements2.into_values() {
+        array_diffs.push(ArrayDiff {
+            key: path.to_owned(),
+            descriptor: ArrayDiffDesc::BHas,
+            value: element2.to_string(),
+        });
+    }
+
+    *value1 = Value::Object(matched1);
+    *value2 = Value::Object(matched2);
+}
+
+/// Looks for arrays under the same key in both objects, whose items were moved
+fn find_moves(
+    data1: &Map<String, Value>,
+    data2: &Map<String, Value>,
+    array_key: Option<&str>,
+    path: &str,
+    moved_elements: &mut Vec<MovedElement>,
+) {
+    for (key, value1) in data1 {
+        if let Some(value2) = data2.get(key) {
+            let key_path = if path.is_empty() {
+                key.to_owned()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            find_value_moves(value1, value2, array_key, &key_path, moved_elements);
+        }
+    }
+}
+
+/// Reports the items present in both arrays, that don't keep their order relative to the other items present in both.
+/// This way an item inserted at the start of an array doesn't make every item after it moved, only the items taken out of the order are.
+/// Arrays of scalars are matched by value and arrays of objects by their `array_key` field. The items of other arrays can't be matched, so they are skipped.
+fn find_value_moves(
+    value1: &Value,
+    value2: &Value,
+    array_key: Option<&str>,
+    path: &str,
+    moved_elements: &mut Vec<MovedElement>,
+) {
+    if let (Value::Object(map1), Value::Object(map2)) = (value1, value2) {
+        return find_moves(map1, map2, array_key, path, moved_elements);
+    }
+
+    let (Value::Array(values1), Value::Array(values2)) = (value1, value2) else {
+        return;
+    };
+    let Some(pairs) = pair_elements(values1, values2, array_key) else {
+        return;
+    };
+
+    let kept_order = find_kept_order(&pairs);
+    for (&(index1, index2), kept) in pairs.iter().zip(kept_order) {
+        if !kept {
+            moved_elements.push(MovedElement {
+                key: path.to_owned(),
+                value: values1[index1].to_string(),
+                from: index1,
+                to: index2,
+            });
+        }
+    }
+
+    if let Some(array_key) = array_key {
+        for (index1, index2) in pairs {
+            let Some(id) = element_id(&values1[index1], array_key) else {
+                continue;
+            };
+            let element_path = format!("{}[{}={}]", path, array_key, id);
+            find_value_moves(
+                &values1[index1],
+                &values2[index2],
+                Some(array_key),
+                &element_path,
+                moved_elements,
+            );
+        }
+    }
+}
+
+/// Pairs the items present in both arrays by their indexes in the first and in the second array, in the order of the first array.
+/// Returns None if the items can't be matched.
+fn pair_elements(
+    values1: &[Value],
+    values2: &[Value],
+    array_key: Option<&str>,
+) -> Option<Vec<(usize, usize)>> {
+    if values1.iter().chain(values2).all(is_scalar) {
+        let mut paired2 = vec![false; values2.len()];
+        let pairs = values1
+            .iter()
+            .enumerate()
+            .filter_map(|(index1, value1)| {
+                let index2 = (0..values2.len()).find(|&i| !paired2[i] && values2[i] == *value1)?;
+                paired2[index2] = true;
+                Some((index1, index2))
+            })
+            .collect();
+        return Some(pairs);
+    }
+
+    let array_key = array_key?;
+    let elements1 = key_elements(values1, array_key)?;
+    let elements2 = key_elements(values2, array_key)?;
+    let pairs = elements1
+        .keys()
+        .enumerate()
+        .filter_map(|(index1, id)| Some((index1, elements2.get_index_of(id)?)))
+        .collect();
+    Some(pairs)
+}
+
+/// Finds the longest run of pairs, whose indexes in the second array increase, meaning the items kept their order.
+/// Returns whether each pair is part of the run.
+fn find_kept_order(pairs: &[(usize, usize)]) -> Vec<bool> {
+    // The last pair of the best run found so far for every run length
+    let mut run_ends: Vec<usize> = vec![];
+    let mut previous = vec![None; pairs.len()];
+    for (i, &(_, index2)) in pairs.iter().enumerate() {
+        let length = run_ends.partition_point(|&end| pairs[end].1 < index2);
+        if length > 0 {
+            previous[i] = Some(run_ends[length - 1]);
+        }
+        if length == run_ends.len() {
+            run_ends.push(i);
+        } else {
+            run_ends[length] = i;
+        }
+    }
+
+    let mut kept = vec![false; pairs.len()];
+    let mut current = run_ends.last().copied();
+    while let Some(i) = current {
+        kept[i] = true;
+        current = previous[i];
+    }
+    kept
+}
+
+/// Checks if the value is not an object or an array
+fn is_scalar(value: &Value) -> bool {
+    !value.is_object() && !value.is_array()
+}
+
+/// Collects the elements of an array by the value of their `array_key` field.
+/// Returns None if any of the elements is not an object with the field, or if a value is present multiple times.
+fn key_elements(values: &[Value], array_key: &str) -> Option<IndexMap<String, Value>> {
+    let mut elements = IndexMap::new();
+    for value in values {
+        let id = element_id(value, array_key)?;
+        if elements.insert(id, value.clone()).is_some() {
+            return None;
+        }
+    }
+    Some(elements)
+}
+
+/// The value of the `array_key` field of an element, if it's an object with the field holding a scalar
+fn element_id(value: &Value, array_key: &str) -> Option<String> {
+    match value.get(array_key)? {
+        Value::String(id) => Some(id.clone()),
+        Value::Object(_) | Value::Array(_) => None,
+        id => Some(id.to_string()),
+    }
+}
+
+/// A step of turning the elements of an array in the first file into the elements of the array in the second file
+#[derive(Debug, PartialEq)]
+enum ArrayEdit {
+    /// The elements at the indexes are checked against each other, as they are either equal or one replaced the other
+    Paired(usize, usize),
+    /// The element at the index is only present in the first array
+    Removed(usize),
+    /// The element at the index is only present in the second array
+    Inserted(usize),
+}
+
+/// The largest table of the longest common subsequence built while aligning arrays, about 32 MB.
+/// Larger arrays are compared element by element at the same index, instead of running out of memory.
+const MAX_ALIGNMENT_CELLS: usize = 1 << 22;
+
+/// Formats an array element the way libdtf does in its Array differences: strings without quotes, anything else as JSON
+fn display_element(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Looks for arrays under the same key in both objects to align element by element
+fn align_arrays(
+    data1: &mut Map<String, Value>,
+    data2: &mut Map<String, Value>,
+    path: &str,
+    report_gaps: bool,
+    array_diffs: &mut Vec<ArrayDiff>,
+) {
+    for (key, value1) in data1.iter_mut() {
+        if let Some(value2) = data2.get_mut(key) {
+            let key_path = if path.is_empty() {
+                key.to_owned()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            align_values(value1, value2, &key_path, report_gaps, array_diffs);
+        }
+    }
+}
+
+/// Replaces arrays with objects, that hold the elements paired by the alignment under `[index]` keys, the index being the one in the first array.
+/// This way an element inserted into an array is reported once, instead of every element after it being reported as changed.
+/// Elements present in only one of the arrays are reported as Array differences with their index in their own array.
+/// If these are not reported, because Array differences are not checked, arrays with such elements are left as they are,
+/// so their elements are checked by index and the changes still show up as Value differences.
+fn align_values(
+    value1: &mut Value,
+    value2: &mut Value,
+    path: &str,
+    report_gaps: bool,
+    array_diffs: &mut Vec<ArrayDiff>,
+) {
+    if let (Value::Object(map1), Value::Object(map2)) = (&mut *value1, &mut *value2) {
+        return align_arrays(map1, map2, path, report_gaps, array_diffs);
+    }
+
+    let (Value::Array(values1), Value::Array(values2)) = (&*value1, &*value2) else {
+        return;
+    };
+
+    let edits = align_elements(values1, values2);
+    let has_gaps = edits
+        .iter()
+        .any(|edit| !matches!(edit, ArrayEdit::Paired(..)));
+    if has_gaps && !report_gaps {
+        return;
+    }
+
+    let mut aligned1 = Map::new();
+    let mut aligned2 = Map::new();
+    for edit in edits {
+        match edit {
+            ArrayEdit::Paired(index1, index2) => {
+                let element_key = format!("[{}]", index1);
+                let mut element1 = values1[index1].clone();
+                let mut element2 = values2[index2].clone();
+                align_values(
+                    &mut element1,
+                    &mut element2,
+                    &format!("{}{}", path, element_key),
+                    report_gaps,
+                    array_diffs,
+                );
+                aligned1.insert(element_key.clone(), element1);
+                aligned2.insert(element_key, element2);
+            }
+            ArrayEdit::Removed(index1) => array_diffs.push(ArrayDiff {
+                key: format!("{}[{}]", path, index1),
+                descriptor: ArrayDiffDesc::AHas,
+                value: display_element(&values1[index1]),
+            }),
+            ArrayEdit::Inserted(index2) => array_diffs.push(ArrayDiff {
+                key: format!("{}[{}]", path, index2),
+                descriptor: ArrayDiffDesc::BHas,
+                value: display_element(&values2[index2]),
+            }),
+        }
+    }
+
+    *value1 = Value::Object(aligned1);
+    *value2 = Value::Object(aligned2);
+}
+
+/// Aligns the elements of two arrays along their longest common subsequence.
+/// Between two equal elements, the elements only in the first array are paired with the ones only in the second array as long as possible,
+/// as they most likely replaced each other. The rest are removed or inserted elements.
+/// The common prefix and suffix are skipped, so arrays with a few changes are aligned fast even if they are large.
+/// If the rest is still too large to align, the elements are paired by their index.
+fn align_elements(values1: &[Value], values2: &[Value]) -> Vec<ArrayEdit> {
+    let prefix = values1
+        .iter()
+        .zip(values2)
+        .take_while(|(value1, value2)| value1 == value2)
+        .count();
+    let suffix = values1[prefix..]
+        .iter()
+        .rev()
+        .zip(values2[prefix..].iter().rev())
+        .take_while(|(value1, value2)| value1 == value2)
+        .count();
+    let middle1 = &values1[prefix..values1.len() - suffix];
+    let middle2 = &values2[prefix..values2.len() - suffix];
+    let cells = (middle1.len() + 1).checked_mul(middle2.len() + 1);
+    if cells.is_none_or(|cells| cells > MAX_ALIGNMENT_CELLS) {
+        return pair_by_index(values1.len(), values2.len());
+    }
+
+    // The length of the longest common subsequence of the elements from the indexes to the end
+    let width = middle2.len() + 1;
+    let mut lcs = vec![0usize; (middle1.len() + 1) * width];
+    for i in (0..middle1.len()).rev() {
+        for j in (0..middle2.len()).rev() {
+            lcs[i * width + j] = if middle1[i] == middle2[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut edits = (0..prefix)
+        .map(|index| ArrayEdit::Paired(index, index))
+        .collect::<Vec<ArrayEdit>>();
+    let (mut removed, mut inserted) = (vec![], vec![]);
+    let (mut i, mut j) = (0, 0);
+    while i < middle1.len() && j < middle2.len() {
+        if middle1[i] == middle2[j] {
+            flush_gap(&mut edits, &mut removed, &mut inserted);
+            edits.push(ArrayEdit::Paired(prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            removed.push(prefix + i);
+            i += 1;
+        } else {
+            inserted.push(prefix + j);
+            j += 1;
+        }
+    }
+    removed.extend((i..middle1.len()).map(|index| prefix + index));
+    inserted.extend((j..middle2.len()).map(|index| prefix + index));
+    flush_gap(&mut edits, &mut removed, &mut inserted);
+
+    edits.extend((0..suffix).map(|offset| {
+        ArrayEdit::Paired(
+            values1.len() - suffix + offset,
+            values2.len() - suffix + offset,
+        )
+    }));
+    edits
+}
+
+/// Pairs the elements of the arrays at the same index, the rest of the longer array being removed or inserted elements
+fn pair_by_index(len1: usize, len2: usize) -> Vec<ArrayEdit> {
+    let paired = len1.min(len2);
+    (0..paired)
+        .map(|index| ArrayEdit::Paired(index, index))
+        .chain((paired..len1).map(ArrayEdit::Removed))
+        .chain((paired..len2).map(ArrayEdit::Inserted))
+        .collect()
+}
+
+/// Adds the edits for the elements between two equal ones: pairs as long as both arrays have elements, then the removed or inserted rest
+fn flush_gap(edits: &mut Vec<ArrayEdit>, removed: &mut Vec<usize>, inserted: &mut Vec<usize>) {
+    let paired = removed.len().min(inserted.len());
+    edits.extend(
+        removed
+            .iter()
+            .zip(inserted.iter())
+            .map(|(index1, index2)| ArrayEdit::Paired(*index1, *index2)),
+    );
+    edits.extend(
+        removed[paired..]
+            .iter()
+            .map(|index1| ArrayEdit::Removed(*index1)),
+    );
+    edits.extend(
+        inserted[paired..]
+            .iter()
+            .map(|index2| ArrayEdit::Inserted(*index2)),
+    );
+    removed.clear();
+    inserted.clear();
+}
+
+/// Converts YAML data to JSON data
+pub fn yaml_to_json(data: &Mapping) -> Result<Map<String, Value>, DtfError> {
+    match serde_json::to_value(data) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(DtfError::DiffError(
+            "YAML data could not be converted to an object".to_string(),
+        )),
+        Err(e) => Err(DtfError::DiffError(format!(
+            "YAML data could not be converted: {}",
+            e
+        ))),
+    }
+}
+
+/// Converts JSON data back to YAML data
+fn json_to_yaml(data: Map<String, Value>) -> Result<Mapping, DtfError> {
+    match serde_yaml::to_value(data) {
+        Ok(serde_yaml::Value::Mapping(mapping)) => Ok(mapping),
+        Ok(_) => Err(DtfError::DiffError(
+            "JSON data could not be converted to a mapping".to_string(),
+        )),
+        Err(e) => Err(DtfError::DiffError(format!(
+            "JSON data could not be converted: {}",
+            e
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        dtfterminal_types::{ConfigBuilder, WorkingContext},
+        json_app::JsonApp,
+        postprocessor::process_diffs,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_remove_null_values() {
+        let mut data1 = as_map(json!({
+            "a": null,
+            "b": { "c": null, "d": 1 },
+            "e": [{ "f": null }, null],
+            "g": null
+        }));
+        let mut data2 = as_map(json!({
+            "a": 1,
+            "b": { "c": "x", "d": null },
+            "e": [{ "f": 2 }, 3],
+            "g": null
+        }));
+
+        remove_null_values(&mut data1, &mut data2, true);
+
+        assert_eq!(
+            Value::Object(data1),
+            json!({ "b": { "d": 1 }, "e": [{}, null], "g": null })
+        );
+        assert_eq!(
+            Value::Object(data2),
+            json!({ "a": 1, "b": { "c": "x" }, "e": [{ "f": 2 }, 3], "g": null })
+        );
+    }
+
+    #[test]
+    fn test_null_is_missing_reports_key_diffs_for_null_keys() {
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_type_diffs(true)
+            .check_for_value_diffs(true)
+            .null_is_missing(true)
+            .build();
+        let json_app = JsonApp::from_data(
+            as_map(json!({ "a": null, "b": 1, "c": null })),
+            as_map(json!({ "b": 2, "c": "x" })),
+            get_working_context(config),
+        );
+
+        let (key_diffs, type_diffs, value_diffs, _) = json_app.perform_new_check();
+
+        let key_diffs = key_diffs.unwrap();
+        let has = |key: &str| {
+            key_diffs
+                .iter()
+                .find(|key_diff| key_diff.key == key)
+                .map(|key_diff| key_diff.has.as_str())
+        };
+        assert_eq!(key_diffs.len(), 2);
+        assert_eq!(has("a"), Some("FileA.json"));
+        assert_eq!(has("c"), Some("FileB.json"));
+        assert!(type_diffs.unwrap().is_empty());
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "b");
+    }
+
+    #[test]
+    fn test_keep_shared_keys() {
+        let mut data1 = as_map(json!({ "a": 1, "b": { "c": 1, "d": 2 }, "e": [{ "f": 1 }] }));
+        let mut data2 = as_map(json!({ "b": { "c": 2 }, "e": [{}], "g": 3 }));
+
+        keep_shared_keys(&mut data1, &mut data2);
+
+        assert_eq!(
+            Value::Object(data1),
+            json!({ "b": { "c": 1 }, "e": [{ "f": 1 }] })
+        );
+        assert_eq!(Value::Object(data2), json!({ "b": { "c": 2 }, "e": [{}] }));
+    }
+
+    #[test]
+    fn test_intersection_only_reports_shared_keys_only() {
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_type_diffs(true)
+            .check_for_value_diffs(true)
+            .intersection_only(true)
+            .build();
+        let json_app = JsonApp::from_data(
+            as_map(json!({ "a": 1, "b": 2 })),
+            as_map(json!({ "a": 9 })),
+            get_working_context(config),
+        );
+
+        let (key_diffs, type_diffs, value_diffs, _) = json_app.perform_new_check();
+
+        assert!(key_diffs.unwrap().is_empty());
+        assert!(type_diffs.unwrap().is_empty());
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "a");
+    }
+
+    #[test]
+    fn test_normalize_keys() {
+        let mut data1 = as_map(json!({ "userName": 1, "nested": [{ " ID ": 2 }], "a": 3, "A": 4 }));
+        let mut data2 = as_map(json!({ " username ": 1, "nested": [{ "id": 2 }], "a ": 3 }));
+
+        let (mut collisions1, mut collisions2) = (vec![], vec![]);
+
+        normalize_keys(
+            &mut data1,
+            &mut data2,
+            KeyNormalization::Both,
+            &mut collisions1,
+            &mut collisions2,
+        );
+
+        assert_eq!(
+            Value::Object(data2),
+            json!({ "userName": 1, "nested": [{ " ID ": 2 }], "a ": 3 }),
+            "keys colliding after normalization should be left as they are"
+        );
+        assert_eq!(collisions1, vec![vec!["a".to_owned(), "A".to_owned()]]);
+        assert!(collisions2.is_empty());
+    }
+
+    #[test]
+    fn test_normalize_keys_collision() {
+        let data1 = as_map(json!({ "user": { "A": 1, "a": 2 } }));
+        let data2 = as_map(json!({ "user": { "a": 1 } }));
+        let config = ConfigBuilder::new()
+            .file_a(Some("a.json".to_owned()))
+            .normalize_keys(Some(KeyNormalization::Lower))
+            .build();
+
+        prepare_json_data(&mut data1.clone(), &mut data2.clone(), &config).unwrap();
+        let strict_config = Config {
+            strict: true,
+            ..config
+        };
+        let result = prepare_json_data(&mut data1.clone(), &mut data2.clone(), &strict_config);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Diff error: a.json has keys, that are the same after normalization: A, a"
+        );
+    }
+
+    #[test]
+    fn test_normalize_keys_reports_no_key_diff() {
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_value_diffs(true)
+            .normalize_keys(Some(KeyNormalization::Trim))
+            .build();
+        let json_app = JsonApp::from_data(
+            as_map(json!({ " a ": 1, "B": 2 })),
+            as_map(json!({ "a": 2, "b": 2 })),
+            get_working_context(config),
+        );
+
+        let (key_diffs, _, value_diffs, _) = json_app.perform_new_check();
+
+        let key_diffs = key_diffs.unwrap();
+        assert_eq!(key_diffs.len(), 2, "trim should keep the case of the keys");
+        assert!(key_diffs.iter().all(|kd| kd.key == "B" || kd.key == "b"));
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, " a ");
+    }
+
+    #[test]
+    fn test_match_rounded_numbers() {
+        let mut value1 = json!({ "a": 1.004, "b": [1.01, 3], "c": "1.0", "d": 2 });
+        let mut value2 = json!({ "a": 1.001, "b": [1.02, 3.001], "c": 1.0, "d": 2.0 });
+
+        match_rounded_numbers(&mut value1, &mut value2, 2, true);
+
+        assert_eq!(
+            value2,
+            json!({ "a": 1.004, "b": [1.02, 3], "c": 1.0, "d": 2 })
+        );
+    }
+
+    #[test]
+    fn test_match_rounded_numbers_in_unordered_arrays() {
+        let mut value1 = json!({ "b": ["x", 1.004, 7] });
+        let mut value2 = json!({ "b": [1.001, "x", 7] });
+
+        match_rounded_numbers(&mut value1, &mut value2, 2, false);
+
+        assert_eq!(value1, json!({ "b": ["x", 1.0, 7] }));
+        assert_eq!(value2, json!({ "b": [1.0, "x", 7] }));
+    }
+
+    #[test]
+    fn test_match_rounded_numbers_compares_large_integers_exactly() {
+        let mut value1 = json!({ "a": 9_007_199_254_740_993_u64, "b": 9_007_199_254_740_993_u64 });
+        let mut value2 = json!({ "a": 9_007_199_254_740_992_u64, "b": 9_007_199_254_740_992.0 });
+
+        match_rounded_numbers(&mut value1, &mut value2, 2, true);
+
+        assert_eq!(
+            value2,
+            json!({ "a": 9_007_199_254_740_992_u64, "b": 9_007_199_254_740_992.0 })
+        );
+    }
+
+    #[test]
+    fn test_round_reports_value_diffs_beyond_digits() {
+        let config = ConfigBuilder::new()
+            .check_for_value_diffs(true)
+            .round(Some(2))
+            .build();
+        let json_app = JsonApp::from_data(
+            as_map(json!({ "a": 1.004, "b": 1.01 })),
+            as_map(json!({ "a": 1.001, "b": 1.02 })),
+            get_working_context(config),
+        );
+
+        let (_, _, value_diffs, _) = json_app.perform_new_check();
+
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "b");
+    }
+
+    #[test]
+    fn test_empty_equals_null() {
+        let check = |empty_equals_null| {
+            let config = ConfigBuilder::new()
+                .check_for_type_diffs(true)
+                .check_for_value_diffs(true)
+                .empty_equals_null(empty_equals_null)
+                .build();
+            JsonApp::from_data(
+                as_map(json!({ "a": null, "b": [""], "c": { "d": "" }, "e": 0 })),
+                as_map(json!({ "a": "", "b": [null], "c": { "d": null }, "e": null })),
+                get_working_context(config),
+            )
+            .perform_new_check()
+        };
+
+        let (_, type_diffs, value_diffs, _) = check(true);
+        let type_diffs = type_diffs.unwrap();
+        assert_eq!(type_diffs.len(), 1, "only null and \"\" are equal");
+        assert_eq!(type_diffs[0].key, "e");
+        assert!(value_diffs.unwrap().is_empty());
+
+        let (_, type_diffs, _, _) = check(false);
+        assert!(type_diffs.unwrap().iter().any(|td| td.key == "a"));
+    }
+
+    #[test]
+    fn test_match_empty_strings_in_unordered_arrays() {
+        let mut value1 = json!({ "b": [1, "", { "c": null }] });
+        let mut value2 = json!({ "b": [null, 1, { "c": "" }] });
+
+        match_empty_strings(&mut value1, &mut value2, false);
+
+        assert_eq!(value1, json!({ "b": [1, "", { "c": "" }] }));
+        assert_eq!(value2, json!({ "b": ["", 1, { "c": "" }] }));
+    }
+
+    #[test]
+    fn test_numbers_to_text() {
+        let mut value1 = json!({ "a": 1, "b": ["x", 1.5, 2], "c": 3, "d": { "e": 4 } });
+        let mut value2 = json!({ "a": 1.0, "b": [1.5, "x"], "c": "3", "d": { "e": 4 } });
+
+        numbers_to_text(&mut value1, &mut value2, false);
+
+        assert_eq!(
+            value1,
+            json!({ "a": "1", "b": ["x", "1.5", "2"], "c": 3, "d": { "e": "4" } })
+        );
+        assert_eq!(
+            value2,
+            json!({ "a": "1.0", "b": ["1.5", "x"], "c": "3", "d": { "e": "4" } })
+        );
+    }
+
+    #[test]
+    fn test_numbers_to_text_in_same_order() {
+        let mut value1 = json!({ "b": [1.5, 2, 3] });
+        let mut value2 = json!({ "b": [1.5, "2"] });
+
+        numbers_to_text(&mut value1, &mut value2, true);
+
+        assert_eq!(value1, json!({ "b": ["1.5", 2, 3] }));
+        assert_eq!(value2, json!({ "b": ["1.5", "2"] }));
+    }
+
+    #[test]
+    fn test_numbers_as_text_reports_value_diff() {
+        let config = ConfigBuilder::new()
+            .check_for_type_diffs(true)
+            .check_for_value_diffs(true)
+            .numbers_as_text(true)
+            .build();
+        let json_app = JsonApp::from_data(
+            as_map(json!({ "a": 1, "b": 2 })),
+            as_map(json!({ "a": 1.0, "b": 2 })),
+            get_working_context(config),
+        );
+
+        let (_, type_diffs, value_diffs, _) = json_app.perform_new_check();
+
+        assert!(type_diffs.unwrap().is_empty());
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "a");
+    }
+
+    #[test]
+    fn test_array_key_matches_elements_by_key() {
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_type_diffs(true)
+            .check_for_value_diffs(true)
+            .check_for_array_diffs(true)
+            .array_key(Some("id".to_owned()))
+            .build();
+        let json_app = JsonApp::from_data(
+            as_map(json!({ "members": [
+                { "id": 1, "name": "Sarah" },
+                { "id": 2, "name": "John" }
+            ] })),
+            as_map(json!({ "members": [
+                { "id": 2, "name": "Johnny" },
+                { "id": 1, "name": "Sarah" }
+            ] })),
+            get_working_context(config.clone()),
+        );
+
+        let (key_diffs, type_diffs, value_diffs, array_diffs) =
+            process_diffs(json_app.perform_new_check(), &config).unwrap();
+
+        assert!(key_diffs.unwrap().is_empty());
+        assert!(type_diffs.unwrap().is_empty());
+        assert!(array_diffs.unwrap().is_empty());
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "members[id=2].name");
+    }
+
+    #[test]
+    fn test_array_key_reports_unmatched_elements() {
+        let config = ConfigBuilder::new()
+            .array_key(Some("id".to_owned()))
+            .build();
+        let mut data1 = as_map(json!({ "members": [{ "id": "a" }, { "id": "b" }] }));
+        let mut data2 = as_map(json!({ "members": [{ "id": "b" }, { "id": "c" }] }));
+
+        let (array_diffs, _) = prepare_json_data(&mut data1, &mut data2, &config).unwrap();
+
+        assert_eq!(
+            array_diffs,
+            vec![
+                ArrayDiff {
+                    key: "members".to_owned(),
+                    descriptor: ArrayDiffDesc::AHas,
+                    value: r#"{"id":"a"}"#.to_owned(),
+                },
+                ArrayDiff {
+                    key: "members".to_owned(),
+                    descriptor: ArrayDiffDesc::BHas,
+                    value: r#"{"id":"c"}"#.to_owned(),
+                },
+            ]
+        );
+        assert_eq!(
+            Value::Object(data1),
+            json!({ "members": { "[id=b]": { "id": "b" } } })
+        );
+    }
+
+    #[test]
+    fn test_array_key_leaves_arrays_without_key() {
+        let config = ConfigBuilder::new()
+            .array_key(Some("id".to_owned()))
+            .build();
+        let mut data1 = as_map(json!({ "a": [{ "id": 1 }, { "name": "x" }], "b": [1, 2] }));
+        let mut data2 = as_map(json!({ "a": [{ "id": 1 }], "b": [2, 1] }));
+
+        let (array_diffs, _) = prepare_json_data(&mut data1, &mut data2, &config).unwrap();
+
+        assert!(array_diffs.is_empty());
+        assert_eq!(
+            Value::Object(data1),
+            json!({ "a": [{ "id": 1 }, { "name": "x" }], "b": [1, 2] })
+        );
+    }
+
+    #[test]
+    fn test_align_elements() {
+        let values1 = vec![json!("a"), json!("b"), json!("c"), json!("d")];
+        let values2 = vec![json!("x"), json!("a"), json!("c"), json!("y")];
+
+        assert_eq!(
+            align_elements(&values1, &values2),
+            vec![
+                ArrayEdit::Inserted(0),
+                ArrayEdit::Paired(0, 1),
+                ArrayEdit::Removed(1),
+                ArrayEdit::Paired(2, 2),
+                ArrayEdit::Paired(3, 3),
+            ]
+        );
+        assert_eq!(
+            align_elements(&values1[..2], &[json!("a"), json!("b"), json!("x")]),
+            vec![
+                ArrayEdit::Paired(0, 0),
+                ArrayEdit::Paired(1, 1),
+                ArrayEdit::Inserted(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_array_same_order_formats_elements_like_libdtf() {
+        let config = ConfigBuilder::new()
+            .check_for_array_diffs(true)
+            .array_same_order(true)
+            .build();
+        let mut data1 = as_map(json!({ "tags": ["a", "b"] }));
+        let mut data2 = as_map(json!({ "tags": ["a", "b", "x"] }));
+
+        let (array_diffs, _) = prepare_json_data(&mut data1, &mut data2, &config).unwrap();
+
+        assert_eq!(
+            array_diffs,
+            vec![ArrayDiff {
+                key: "tags[2]".to_owned(),
+                descriptor: ArrayDiffDesc::BHas,
+                value: "x".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_align_elements_of_large_arrays_by_index() {
+        let values1 = (0..3000).map(|value| json!(value)).collect::<Vec<Value>>();
+        let values2 = (0..3001)
+            .rev()
+            .map(|value| json!(value))
+            .collect::<Vec<Value>>();
+
+        let edits = align_elements(&values1, &values2);
+
+        assert_eq!(edits.len(), 3001);
+        assert_eq!(edits[2999], ArrayEdit::Paired(2999, 2999));
+        assert_eq!(edits[3000], ArrayEdit::Inserted(3000));
+    }
+
+    #[test]
+    fn test_array_same_order_reports_single_insertion() {
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_type_diffs(true)
+            .check_for_value_diffs(true)
+            .check_for_array_diffs(true)
+            .array_same_order(true)
+            .build();
+        let json_app = JsonApp::from_data(
+            as_map(json!({ "items": [{ "id": 1 }, { "id": 2 }, { "id": 3, "tags": ["a"] }] })),
+            as_map(
+                json!({ "items": [{ "id": 0 }, { "id": 1 }, { "id": 2 }, { "id": 3, "tags": ["b"] }] }),
+            ),
+            get_working_context(config.clone()),
+        );
+
+        let (key_diffs, type_diffs, value_diffs, array_diffs) =
+            process_diffs(json_app.perform_new_check(), &config).unwrap();
+
+        assert!(key_diffs.unwrap().is_empty());
+        assert!(type_diffs.unwrap().is_empty());
+        let value_diffs = value_diffs.unwrap();
+        assert_eq!(value_diffs.len(), 1);
+        assert_eq!(value_diffs[0].key, "items[2].tags[0]");
+        assert_eq!(
+            array_diffs.unwrap(),
+            vec![ArrayDiff {
+                key: "items[0]".to_owned(),
+                descriptor: ArrayDiffDesc::BHas,
+                value: r#"{"id":0}"#.to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_array_same_order_keys_insertions_by_their_index() {
+        let config = ConfigBuilder::new()
+            .check_for_array_diffs(true)
+            .array_same_order(true)
+            .build();
+        let mut data1 = as_map(json!({ "arr": [1] }));
+        let mut data2 = as_map(json!({ "arr": [1, 2, 3] }));
+
+        let (array_diffs, _) = prepare_json_data(&mut data1, &mut data2, &config).unwrap();
+
+        let keys: Vec<&str> = array_diffs.iter().map(|diff| diff.key.as_str()).collect();
+        assert_eq!(keys, vec!["arr[1]", "arr[2]"]);
+    }
+
+    #[test]
+    fn test_array_same_order_reports_insertions_without_array_diffs() {
+        let config = ConfigBuilder::new()
+            .check_for_value_diffs(true)
+            .array_same_order(true)
+            .build();
+        let json_app = JsonApp::from_data(
+            as_map(json!({ "arr": [1, 2, 3] })),
+            as_map(json!({ "arr": [0, 1, 2, 3] })),
+            get_working_context(config.clone()),
+        );
+
+        let (_, _, value_diffs, _) = process_diffs(json_app.perform_new_check(), &config).unwrap();
+
+        assert!(!value_diffs.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_report_moves_reports_single_move() {
+        let config = ConfigBuilder::new().report_moves(true).build();
+        let mut data1 = as_map(json!({ "tags": ["x", "a", "b"], "ids": [1, 2, 3] }));
+        let mut data2 = as_map(json!({ "tags": ["a", "b", "x"], "ids": [0, 1, 2, 3] }));
+
+        let (_, moved_elements) = prepare_json_data(&mut data1, &mut data2, &config).unwrap();
+
+        assert_eq!(
+            moved_elements,
+            vec![MovedElement {
+                key: "tags".to_owned(),
+                value: r#""x""#.to_owned(),
+                from: 0,
+                to: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_report_moves_matches_objects_by_array_key() {
+        let config = ConfigBuilder::new()
+            .report_moves(true)
+            .array_key(Some("id".to_owned()))
+            .build();
+        let mut data1 = as_map(json!({ "items": [
+            { "id": 1, "tags": ["a", "b"] },
+            { "id": 2 },
+            { "id": 3 }
+        ] }));
+        let mut data2 = as_map(json!({ "items": [
+            { "id": 2 },
+            { "id": 3 },
+            { "id": 1, "tags": ["b", "a"] }
+        ] }));
+
+        let (_, moved_elements) = prepare_json_data(&mut data1, &mut data2, &config).unwrap();
+
+        let moves: Vec<(&str, usize, usize)> = moved_elements
+            .iter()
+            .map(|moved| (moved.key.as_str(), moved.from, moved.to))
+            .collect();
+        assert_eq!(moves, vec![("items", 0, 2), ("items[id=1].tags", 0, 1)]);
+    }
+
+    #[test]
+    fn test_subtree_pointer() {
+        let config = ConfigBuilder::new()
+            .subtree_pointer(Some("/data/items".to_owned()))
+            .build();
+        let mut data1 = as_map(json!({ "meta": 1, "data": { "items": [{ "a": 1 }, 2] } }));
+        let mut data2 = as_map(json!({ "meta": 2, "data": { "items": { "a": 1 } } }));
+
+        prepare_json_data(&mut data1, &mut data2, &config).unwrap();
+
+        assert_eq!(Value::Object(data1), json!({ "[0]": { "a": 1 }, "[1]": 2 }));
+        assert_eq!(Value::Object(data2), json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn test_subtree_pointer_missing() {
+        let config = ConfigBuilder::new()
+            .subtree_pointer(Some("/data".to_owned()))
+            .file_b(Some("b.json".to_owned()))
+            .build();
+        let mut data1 = as_map(json!({ "data": {} }));
+        let mut data2 = as_map(json!({ "other": {} }));
+
+        let error = prepare_json_data(&mut data1, &mut data2, &config).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Diff error: /data is missing from b.json"
+        );
+    }
+
+    #[test]
+    fn test_require_non_empty() {
+        let config = ConfigBuilder::new()
+            .file_a(Some("a.json".to_owned()))
+            .file_b(Some("b.json".to_owned()))
+            .require_non_empty(true)
+            .build();
+
+        let result = prepare_json_data(&mut as_map(json!({ "a": 1 })), &mut Map::new(), &config);
+
+        assert!(matches!(result, Err(DtfError::EmptyFile(file)) if file == "b.json"));
+    }
+
+    #[test]
+    fn test_expand_env() {
+        let home = env::var("HOME").unwrap();
+        let config = ConfigBuilder::new()
+            .expand_env(Some(EnvExpansion::Keep))
+            .build();
+        let mut data1 = as_map(json!({
+            "paths": { "data": "${HOME}/data", "cache": "${DTF_UNSET_VARIABLE}/cache" },
+            "template": "${HOME"
+        }));
+        let mut data2 = as_map(json!({
+            "paths": { "data": format!("{}/data", home), "cache": "${DTF_UNSET_VARIABLE}/cache" },
+            "template": "${HOME"
+        }));
+
+        prepare_json_data(&mut data1, &mut data2, &config).unwrap();
+
+        assert_eq!(data1, data2);
+    }
+
+    #[test]
+    fn test_expand_env_with_unset_variable() {
+        let config = ConfigBuilder::new()
+            .file_a(Some("a.json".to_owned()))
+            .expand_env(Some(EnvExpansion::Error))
+            .build();
+
+        let result = prepare_json_data(
+            &mut as_map(json!({ "url": "https://${DTF_UNSET_VARIABLE}/" })),
+            &mut Map::new(),
+            &config,
+        );
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Diff error: a.json uses the environment variable DTF_UNSET_VARIABLE, which is not set"
+        );
+    }
+
+    #[test]
+    fn test_empty_file_checked_without_require_non_empty() {
+        let config = ConfigBuilder::new().check_for_key_diffs(true).build();
+        let json_app = JsonApp::from_data(
+            as_map(json!({ "a": 1 })),
+            Map::new(),
+            get_working_context(config),
+        );
+
+        let (key_diffs, _, _, _) = json_app.perform_new_check();
+
+        assert_eq!(key_diffs.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_strict_with_unsupported_yaml_values() {
+        let config = ConfigBuilder::new()
+            .file_a(Some("a.yaml".to_owned()))
+            .strict(true)
+            .build();
+        let prepare = |yaml1: &str, yaml2: &str, config: &Config| {
+            let mut data1: Mapping = serde_yaml::from_str(yaml1).unwrap();
+            let mut data2: Mapping = serde_yaml::from_str(yaml2).unwrap();
+            prepare_yaml_data(&mut data1, &mut data2, config).map(|_| ())
+        };
+
+        assert!(prepare("a: [1, {b: 2}]", "a: 1", &config).is_ok());
+        assert!(prepare("a: !secret abc", "a: abc", &ConfigBuilder::new().build()).is_ok());
+        assert_eq!(
+            prepare("a: [1, {b: !secret abc}]", "a: 1", &config)
+                .unwrap_err()
+                .to_string(),
+            "Diff error: Unsupported value in a.yaml: the value at a[1].b is tagged !secret"
+        );
+        assert_eq!(
+            prepare("a:\n  1: x", "a: 1", &config)
+                .unwrap_err()
+                .to_string(),
+            "Diff error: Unsupported value in a.yaml: the key 1 at a is not a string"
+        );
+    }
+
+    #[test]
+    fn test_prepare_yaml_data() {
+        let config = ConfigBuilder::new().null_is_missing(true).build();
+        let mut data1: Mapping = serde_yaml::from_str("a: null\nb: 1").unwrap();
+        let mut data2: Mapping = serde_yaml::from_str("a: 1\nb: 2").unwrap();
+
+        prepare_yaml_data(&mut data1, &mut data2, &config).unwrap();
+
+        assert_eq!(data1, serde_yaml::from_str::<Mapping>("b: 1").unwrap());
+        assert_eq!(
+            data2,
+            serde_yaml::from_str::<Mapping>("a: 1\nb: 2").unwrap()
+        );
+    }
+
+    fn as_map(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    fn get_working_context(config: Config) -> WorkingContext {
+        let working_file_a = libdtf::core::diff_types::WorkingFile::new("FileA.json".to_string());
+        let working_file_b = libdtf::core::diff_types::WorkingFile::new("FileB.json".to_string());
+        let lib_working_context = libdtf::core::diff_types::WorkingContext::new(
+            working_file_a,
+            working_file_b,
+            libdtf::core::diff_types::Config {
+                array_same_order: config.array_same_order,
+            },
+        );
+        WorkingContext::new(lib_working_context, config)
+    }
+}
@@ -0,0 +1,337 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dtfterminal_types::{
+        Config, DiffKind, DtfError, EnvExpansion, KeyNormalization, Severity, SeverityOverride,
+        ValueType,
+    },
+    file_handler::write_atomically,
+};
+
+/// The profiles saved by the user, by name
+type Profiles = BTreeMap<String, Profile>;
+
+/// The settings of a check saved under a name, so they can be reused with `--profile`.
+/// The paths of the checked files and the output files are not part of it, neither is the theme.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub key_diffs: bool,
+    pub type_diffs: bool,
+    pub value_diffs: bool,
+    pub array_diffs: bool,
+    pub array_same_order: bool,
+    pub printer_friendly: bool,
+    pub no_browser_show: bool,
+    pub null_is_missing: bool,
+    pub quiet: bool,
+    pub ignore_patterns: Vec<String>,
+    pub ignore_globs: Vec<String>,
+    pub only_patterns: Vec<String>,
+    pub warn_duplicate_keys: bool,
+    pub intersection_only: bool,
+    pub compact_values: bool,
+    pub fail_on: Vec<DiffKind>,
+    pub no_color: bool,
+    pub numbers_as_text: bool,
+    pub array_key: Option<String>,
+    pub json_pointer: bool,
+    pub verbose: bool,
+    pub context_size: usize,
+    pub no_dedup: bool,
+    pub print_summary: bool,
+    pub subtree_pointer: Option<String>,
+    pub plain: bool,
+    pub limit: Option<usize>,
+    pub swap: bool,
+    pub group_by_key: bool,
+    pub explain: bool,
+    pub max_value_len: Option<usize>,
+    pub normalize_keys: Option<KeyNormalization>,
+    pub combine_type_value: bool,
+    pub round: Option<u32>,
+    pub include_equal: bool,
+    pub max_width: Option<usize>,
+    pub require_non_empty: bool,
+    pub ignore_types: Vec<ValueType>,
+    pub by_direction: bool,
+    pub abbreviate_keys: bool,
+    pub show_delta: bool,
+    pub thousands_sep: Option<String>,
+    pub expand_env: Option<EnvExpansion>,
+    pub strict: bool,
+    pub smart_dates: bool,
+    pub min_severity: Option<Severity>,
+    pub severity_overrides: Vec<SeverityOverride>,
+    pub count_only: bool,
+    pub stacked: bool,
+    pub max_array_diffs: Option<usize>,
+    pub empty_equals_null: bool,
+    pub side_by_side: bool,
+    pub min_array_size: Option<usize>,
+    pub only_changed_files: bool,
+    pub key_separator: Option<String>,
+    pub report_moves: bool,
+}
+
+impl Profile {
+    /// Creates a profile from the settings of the current run
+    pub fn from_config(config: &Config) -> Profile {
+        Profile {
+            key_diffs: config.check_for_key_diffs,
+            type_diffs: config.check_for_type_diffs,
+            value_diffs: config.check_for_value_diffs,
+            array_diffs: config.check_for_array_diffs,
+            array_same_order: config.array_same_order,
+            printer_friendly: config.printer_friendly,
+            no_browser_show: config.no_browser_show,
+            null_is_missing: config.null_is_missing,
+            quiet: config.quiet,
+            ignore_patterns: config.ignore_patterns.clone(),
+            ignore_globs: config.ignore_globs.clone(),
+            only_patterns: config.only_patterns.clone(),
+            warn_duplicate_keys: config.warn_duplicate_keys,
+            intersection_only: config.intersection_only,
+            compact_values: config.compact_values,
+            fail_on: config.fail_on.clone(),
+            no_color: config.no_color,
+            numbers_as_text: config.numbers_as_text,
+            array_key: config.array_key.clone(),
+            json_pointer: config.json_pointer,
+            verbose: config.verbose,
+            context_size: config.context_size,
+            no_dedup: config.no_dedup,
+            print_summary: config.print_summary,
+            subtree_pointer: config.subtree_pointer.clone(),
+            plain: config.plain,
+            limit: config.limit,
+            swap: config.swap,
+            group_by_key: config.group_by_key,
+            explain: config.explain,
+            max_value_len: config.max_value_len,
+            normalize_keys: config.normalize_keys,
+            combine_type_value: config.combine_type_value,
+            round: config.round,
+            include_equal: config.include_equal,
+            max_width: config.max_width,
+            require_non_empty: config.require_non_empty,
+            ignore_types: config.ignore_types.clone(),
+            by_direction: config.by_direction,
+            abbreviate_keys: config.abbreviate_keys,
+            show_delta: config.show_delta,
+            thousands_sep: config.thousands_sep.clone(),
+            expand_env: config.expand_env,
+            strict: config.strict,
+            smart_dates: config.smart_dates,
+            min_severity: config.min_severity,
+            severity_overrides: config.severity_overrides.clone(),
+            count_only: config.count_only,
+            stacked: config.stacked,
+            max_array_diffs: config.max_array_diffs,
+            empty_equals_null: config.empty_equals_null,
+            side_by_side: config.side_by_side,
+            min_array_size: config.min_array_size,
+            only_changed_files: config.only_changed_files,
+            key_separator: config.key_separator.clone(),
+            report_moves: config.report_moves,
+        }
+    }
+
+    /// Merges the profile into the settings given on the command line.
+    /// Flags are on if either of them turns them on, and every other setting given on the command line replaces the one of the profile.
+    /// As the command line has no way to turn a flag off, the flags saved in a profile are always on when it is used.
+    pub fn apply(self, config: Config) -> Config {
+        Config {
+            check_for_key_diffs: config.check_for_key_diffs || self.key_diffs,
+            check_for_type_diffs: config.check_for_type_diffs || self.type_diffs,
+            check_for_value_diffs: config.check_for_value_diffs || self.value_diffs,
+            check_for_array_diffs: config.check_for_array_diffs || self.array_diffs,
+            render_key_diffs: config.render_key_diffs || self.key_diffs,
+            render_type_diffs: config.render_type_diffs || self.type_diffs,
+            render_value_diffs: config.render_value_diffs || self.value_diffs,
+            render_array_diffs: config.render_array_diffs || self.array_diffs,
+            array_same_order: config.array_same_order || self.array_same_order,
+            printer_friendly: config.printer_friendly || self.printer_friendly,
+            no_browser_show: config.no_browser_show || self.no_browser_show,
+            null_is_missing: config.null_is_missing || self.null_is_missing,
+            quiet: config.quiet || self.quiet,
+            ignore_patterns: given_or_saved(config.ignore_patterns, self.ignore_patterns),
+            ignore_globs: given_or_saved(config.ignore_globs, self.ignore_globs),
+            only_patterns: given_or_saved(config.only_patterns, self.only_patterns),
+            warn_duplicate_keys: config.warn_duplicate_keys || self.warn_duplicate_keys,
+            intersection_only: config.intersection_only || self.intersection_only,
+            compact_values: config.compact_values || self.compact_values,
+            fail_on: given_or_saved(config.fail_on, self.fail_on),
+            no_color: config.no_color || self.no_color,
+            numbers_as_text: config.numbers_as_text || self.numbers_as_text,
+            array_key: config.array_key.or(self.array_key),
+            json_pointer: config.json_pointer || self.json_pointer,
+            verbose: config.verbose || self.verbose,
+            context_size: if config.context_size > 0 {
+                config.context_size
+            } else {
+                self.context_size
+            },
+            no_dedup: config.no_dedup || self.no_dedup,
+            print_summary: config.print_summary || self.print_summary,
+            subtree_pointer: config.subtree_pointer.or(self.subtree_pointer),
+            plain: config.plain || self.plain,
+            limit: config.limit.or(self.limit),
+            swap: config.swap || self.swap,
+            group_by_key: config.group_by_key || self.group_by_key,
+            explain: config.explain || self.explain,
+            max_value_len: config.max_value_len.or(self.max_value_len),
+            normalize_keys: config.normalize_keys.or(self.normalize_keys),
+            combine_type_value: config.combine_type_value || self.combine_type_value,
+            round: config.round.or(self.round),
+            include_equal: config.include_equal || self.include_equal,
+            max_width: config.max_width.or(self.max_width),
+            require_non_empty: config.require_non_empty || self.require_non_empty,
+            ignore_types: given_or_saved(config.ignore_types, self.ignore_types),
+            by_direction: config.by_direction || self.by_direction,
+            abbreviate_keys: config.abbreviate_keys || self.abbreviate_keys,
+            show_delta: config.show_delta || self.show_delta,
+            thousands_sep: config.thousands_sep.or(self.thousands_sep),
+            expand_env: config.expand_env.or(self.expand_env),
+            strict: config.strict || self.strict,
+            smart_dates: config.smart_dates || self.smart_dates,
+            min_severity: config.min_severity.or(self.min_severity),
+            severity_overrides: given_or_saved(config.severity_overrides, self.severity_overrides),
+            count_only: config.count_only || self.count_only,
+            stacked: config.stacked || self.stacked,
+            max_array_diffs: config.max_array_diffs.or(self.max_array_diffs),
+            empty_equals_null: config.empty_equals_null || self.empty_equals_null,
+            side_by_side: config.side_by_side || self.side_by_side,
+            min_array_size: config.min_array_size.or(self.min_array_size),
+            only_changed_files: config.only_changed_files || self.only_changed_files,
+            key_separator: config.key_separator.or(self.key_separator),
+            report_moves: config.report_moves || self.report_moves,
+            ..config
+        }
+    }
+}
+
+/// Gets the path of the profiles file in the config directory of the user
+pub fn get_profiles_path() -> Result<PathBuf, DtfError> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .ok_or(DtfError::DiffError(
+            "Could not find the config directory to store profiles in".to_owned(),
+        ))?;
+    Ok(config_dir.join("dtfterminal").join("profiles.json"))
+}
+
+/// Saves the profile under the name in the profiles file, replacing the profile already saved with the same name
+pub fn save_profile(profiles_path: &Path, name: &str, profile: Profile) -> Result<(), DtfError> {
+    let mut profiles = read_profiles(profiles_path)?;
+    profiles.insert(name.to_owned(), profile);
+
+    if let Some(dir) = profiles_path.parent() {
+        fs::create_dir_all(dir).map_err(DtfError::IoError)?;
+    }
+    write_atomically(profiles_path, |file| {
+        let content = serde_json::to_string_pretty(&profiles)
+            .map_err(|e| DtfError::DiffError(e.to_string()))?;
+        file.write_all(content.as_bytes())
+            .map_err(DtfError::IoError)
+    })
+}
+
+/// Loads the profile saved under the name from the profiles file
+pub fn load_profile(profiles_path: &Path, name: &str) -> Result<Profile, DtfError> {
+    read_profiles(profiles_path)?
+        .remove(name)
+        .ok_or(DtfError::DiffError(format!(
+            "There is no profile named {}",
+            name
+        )))
+}
+
+/// Reads every profile from the profiles file. A missing file has no profiles
+fn read_profiles(profiles_path: &Path) -> Result<Profiles, DtfError> {
+    match fs::read_to_string(profiles_path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| DtfError::parse_error(&profiles_path.to_string_lossy(), e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Profiles::new()),
+        Err(e) => Err(DtfError::IoError(e)),
+    }
+}
+
+/// Keeps the values given on the command line, or falls back to the saved ones if none were given
+fn given_or_saved<T>(given: Vec<T>, saved: Vec<T>) -> Vec<T> {
+    if given.is_empty() {
+        saved
+    } else {
+        given
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dtfterminal_types::ConfigBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_save_and_load_profile() {
+        let profiles_path = env::temp_dir()
+            .join("dtfterminal_profiles_test")
+            .join("profiles.json");
+        let config = ConfigBuilder::new()
+            .check_for_key_diffs(true)
+            .check_for_value_diffs(true)
+            .file_a(Some("a.json".to_owned()))
+            .file_b(Some("b.json".to_owned()))
+            .ignore_patterns(vec!["^meta\\.".to_owned()])
+            .fail_on(vec![DiffKind::Value])
+            .array_key(Some("id".to_owned()))
+            .context_size(2)
+            .build();
+
+        save_profile(&profiles_path, "nightly", Profile::from_config(&config)).unwrap();
+        save_profile(&profiles_path, "other", Profile::default()).unwrap();
+        let loaded = load_profile(&profiles_path, "nightly")
+            .unwrap()
+            .apply(ConfigBuilder::new().build());
+
+        assert!(loaded.check_for_key_diffs && loaded.render_key_diffs);
+        assert!(loaded.check_for_value_diffs && loaded.render_value_diffs);
+        assert!(!loaded.check_for_type_diffs);
+        assert_eq!(loaded.ignore_patterns, vec!["^meta\\.".to_owned()]);
+        assert_eq!(loaded.fail_on, vec![DiffKind::Value]);
+        assert_eq!(loaded.array_key, Some("id".to_owned()));
+        assert_eq!(loaded.context_size, 2);
+        assert_eq!(loaded.file_a, None);
+        assert!(load_profile(&profiles_path, "missing").is_err());
+        fs::remove_dir_all(profiles_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_apply_profile_keeps_given_settings() {
+        let profile = Profile {
+            ignore_patterns: vec!["saved".to_owned()],
+            array_key: Some("id".to_owned()),
+            limit: Some(5),
+            ..Profile::default()
+        };
+        let config = ConfigBuilder::new()
+            .ignore_patterns(vec!["given".to_owned()])
+            .limit(Some(10))
+            .build();
+
+        let merged = profile.apply(config);
+
+        assert_eq!(merged.ignore_patterns, vec!["given".to_owned()]);
+        assert_eq!(merged.array_key, Some("id".to_owned()));
+        assert_eq!(merged.limit, Some(10));
+    }
+}
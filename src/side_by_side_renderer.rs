@@ -0,0 +1,406 @@
+use colored::Colorize;
+use serde_json::{Map, Value};
+use terminal_size::{terminal_size, Width};
+
+use crate::{
+    dtfterminal_types::{iter_diffs, AnyDiff, DiffCollection, WorkingContext, DEFAULT_TABLE_WIDTH},
+    utils::split_key,
+};
+
+/// The line between the two documents
+const SEPARATOR: &str = " │ ";
+
+/// How a line is highlighted
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Change {
+    None,
+    /// The key is only in the second document
+    Added,
+    /// The key is only in the first document
+    Removed,
+    /// The value has a Type, Value or Array difference
+    Changed,
+}
+
+/// A line of a pretty-printed document, with the path of the value it shows
+struct Line {
+    text: String,
+    path: Vec<String>,
+}
+
+/// A line of the first document and the line of the second one shown next to it. A side is empty if the other document has no matching line
+type Row = (Option<Line>, Option<Line>);
+
+/// Renders both documents pretty-printed side by side, the first one on the left.
+/// The keys and items both documents have are lined up, and the lines of values with differences the user wants to see are colored:
+/// * Keys only in the first document are red, keys only in the second one are green
+/// * Values with Type, Value or Array differences are yellow. Array differences color the whole array, as the order of the items is not checked
+pub fn render_side_by_side(
+    diffs: &DiffCollection,
+    data: (&Value, &Value),
+    context: &WorkingContext,
+) -> String {
+    let config = &context.config;
+    let changes = iter_diffs(diffs)
+        .filter(|diff| match diff {
+            AnyDiff::Key(_) => config.render_key_diffs,
+            AnyDiff::Type(_) => config.render_type_diffs,
+            AnyDiff::Value(_) => config.render_value_diffs,
+            AnyDiff::Array(_) => config.render_array_diffs,
+        })
+        .map(|diff| {
+            (
+                split_key(diff.key(), config.json_pointer),
+                matches!(diff, AnyDiff::Key(_)),
+            )
+        })
+        .collect::<Vec<(Vec<String>, bool)>>();
+    let column_width = config
+        .max_width
+        .or_else(|| terminal_size().map(|(Width(width), _)| usize::from(width)))
+        .unwrap_or(DEFAULT_TABLE_WIDTH)
+        .saturating_sub(SEPARATOR.chars().count())
+        / 2;
+
+    let mut rows = vec![];
+    align_values(
+        None,
+        (Some(data.0), Some(data.1)),
+        &[],
+        0,
+        (false, false),
+        &mut rows,
+    );
+
+    let header = format!(
+        "{}{}{}",
+        fit(&context.lib_working_context.file_a.name, column_width).bold(),
+        SEPARATOR,
+        context.lib_working_context.file_b.name.bold()
+    );
+    let lines = rows.into_iter().map(|(line_a, line_b)| {
+        let left = line_a.as_ref().map_or(Change::None, |line| {
+            change_of(&line.path, &changes, Change::Removed)
+        });
+        let right = line_b.as_ref().map_or(Change::None, |line| {
+            change_of(&line.path, &changes, Change::Added)
+        });
+        let text_a = line_a.map(|line| line.text).unwrap_or_default();
+        let text_b = line_b.map(|line| line.text).unwrap_or_default();
+        format!(
+            "{}{}{}",
+            paint(&fit(&text_a, column_width), left),
+            SEPARATOR,
+            paint(&text_b, right)
+        )
+        .trim_end()
+        .to_owned()
+    });
+
+    std::iter::once(header)
+        .chain(lines)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Pretty-prints the values of both documents at the same path into rows.
+/// Objects and arrays in both documents are opened on the same row, and their keys and items are lined up.
+/// Keys only in the second document follow the keys of the first one.
+fn align_values(
+    key: Option<&str>,
+    values: (Option<&Value>, Option<&Value>),
+    path: &[String],
+    indent: usize,
+    commas: (bool, bool),
+    rows: &mut Vec<Row>,
+) {
+    // Only the opening line of an object or an array has the key
+    let line = |key: Option<&str>, value: &str, comma: bool| Line {
+        text: format_line(key, value, indent, comma),
+        path: path.to_vec(),
+    };
+    match values {
+        (Some(Value::Object(object_a)), Some(Value::Object(object_b)))
+            if !object_a.is_empty() && !object_b.is_empty() =>
+        {
+            rows.push((Some(line(key, "{", false)), Some(line(key, "{", false))));
+            let keys = object_a
+                .keys()
+                .chain(object_b.keys().filter(|key| !object_a.contains_key(*key)));
+            for child_key in keys {
+                let child_path = [path, std::slice::from_ref(child_key)].concat();
+                align_values(
+                    Some(child_key),
+                    (object_a.get(child_key), object_b.get(child_key)),
+                    &child_path,
+                    indent + 1,
+                    (
+                        !is_last_key(object_a, child_key),
+                        !is_last_key(object_b, child_key),
+                    ),
+                    rows,
+                );
+            }
+            rows.push((
+                Some(line(None, "}", commas.0)),
+                Some(line(None, "}", commas.1)),
+            ));
+        }
+        (Some(Value::Array(array_a)), Some(Value::Array(array_b)))
+            if !array_a.is_empty() && !array_b.is_empty() =>
+        {
+            rows.push((Some(line(key, "[", false)), Some(line(key, "[", false))));
+            for index in 0..array_a.len().max(array_b.len()) {
+                let child_path = [path, &[index.to_string()]].concat();
+                align_values(
+                    None,
+                    (array_a.get(index), array_b.get(index)),
+                    &child_path,
+                    indent + 1,
+                    (index + 1 < array_a.len(), index + 1 < array_b.len()),
+                    rows,
+                );
+            }
+            rows.push((
+                Some(line(None, "]", commas.0)),
+                Some(line(None, "]", commas.1)),
+            ));
+        }
+        (value_a, value_b) => {
+            let mut lines_a = vec![];
+            let mut lines_b = vec![];
+            if let Some(value) = value_a {
+                print_value(key, value, path, indent, commas.0, &mut lines_a);
+            }
+            if let Some(value) = value_b {
+                print_value(key, value, path, indent, commas.1, &mut lines_b);
+            }
+            let mut lines_a = lines_a.into_iter();
+            let mut lines_b = lines_b.into_iter();
+            loop {
+                match (lines_a.next(), lines_b.next()) {
+                    (None, None) => break,
+                    row => rows.push(row),
+                }
+            }
+        }
+    }
+}
+
+/// Pretty-prints a value of a single document
+fn print_value(
+    key: Option<&str>,
+    value: &Value,
+    path: &[String],
+    indent: usize,
+    comma: bool,
+    lines: &mut Vec<Line>,
+) {
+    // Only the opening line of an object or an array has the key
+    let line = |key: Option<&str>, value: &str, comma: bool| Line {
+        text: format_line(key, value, indent, comma),
+        path: path.to_vec(),
+    };
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            lines.push(line(key, "{", false));
+            for (child_key, child) in object {
+                let child_path = [path, std::slice::from_ref(child_key)].concat();
+                let comma = !is_last_key(object, child_key);
+                print_value(
+                    Some(child_key),
+                    child,
+                    &child_path,
+                    indent + 1,
+                    comma,
+                    lines,
+                );
+            }
+            lines.push(line(None, "}", comma));
+        }
+        Value::Array(array) if !array.is_empty() => {
+            lines.push(line(key, "[", false));
+            for (index, item) in array.iter().enumerate() {
+                let child_path = [path, &[index.to_string()]].concat();
+                let comma = index + 1 < array.len();
+                print_value(None, item, &child_path, indent + 1, comma, lines);
+            }
+            lines.push(line(None, "]", comma));
+        }
+        _ => lines.push(line(key, &value.to_string(), comma)),
+    }
+}
+
+/// Formats a line of a document, indented by two spaces per level
+fn format_line(key: Option<&str>, value: &str, indent: usize, comma: bool) -> String {
+    let key = key
+        .map(|key| format!("{}: ", Value::String(key.to_owned())))
+        .unwrap_or_default();
+    let comma = if comma { "," } else { "" };
+    format!("{}{}{}{}", "  ".repeat(indent), key, value, comma)
+}
+
+/// Checks if the key is the last one of the object
+fn is_last_key(object: &Map<String, Value>, key: &str) -> bool {
+    object.keys().next_back().map(String::as_str) == Some(key)
+}
+
+/// Decides how a line is highlighted from the differences at its path or at the path of a value containing it.
+/// Key differences are highlighted as given, as they are only in the document, that has the key.
+fn change_of(path: &[String], changes: &[(Vec<String>, bool)], key_change: Change) -> Change {
+    changes
+        .iter()
+        .find(|(diff_path, _)| path.starts_with(diff_path))
+        .map_or(Change::None, |(_, is_key_diff)| {
+            if *is_key_diff {
+                key_change
+            } else {
+                Change::Changed
+            }
+        })
+}
+
+/// Colors the text of a line
+fn paint(text: &str, change: Change) -> String {
+    match change {
+        Change::None => text.to_owned(),
+        Change::Added => text.green().to_string(),
+        Change::Removed => text.red().to_string(),
+        Change::Changed => text.yellow().to_string(),
+    }
+}
+
+/// Pads the text with spaces to the width, or truncates it with an ellipsis if it's longer
+fn fit(text: &str, width: usize) -> String {
+    let length = text.chars().count();
+    if length <= width {
+        return format!("{}{}", text, " ".repeat(width - length));
+    }
+
+    let mut truncated = text
+        .chars()
+        .take(width.saturating_sub(1))
+        .collect::<String>();
+    truncated.push('\u{2026}');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use libdtf::core::diff_types::{KeyDiff, ValueDiff};
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_align_values() {
+        let data_a = json!({ "name": "Pen", "tags": ["sale"] });
+        let data_b = json!({ "name": "Pencil", "stock": { "count": 3 } });
+        let mut rows = vec![];
+
+        align_values(
+            None,
+            (Some(&data_a), Some(&data_b)),
+            &[],
+            0,
+            (false, false),
+            &mut rows,
+        );
+
+        let texts = rows
+            .iter()
+            .map(|(line_a, line_b)| {
+                (
+                    line_a.as_ref().map(|line| line.text.as_str()),
+                    line_b.as_ref().map(|line| line.text.as_str()),
+                )
+            })
+            .collect::<Vec<(Option<&str>, Option<&str>)>>();
+        assert_eq!(
+            texts,
+            vec![
+                (Some("{"), Some("{")),
+                (
+                    Some("  \"name\": \"Pen\","),
+                    Some("  \"name\": \"Pencil\",")
+                ),
+                (Some("  \"tags\": ["), None),
+                (Some("    \"sale\""), None),
+                (Some("  ]"), None),
+                (None, Some("  \"stock\": {")),
+                (None, Some("    \"count\": 3")),
+                (None, Some("  }")),
+                (Some("}"), Some("}")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_lines_colored() {
+        let changes = vec![
+            (vec!["name".to_owned()], false),
+            (vec!["stock".to_owned()], true),
+        ];
+        let name_path = vec!["name".to_owned()];
+        let count_path = vec!["stock".to_owned(), "count".to_owned()];
+
+        assert_eq!(
+            change_of(&name_path, &changes, Change::Added),
+            Change::Changed
+        );
+        assert_eq!(
+            change_of(&count_path, &changes, Change::Added),
+            Change::Added
+        );
+        assert_eq!(change_of(&[], &changes, Change::Added), Change::None);
+        assert_eq!(
+            paint("  \"name\": \"Pen\",", Change::Changed),
+            "  \"name\": \"Pen\",".yellow().to_string()
+        );
+    }
+
+    #[test]
+    fn test_render_side_by_side() {
+        let diffs: DiffCollection = (
+            Some(vec![KeyDiff {
+                key: "stock".to_owned(),
+                has: "b.json".to_owned(),
+                misses: "a.json".to_owned(),
+            }]),
+            None,
+            Some(vec![ValueDiff {
+                key: "name".to_owned(),
+                value1: "\"Pen\"".to_owned(),
+                value2: "\"Pencil\"".to_owned(),
+            }]),
+            None,
+        );
+        let context = crate::utils::create_working_context(
+            &crate::dtfterminal_types::ConfigBuilder::new()
+                .check_for_key_diffs(true)
+                .check_for_value_diffs(true)
+                .render_key_diffs(true)
+                .render_value_diffs(true)
+                .file_a(Some("a.json".to_owned()))
+                .file_b(Some("b.json".to_owned()))
+                .max_width(Some(43))
+                .build(),
+        )
+        .unwrap();
+
+        let rendered = render_side_by_side(
+            &diffs,
+            (
+                &json!({ "name": "Pen" }),
+                &json!({ "name": "Pencil", "stock": 3 }),
+            ),
+            &context,
+        );
+
+        let lines = rendered.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].contains("a.json") && lines[0].contains("b.json"));
+        assert!(lines[2].contains(&"  \"name\": \"Pen\"     ".yellow().to_string()));
+        assert!(lines[3].ends_with(&"  \"stock\": 3".green().to_string()));
+    }
+}
@@ -0,0 +1,297 @@
+use std::io::{self, Stdout};
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame, Terminal,
+};
+
+use crate::{
+    dtfterminal_types::{iter_diffs, AnyDiff, DiffCollection, DiffKind, DtfError, WorkingContext},
+    utils::describe_array_diff,
+};
+
+/// The categories of differences to browse, with their titles
+const CATEGORIES: [(DiffKind, &str); 4] = [
+    (DiffKind::Key, "Key Differences"),
+    (DiffKind::Type, "Type Differences"),
+    (DiffKind::Value, "Value Differences"),
+    (DiffKind::Array, "Array Differences"),
+];
+
+/// Interactive view in the terminal to browse the differences by category and filter them by key
+pub struct TuiApp {
+    state: TuiState,
+}
+
+impl TuiApp {
+    pub fn new(diffs: &DiffCollection, context: &WorkingContext) -> TuiApp {
+        let (file_a, file_b) = context.get_file_names();
+        TuiApp {
+            state: TuiState::new(
+                iter_diffs(diffs).collect(),
+                (file_a.to_owned(), file_b.to_owned()),
+            ),
+        }
+    }
+
+    /// Takes over the terminal until the user quits, then restores it
+    pub fn run(&mut self) -> Result<(), DtfError> {
+        enable_raw_mode().map_err(DtfError::IoError)?;
+        execute!(io::stdout(), EnterAlternateScreen).map_err(DtfError::IoError)?;
+
+        let result = Terminal::new(CrosstermBackend::new(io::stdout()))
+            .map_err(DtfError::IoError)
+            .and_then(|mut terminal| self.event_loop(&mut terminal));
+
+        disable_raw_mode().map_err(DtfError::IoError)?;
+        execute!(io::stdout(), LeaveAlternateScreen).map_err(DtfError::IoError)?;
+        result
+    }
+
+    /// Draws the view and handles key presses until the user quits
+    fn event_loop(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    ) -> Result<(), DtfError> {
+        while !self.state.quit {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(DtfError::IoError)?;
+
+            if let Event::Key(key) = event::read().map_err(DtfError::IoError)? {
+                if key.kind == KeyEventKind::Press {
+                    self.state.handle_key(key.code);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws the filter box, the list of categories or differences and the help line
+    fn draw(&self, frame: &mut Frame) {
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(frame.size());
+
+        let filter_title = if self.state.editing_filter {
+            "Filter by key (Enter to apply)"
+        } else {
+            "Filter by key (/ to edit)"
+        };
+        frame.render_widget(
+            Paragraph::new(self.state.filter.as_str())
+                .block(Block::default().borders(Borders::ALL).title(filter_title)),
+            areas[0],
+        );
+
+        let items = self
+            .state
+            .visible_lines()
+            .into_iter()
+            .map(ListItem::new)
+            .collect::<Vec<ListItem>>();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(self.state.title()),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut list_state = ListState::default().with_selected(Some(self.state.selected));
+        frame.render_stateful_widget(list, areas[1], &mut list_state);
+
+        frame.render_widget(
+            Paragraph::new("Up/Down: move  Enter: open  Esc: back  /: filter  q: quit"),
+            areas[2],
+        );
+    }
+}
+
+/// The state of the interactive view, kept apart from the terminal so it can be tested
+struct TuiState {
+    diffs: Vec<AnyDiff>,
+    file_names: (String, String),
+    /// The index of the opened category, None while the categories are listed
+    category: Option<usize>,
+    selected: usize,
+    filter: String,
+    editing_filter: bool,
+    quit: bool,
+}
+
+impl TuiState {
+    fn new(diffs: Vec<AnyDiff>, file_names: (String, String)) -> TuiState {
+        TuiState {
+            diffs,
+            file_names,
+            category: None,
+            selected: 0,
+            filter: String::new(),
+            editing_filter: false,
+            quit: false,
+        }
+    }
+
+    /// Updates the state based on the key the user pressed
+    fn handle_key(&mut self, key: KeyCode) {
+        if self.editing_filter {
+            match key {
+                KeyCode::Char(c) => self.filter.push(c),
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Enter | KeyCode::Esc => self.editing_filter = false,
+                _ => {}
+            }
+            self.selected = 0;
+            return;
+        }
+
+        match key {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Char('/') => self.editing_filter = true,
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                let last = self.visible_lines().len().saturating_sub(1);
+                self.selected = (self.selected + 1).min(last);
+            }
+            KeyCode::Enter if self.category.is_none() => {
+                self.category = Some(self.selected);
+                self.selected = 0;
+            }
+            KeyCode::Esc | KeyCode::Backspace if self.category.is_some() => {
+                self.selected = self.category.take().unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+
+    /// The title of the list currently shown
+    fn title(&self) -> &'static str {
+        match self.category {
+            Some(category) => CATEGORIES[category].1,
+            None => "Categories",
+        }
+    }
+
+    /// The lines of the list currently shown: the categories with the number of matching differences,
+    /// or the matching differences of the opened category
+    fn visible_lines(&self) -> Vec<String> {
+        match self.category {
+            Some(category) => self
+                .matching_diffs(CATEGORIES[category].0)
+                .map(|diff| format_diff(diff, &self.file_names))
+                .collect(),
+            None => CATEGORIES
+                .iter()
+                .map(|(kind, title)| format!("{} ({})", title, self.matching_diffs(*kind).count()))
+                .collect(),
+        }
+    }
+
+    /// The differences of the given kind with keys containing the filter
+    fn matching_diffs(&self, kind: DiffKind) -> impl Iterator<Item = &AnyDiff> {
+        self.diffs
+            .iter()
+            .filter(move |diff| diff.is_kind(kind) && diff.key().contains(self.filter.as_str()))
+    }
+}
+
+/// Formats a difference to fit a single line of the list
+fn format_diff(diff: &AnyDiff, file_names: &(String, String)) -> String {
+    let line = match diff {
+        AnyDiff::Key(kd) => format!("{}: only in {}", kd.key, kd.has),
+        AnyDiff::Type(td) => format!("{}: {} -> {}", td.key, td.type1, td.type2),
+        AnyDiff::Value(vd) => format!("{}: {} -> {}", vd.key, vd.value1, vd.value2),
+        AnyDiff::Array(ad) => format!(
+            "{}: {} {}",
+            ad.key,
+            ad.value,
+            describe_array_diff(&ad.descriptor, (&file_names.0, &file_names.1))
+        ),
+    };
+    line.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use libdtf::core::diff_types::{KeyDiff, ValueDiff};
+
+    use super::*;
+
+    #[test]
+    fn test_categories_show_counts() {
+        let state = get_state();
+
+        assert_eq!(
+            state.visible_lines(),
+            vec![
+                "Key Differences (1)",
+                "Type Differences (0)",
+                "Value Differences (2)",
+                "Array Differences (0)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_open_category_and_filter() {
+        let mut state = get_state();
+
+        state.handle_key(KeyCode::Down);
+        state.handle_key(KeyCode::Down);
+        state.handle_key(KeyCode::Enter);
+        assert_eq!(state.title(), "Value Differences");
+        assert_eq!(state.visible_lines().len(), 2);
+
+        state.handle_key(KeyCode::Char('/'));
+        "price"
+            .chars()
+            .for_each(|c| state.handle_key(KeyCode::Char(c)));
+        state.handle_key(KeyCode::Enter);
+        assert_eq!(state.visible_lines(), vec!["item.price: 10 -> 12"]);
+
+        state.handle_key(KeyCode::Esc);
+        assert_eq!(state.title(), "Categories");
+        assert_eq!(state.selected, 2);
+
+        state.handle_key(KeyCode::Char('q'));
+        assert!(state.quit);
+    }
+
+    fn get_state() -> TuiState {
+        TuiState::new(
+            vec![
+                AnyDiff::Key(KeyDiff {
+                    key: "item.discount".to_owned(),
+                    has: "a.json".to_owned(),
+                    misses: "b.json".to_owned(),
+                }),
+                AnyDiff::Value(ValueDiff {
+                    key: "item.name".to_owned(),
+                    value1: "Pen".to_owned(),
+                    value2: "Pencil".to_owned(),
+                }),
+                AnyDiff::Value(ValueDiff {
+                    key: "item.price".to_owned(),
+                    value1: "10".to_owned(),
+                    value2: "12".to_owned(),
+                }),
+            ],
+            ("a.json".to_owned(), "b.json".to_owned()),
+        )
+    }
+}
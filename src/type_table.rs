@@ -1,10 +1,11 @@
+use colored::{ColoredString, Colorize};
 use libdtf::core::diff_types::TypeDiff;
 use term_table::{
     row::Row,
     table_cell::{Alignment, TableCell},
 };
 
-use crate::dtfterminal_types::{TableContext, TermTable, WorkingContext};
+use crate::dtfterminal_types::{DiffKind, TableContext, TermTable, TypeColors, WorkingContext};
 
 /// Table to display type differences in the terminal
 pub struct TypeTable<'a> {
@@ -25,11 +26,18 @@ impl<'a> TermTable<TypeDiff> for TypeTable<'a> {
         let (file_name_a_str, file_name_b_str) = self.context.working_context().get_file_names();
         let file_name_a = file_name_a_str.to_owned();
         let file_name_b = file_name_b_str.to_owned();
+        let title = self
+            .context
+            .working_context()
+            .config
+            .theme
+            .color_title("Type Differences");
         self.context
-            .add_row(Row::new(vec![TableCell::builder("Type Differences")
+            .add_row(Row::new(vec![TableCell::builder(title)
             .col_span(3)
             .alignment(Alignment::Center)
             ]));
+        self.context.add_explanation_row(DiffKind::Type);
         self.context.add_row(Row::new(vec![
             TableCell::new("Key"),
             TableCell::new(file_name_a),
@@ -38,13 +46,17 @@ impl<'a> TermTable<TypeDiff> for TypeTable<'a> {
     }
 
     fn add_rows(&mut self, data: &[TypeDiff]) {
-        for td in data {
+        let colors = self.context.working_context().config.theme.types.clone();
+        for td in data.iter().take(self.context.row_limit()) {
             self.context.add_row(Row::new(vec![
-                TableCell::new(&td.key),
-                TableCell::new(&td.type1),
-                TableCell::new(&td.type2),
+                TableCell::new(self.context.key_text(&td.key, || {
+                    format!("types differ: {} vs {}", td.type1, td.type2)
+                })),
+                TableCell::new(colorize_type(&td.type1, &colors)),
+                TableCell::new(colorize_type(&td.type2, &colors)),
             ]));
         }
+        self.context.add_truncated_row(data.len());
     }
 }
 
@@ -57,3 +69,76 @@ impl<'a> TypeTable<'a> {
         table
     }
 }
+
+/// Colors the name of a type by its kind, so the differing types are easy to tell apart
+fn colorize_type(name: &str, colors: &TypeColors) -> ColoredString {
+    match name {
+        "string" => name.color(colors.string),
+        "number" => name.color(colors.number),
+        "boolean" | "bool" => name.color(colors.boolean),
+        "array" => name.color(colors.array),
+        "object" => name.color(colors.object),
+        "null" => name.color(colors.null),
+        _ => name.normal(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use colored::Color;
+
+    use crate::dtfterminal_types::ConfigBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_explanation() {
+        let data = vec![TypeDiff {
+            key: "age".to_owned(),
+            type1: "number".to_owned(),
+            type2: "string".to_owned(),
+        }];
+
+        let explained = TypeTable::new(&data, &get_working_context(true)).render();
+        let plain = TypeTable::new(&data, &get_working_context(false)).render();
+
+        assert!(explained.contains(DiffKind::Type.explanation()));
+        assert!(explained.contains("types differ: number vs string"));
+        assert!(!plain.contains(DiffKind::Type.explanation()));
+        assert!(!plain.contains("types differ"));
+    }
+
+    #[test]
+    fn test_colorize_type() {
+        let colors = TypeColors::default();
+        assert_eq!(
+            colorize_type("string", &colors),
+            "string".color(Color::Green)
+        );
+        assert_eq!(
+            colorize_type("number", &colors),
+            "number".color(Color::Blue)
+        );
+        assert_eq!(
+            colorize_type("null", &colors),
+            "null".color(Color::BrightBlack)
+        );
+        assert_eq!(colorize_type("unknown", &colors), "unknown".normal());
+    }
+
+    fn get_working_context(explain: bool) -> WorkingContext {
+        let working_file_a = libdtf::core::diff_types::WorkingFile::new("file_a.json".to_string());
+        let working_file_b = libdtf::core::diff_types::WorkingFile::new("file_b.json".to_string());
+        let lib_working_context = libdtf::core::diff_types::WorkingContext::new(
+            working_file_a,
+            working_file_b,
+            libdtf::core::diff_types::Config {
+                array_same_order: false,
+            },
+        );
+        WorkingContext::new(
+            lib_working_context,
+            ConfigBuilder::new().explain(explain).build(),
+        )
+    }
+}
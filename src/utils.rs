@@ -1,9 +1,23 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    io::Write,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
 
-use libdtf::core::diff_types::{ArrayDiff, ArrayDiffDesc, WorkingFile};
+use indexmap::IndexMap;
+use libdtf::core::diff_types::{ArrayDiff, ArrayDiffDesc, TypeDiff, ValueDiff, WorkingFile};
 use serde_yaml::Value;
 
-use crate::dtfterminal_types::{Config, LibConfig, LibWorkingContext, WorkingContext};
+use terminal_size::{terminal_size, Width};
+
+use crate::dtfterminal_types::{
+    iter_diffs, Config, DataFormat, DiffCollection, DiffKind, DtfError, LibConfig,
+    LibWorkingContext, Severity, ValueType, ValueTypes, WorkingContext, DEFAULT_TABLE_WIDTH,
+    MAX_COLUMN_WIDTH, MIN_COLUMN_WIDTH, TABLE_BORDER_WIDTH,
+};
+use crate::{postprocessor::display_key, preprocessor::ESCAPED_DOT};
 
 /// Unicode representation of a checkmark to render in the terminal
 pub const CHECKMARK: &str = "\u{2713}";
@@ -11,9 +25,21 @@ pub const CHECKMARK: &str = "\u{2713}";
 /// Unicode representation of a cross to render in the terminal
 pub const MULTIPLY: &str = "\u{00D7}";
 
-/// Group array diffs by key
-pub fn group_by_key(data: &[ArrayDiff]) -> HashMap<&str, Vec<&ArrayDiff>> {
-    let mut map = HashMap::new();
+/// Describes an array difference descriptor with the names of the files, e.g. `only in a.json` for `AHas`
+pub fn describe_array_diff(descriptor: &ArrayDiffDesc, file_names: (&str, &str)) -> String {
+    let (file_a, file_b) = file_names;
+    match descriptor {
+        ArrayDiffDesc::AHas => format!("only in {}", file_a),
+        ArrayDiffDesc::BHas => format!("only in {}", file_b),
+        ArrayDiffDesc::AMisses => format!("missing from {}", file_a),
+        ArrayDiffDesc::BMisses => format!("missing from {}", file_b),
+    }
+}
+
+/// Group array diffs by key.
+/// Groups keep the order in which their keys first appear in the data.
+pub fn group_by_key(data: &[ArrayDiff]) -> IndexMap<&str, Vec<&ArrayDiff>> {
+    let mut map = IndexMap::new();
 
     for ad in data {
         let key = ad.key.as_str();
@@ -35,23 +61,611 @@ pub fn get_display_values_by_column(
     values: &[&ArrayDiff],
     diff_desc: ArrayDiffDesc,
 ) -> Vec<String> {
-    let file_names = context.get_file_names();
     values
         .iter()
         .filter(|ad| ad.descriptor == diff_desc)
-        .map(|ad| prettify_data(file_names, ad.value.as_str()))
+        .map(|ad| format_data(context, ad.value.as_str()))
         .collect()
 }
 
+/// Get values to display in the columns of file A and file B.
+/// A value belongs to file A's column if file A has it or file B misses it and vice versa.
+pub fn get_display_values_by_file(
+    context: &WorkingContext,
+    values: &[&ArrayDiff],
+) -> (Vec<String>, Vec<String>) {
+    let values_a = merge_descriptions(
+        get_display_values_by_column(context, values, ArrayDiffDesc::AHas),
+        get_display_values_by_column(context, values, ArrayDiffDesc::BMisses),
+    );
+    let values_b = merge_descriptions(
+        get_display_values_by_column(context, values, ArrayDiffDesc::BHas),
+        get_display_values_by_column(context, values, ArrayDiffDesc::AMisses),
+    );
+
+    (values_a, values_b)
+}
+
+/// Keeps at most `max_array_diffs` of the differences of a single array, if the user set it.
+/// Returns the differences kept, and how many were left out.
+pub fn cap_array_diffs<'a>(
+    config: &Config,
+    values: Vec<&'a ArrayDiff>,
+) -> (Vec<&'a ArrayDiff>, usize) {
+    let max = config.max_array_diffs.unwrap_or(usize::MAX);
+    let hidden = values.len().saturating_sub(max);
+    (values.into_iter().take(max).collect(), hidden)
+}
+
+/// Adds how many differences of an array were left out below its key, if any were
+pub fn with_hidden_count(key: String, hidden: usize) -> String {
+    if hidden == 0 {
+        key
+    } else {
+        format!("{}\n\u{2026} and {} more", key, hidden)
+    }
+}
+
+/// Merges the values one file has with the values the other file misses, as both describe the same items.
+/// Every item described both ways is kept once, while items repeated in the array are kept as many times as they are repeated.
+fn merge_descriptions(has: Vec<String>, misses: Vec<String>) -> Vec<String> {
+    let mut unmatched = has.clone();
+    let mut values = has;
+    for value in misses {
+        match unmatched.iter().position(|has_value| has_value == &value) {
+            Some(position) => {
+                unmatched.swap_remove(position);
+            }
+            None => values.push(value),
+        }
+    }
+    values
+}
+
+/// Checks if the collection has any differences of the given kinds
+pub fn has_diffs_of_kinds(kinds: &[DiffKind], diffs: &DiffCollection) -> bool {
+    iter_diffs(diffs).any(|diff| kinds.iter().any(|kind| diff.is_kind(*kind)))
+}
+
+/// Runs a phase of the app and prints how long it took to stderr, if the user asked for verbose output.
+/// With the tracing feature, the phase is traced as a span as well.
+pub fn time_phase<T>(config: &Config, phase: &str, run: impl FnOnce() -> T) -> T {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::trace_span!("phase", phase).entered();
+    time_phase_to(&mut std::io::stderr(), config.verbose, phase, run)
+}
+
+/// Runs the check on a worker thread and returns an error if it doesn't finish within the timeout.
+/// The check can't be cancelled, so the thread is left to finish in the background. Without a timeout, the check runs on the current thread.
+/// A panic of the check is passed on, as if the check ran on the current thread.
+pub fn run_with_timeout<T: Send + 'static>(
+    timeout: Option<Duration>,
+    check: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, DtfError> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return Ok(check()),
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let worker = thread::spawn(move || {
+        // The receiver is gone if the check timed out, so the result is not needed anymore
+        let _ = sender.send(check());
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => Ok(result),
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(DtfError::DiffError(format!(
+            "The check did not finish within the timeout of {:?}",
+            timeout
+        ))),
+        Err(mpsc::RecvTimeoutError::Disconnected) => match worker.join() {
+            Err(panic) => std::panic::resume_unwind(panic),
+            Ok(()) => Err(DtfError::DiffError(
+                "The check stopped unexpectedly".to_string(),
+            )),
+        },
+    }
+}
+
+/// Prints the number of differences found per kind to stderr, if the user asked for verbose output
+pub fn print_diff_counts(config: &Config, diffs: &DiffCollection) {
+    if config.verbose {
+        write_diff_counts(&mut std::io::stderr(), diffs);
+    }
+}
+
+/// Prints the single line summary of the differences to stderr, if `print_summary` is set
+pub fn print_summary(config: &Config, diffs: &DiffCollection) {
+    if config.print_summary {
+        eprintln!("{}", format_summary(diffs));
+    }
+}
+
+/// Formats the number of differences per kind in a single line, that is easy to find in logs.
+/// Kinds, that weren't checked count as zero.
+fn format_summary(diffs: &DiffCollection) -> String {
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = diffs;
+    format!(
+        "datadiff: {} key, {} type, {} value, {} array",
+        key_diffs.as_ref().map_or(0, Vec::len),
+        type_diffs.as_ref().map_or(0, Vec::len),
+        value_diffs.as_ref().map_or(0, Vec::len),
+        array_diffs.as_ref().map_or(0, Vec::len)
+    )
+}
+
+/// Formats the number of differences per kind, one kind per line like `key: 3`.
+/// Kinds, that weren't checked count as zero.
+pub fn format_counts(diffs: &DiffCollection) -> String {
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = diffs;
+    format!(
+        "key: {}\ntype: {}\nvalue: {}\narray: {}\n",
+        key_diffs.as_ref().map_or(0, Vec::len),
+        type_diffs.as_ref().map_or(0, Vec::len),
+        value_diffs.as_ref().map_or(0, Vec::len),
+        array_diffs.as_ref().map_or(0, Vec::len)
+    )
+}
+
+/// Runs a phase of the app and writes how long it took, if `verbose` is set
+fn time_phase_to<T>(
+    writer: &mut impl Write,
+    verbose: bool,
+    phase: &str,
+    run: impl FnOnce() -> T,
+) -> T {
+    if !verbose {
+        return run();
+    }
+
+    let start = Instant::now();
+    let result = run();
+    let _ = writeln!(writer, "{} took {:.2?}", phase, start.elapsed());
+    result
+}
+
+/// Writes the number of differences found per kind. Kinds, that weren't checked are skipped.
+fn write_diff_counts(writer: &mut impl Write, diffs: &DiffCollection) {
+    let (key_diffs, type_diffs, value_diffs, array_diffs) = diffs;
+    let counts = [
+        ("Key", key_diffs.as_ref().map(Vec::len)),
+        ("Type", type_diffs.as_ref().map(Vec::len)),
+        ("Value", value_diffs.as_ref().map(Vec::len)),
+        ("Array", array_diffs.as_ref().map(Vec::len)),
+    ];
+    for (kind, count) in counts {
+        if let Some(count) = count {
+            let _ = writeln!(writer, "{} differences found: {}", kind, count);
+        }
+    }
+}
+
+/// Splits the key of a difference into the keys and indexes leading to the value.
+/// JSON Pointers are split by `/`, other keys by `.` and the brackets of indexes.
+pub fn split_key(key: &str, json_pointer: bool) -> Vec<String> {
+    if json_pointer {
+        return key
+            .split('/')
+            .skip(1)
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .collect();
+    }
+
+    key.split('.')
+        .flat_map(split_index_segments)
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Shortens keys with more than three segments to the first segment and the last two, e.g. `a.b.c.d.e.name` becomes `a…e.name`
+pub fn abbreviate_key(key: &str, json_pointer: bool) -> String {
+    let (prefix, separator) = if json_pointer { ("/", '/') } else { ("", '.') };
+    let segments = key
+        .strip_prefix(prefix)
+        .unwrap_or(key)
+        .split(separator)
+        .collect::<Vec<&str>>();
+    match segments.as_slice() {
+        [first, _, .., parent, field] => {
+            format!(
+                "{}{}\u{2026}{}{}{}",
+                prefix, first, parent, separator, field
+            )
+        }
+        _ => key.to_owned(),
+    }
+}
+
+/// Splits the indexes from a key segment, e.g. `items[0][1]` becomes `items`, `0` and `1`
+pub fn split_index_segments(segment: &str) -> Vec<&str> {
+    let (name, mut indexes) = match segment.find('[') {
+        Some(start) if segment.ends_with(']') => segment.split_at(start),
+        _ => return vec![segment],
+    };
+
+    let mut segments = vec![];
+    if !name.is_empty() {
+        segments.push(name);
+    }
+    while let Some((index, rest)) = indexes
+        .strip_prefix('[')
+        .and_then(|indexes| indexes.split_once(']'))
+    {
+        segments.push(index);
+        indexes = rest;
+    }
+    segments
+}
+
+/// Escapes a key to be used as a segment of a JSON Pointer
+pub fn escape_json_pointer_segment(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+/// Finds up to `count` keys with the same value in both files, next to the keys of the Value differences.
+/// The result holds the keys and values for every Value difference in the same order.
+/// Every object is only listed once, for its first difference. Nested objects and arrays are left out.
+pub fn find_unchanged_siblings(
+    data1: &serde_json::Value,
+    data2: &serde_json::Value,
+    value_diffs: &[ValueDiff],
+    count: usize,
+    json_pointer: bool,
+) -> Vec<Vec<(String, String)>> {
+    let mut listed_parents = vec![];
+    value_diffs
+        .iter()
+        .map(|vd| {
+            let mut segments = split_key(&vd.key, json_pointer);
+            let Some(last_segment) = segments.pop() else {
+                return vec![];
+            };
+            if listed_parents.contains(&segments) {
+                return vec![];
+            }
+
+            let (
+                Some(serde_json::Value::Object(parent1)),
+                Some(serde_json::Value::Object(parent2)),
+            ) = (find_value(data1, &segments), find_value(data2, &segments))
+            else {
+                return vec![];
+            };
+            let last_key = if json_pointer {
+                escape_json_pointer_segment(&last_segment)
+            } else {
+                last_segment.clone()
+            };
+            let Some(prefix) = vd.key.strip_suffix(&last_key) else {
+                return vec![];
+            };
+            listed_parents.push(segments);
+
+            parent1
+                .iter()
+                .filter(|(key, value)| {
+                    **key != last_segment
+                        && !value.is_object()
+                        && !value.is_array()
+                        && parent2.get(*key) == Some(value)
+                })
+                .take(count)
+                .map(|(key, value)| {
+                    let key = if json_pointer {
+                        escape_json_pointer_segment(key)
+                    } else {
+                        key.to_owned()
+                    };
+                    let value = match value {
+                        serde_json::Value::String(value) => value.to_owned(),
+                        value => value.to_string(),
+                    };
+                    (format!("{}{}", prefix, key), value)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Finds the keys with the same value in both files, in the order of the first file.
+/// Objects are walked into, so every unchanged value is listed under its own key. Arrays are only listed as a whole.
+pub fn find_equal_values(
+    data1: &serde_json::Value,
+    data2: &serde_json::Value,
+    json_pointer: bool,
+) -> Vec<(String, String)> {
+    let mut equal_values = vec![];
+    collect_equal_values(data1, data2, "", json_pointer, &mut equal_values);
+    equal_values
+}
+
+/// Collects the keys with the same value in both values, below the path
+fn collect_equal_values(
+    value1: &serde_json::Value,
+    value2: &serde_json::Value,
+    path: &str,
+    json_pointer: bool,
+    equal_values: &mut Vec<(String, String)>,
+) {
+    match (value1, value2) {
+        (serde_json::Value::Object(map1), serde_json::Value::Object(map2)) => {
+            for (key, nested1) in map1 {
+                let Some(nested2) = map2.get(key) else {
+                    continue;
+                };
+                let nested_path = if json_pointer {
+                    format!("{}/{}", path, escape_json_pointer_segment(key))
+                } else if path.is_empty() || key.starts_with('[') {
+                    format!("{}{}", path, key)
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                collect_equal_values(nested1, nested2, &nested_path, json_pointer, equal_values);
+            }
+        }
+        _ if value1 == value2 => {
+            let value = match value1 {
+                serde_json::Value::String(value) => value.to_owned(),
+                value => value.to_string(),
+            };
+            equal_values.push((path.to_owned(), value));
+        }
+        _ => {}
+    }
+}
+
+/// Finds the types of the values of the Value differences in both files, by the keys of the differences as they are shown.
+/// The differences have to be the ones found by the check, before they are processed, and the data has to be the checked one,
+/// so the keys can be followed segment by segment, with the dots in the names of keys escaped.
+/// Differences, whose values are not found in both files, are left out.
+pub fn find_value_types(
+    data1: &serde_json::Value,
+    data2: &serde_json::Value,
+    value_diffs: &[ValueDiff],
+    config: &Config,
+) -> ValueTypes {
+    value_diffs
+        .iter()
+        .filter_map(|vd| {
+            let segments = split_key(&vd.key, false);
+            let types = (
+                ValueType::of(find_value(data1, &segments)?),
+                ValueType::of(find_value(data2, &segments)?),
+            );
+            Some((display_key(&vd.key, config), types))
+        })
+        .collect::<HashMap<_, _>>()
+}
+
+/// Finds the values of the Type differences in both files, so they can be shown as rows of the Value table.
+/// Like in `find_value_types`, the differences have to be the ones found by the check and the data the checked one.
+/// The rows keep the keys of the differences. Differences, whose values are not found in both files, are left out.
+pub fn find_type_diff_values(
+    data1: &serde_json::Value,
+    data2: &serde_json::Value,
+    type_diffs: &[TypeDiff],
+) -> Vec<ValueDiff> {
+    let text = |value: &serde_json::Value| match value {
+        serde_json::Value::String(value) => value.to_owned(),
+        value => value.to_string(),
+    };
+    type_diffs
+        .iter()
+        .filter_map(|td| {
+            let segments = split_key(&td.key, false);
+            Some(ValueDiff {
+                key: td.key.clone(),
+                value1: text(find_value(data1, &segments)?),
+                value2: text(find_value(data2, &segments)?),
+            })
+        })
+        .collect()
+}
+
+/// Removes the Array differences of arrays, that are shorter than the minimum size in both files.
+/// A file without the array counts as having a shorter one. Differences of arrays, that are found in neither file, are kept.
+pub fn remove_small_array_diffs(
+    array_diffs: Vec<ArrayDiff>,
+    data: (&serde_json::Value, &serde_json::Value),
+    min_size: usize,
+) -> Vec<ArrayDiff> {
+    let size = |data: &serde_json::Value, key: &str| {
+        find_value_at_key(data, key, ".")
+            .and_then(serde_json::Value::as_array)
+            .map(Vec::len)
+    };
+    array_diffs
+        .into_iter()
+        .filter(
+            |diff| match (size(data.0, &diff.key), size(data.1, &diff.key)) {
+                (None, None) => true,
+                (size1, size2) => size1.unwrap_or(0) >= min_size || size2.unwrap_or(0) >= min_size,
+            },
+        )
+        .collect()
+}
+
+/// Removes the Value differences, where the value in either file is of an ignored type.
+/// The keys of the differences are followed segment by segment, like in `find_value_types`.
+/// Differences, whose values are found in neither file, are kept.
+pub fn remove_ignored_value_types(
+    value_diffs: Vec<ValueDiff>,
+    data: (&serde_json::Value, &serde_json::Value),
+    ignored: &[ValueType],
+) -> Vec<ValueDiff> {
+    let is_ignored = |data: &serde_json::Value, key: &str| {
+        find_value(data, &split_key(key, false))
+            .is_some_and(|value| ignored.contains(&ValueType::of(value)))
+    };
+    value_diffs
+        .into_iter()
+        .filter(|diff| !is_ignored(data.0, &diff.key) && !is_ignored(data.1, &diff.key))
+        .collect()
+}
+
+/// Finds the value at the key of a difference, in which the keys of nested objects are joined by the separator.
+/// Indexes of arrays and the keys of arrays matched by `--array-key` follow in brackets.
+/// Keys can contain the separator themselves, so if the key up to the next separator doesn't lead to the value,
+/// every key of the object starting the rest of the key is tried.
+pub fn find_value_at_key<'a>(
+    data: &'a serde_json::Value,
+    key: &str,
+    separator: &str,
+) -> Option<&'a serde_json::Value> {
+    if key.is_empty() {
+        return Some(data);
+    }
+
+    match data {
+        serde_json::Value::Object(map) => {
+            let follow = |name: &str, nested: &'a serde_json::Value| {
+                let rest = strip_key_separator(key.strip_prefix(name)?, separator)?;
+                find_value_at_key(nested, rest, separator)
+            };
+            let end = key.find(separator).unwrap_or(key.len());
+            let name = &key[..key[..end].find('[').filter(|&i| i > 0).unwrap_or(end)];
+            map.get(name)
+                .and_then(|nested| follow(name, nested))
+                .or_else(|| {
+                    map.iter().find_map(|(name, nested)| {
+                        follow(name, nested).or_else(|| {
+                            name.contains(ESCAPED_DOT)
+                                .then(|| follow(&name.replace(ESCAPED_DOT, "."), nested))
+                                .flatten()
+                        })
+                    })
+                })
+        }
+        serde_json::Value::Array(values) => {
+            let (index, rest) = key.strip_prefix('[')?.split_once(']')?;
+            let nested = values.get(index.parse::<usize>().ok()?)?;
+            find_value_at_key(nested, strip_key_separator(rest, separator)?, separator)
+        }
+        _ => None,
+    }
+}
+
+/// Removes the separator from the start of the rest of a key. The rest is returned as it is if it's empty or starts with an index.
+/// Returns None if the rest starts with anything else, so the key before it was only a part of a longer key.
+fn strip_key_separator<'k>(rest: &'k str, separator: &str) -> Option<&'k str> {
+    if rest.is_empty() || rest.starts_with('[') {
+        Some(rest)
+    } else {
+        rest.strip_prefix(separator)
+    }
+}
+
+/// Finds the value at the end of the path of keys and indexes.
+/// Keys of arrays matched by `--array-key` are found without their brackets as well.
+fn find_value<'a>(
+    data: &'a serde_json::Value,
+    segments: &[String],
+) -> Option<&'a serde_json::Value> {
+    segments
+        .iter()
+        .try_fold(data, |value, segment| match value {
+            serde_json::Value::Object(map) => map
+                .get(segment)
+                .or_else(|| map.get(&format!("[{}]", segment))),
+            serde_json::Value::Array(values) => values.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
 /// Creates a working context object based on user configuration
-pub fn create_working_context(config: &Config) -> WorkingContext {
+pub fn create_working_context(config: &Config) -> Result<WorkingContext, DtfError> {
+    if !(config.check_for_key_diffs
+        || config.check_for_type_diffs
+        || config.check_for_value_diffs
+        || config.check_for_array_diffs)
+    {
+        return Err(DtfError::DiffError("no diff categories enabled".to_owned()));
+    }
+
     let file_a = WorkingFile::new(config.file_a.as_ref().unwrap().clone());
     let file_b = WorkingFile::new(config.file_b.as_ref().unwrap().clone());
 
     let lib_working_context =
         LibWorkingContext::new(file_a, file_b, LibConfig::new(config.array_same_order));
 
-    WorkingContext::new(lib_working_context, config.clone())
+    let mut config = config.clone();
+    if let Some(min_severity) = config.min_severity {
+        let is_severe = |kind| Severity::of(kind, &config.severity_overrides) >= min_severity;
+        config.render_key_diffs &= is_severe(DiffKind::Key);
+        config.render_type_diffs &= is_severe(DiffKind::Type);
+        config.render_value_diffs &= is_severe(DiffKind::Value);
+        config.render_array_diffs &= is_severe(DiffKind::Array);
+    }
+
+    Ok(WorkingContext::new(lib_working_context, config))
+}
+
+/// Gets the width of a column of the terminal tables, sharing the width of the tables between their three columns.
+/// The tables are as wide as `--max-width`, or the terminal if it's not given. If the output is not a terminal, they are 80 characters wide.
+pub fn get_column_width(config: &Config) -> usize {
+    let table_width = config
+        .max_width
+        .or_else(|| terminal_size().map(|(Width(width), _)| usize::from(width)))
+        .unwrap_or(DEFAULT_TABLE_WIDTH);
+    (table_width.saturating_sub(TABLE_BORDER_WIDTH) / 3).clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH)
+}
+
+/// Formats data for display based on user configuration
+pub fn format_data(context: &WorkingContext, data: &str) -> String {
+    if context.config.compact_values {
+        return compact_data(data, get_column_width(&context.config));
+    }
+
+    prettify_data(context.get_file_names(), data)
+}
+
+/// Groups the digits of the integer part of a number by thousands with the separator, e.g. `1000000.5` becomes `1,000,000.5`.
+/// The data is only the text of the number, so the caller has to make sure it's a number in the data.
+/// Returns None if the data is not written as a number, or it's written with an exponent.
+pub fn group_thousands(data: &str, separator: &str) -> Option<String> {
+    let number = data.trim();
+    if !serde_json::from_str::<serde_json::Value>(number)
+        .ok()?
+        .is_number()
+        || number.contains(['e', 'E'])
+    {
+        return None;
+    }
+
+    let (sign, unsigned) = match number.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", number),
+    };
+    let (integer, fraction) = unsigned.split_at(unsigned.find('.').unwrap_or(unsigned.len()));
+    let groups = integer
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|group| String::from_utf8_lossy(group).into_owned())
+        .collect::<Vec<String>>();
+    Some(format!("{}{}{}", sign, groups.join(separator), fraction))
+}
+
+/// Formats objects and arrays as single line JSON.
+/// The result is truncated with an ellipsis if it's longer than `max_width` characters.
+pub fn compact_data(data: &str, max_width: usize) -> String {
+    let compact = match serde_yaml::from_str::<Value>(data) {
+        Ok(value @ (Value::Mapping(_) | Value::Sequence(_))) => {
+            serde_json::to_string(&value).unwrap_or(data.to_owned())
+        }
+        _ => data.to_owned(),
+    };
+
+    if compact.chars().count() <= max_width {
+        return compact;
+    }
+
+    let mut truncated = compact
+        .chars()
+        .take(max_width.saturating_sub(1))
+        .collect::<String>();
+    truncated.push('\u{2026}');
+    truncated
 }
 
 /// Formats data based on file type
@@ -65,6 +679,32 @@ pub fn prettify_data(file_names: (&str, &str), data: &str) -> String {
     prettify_json_str(data)
 }
 
+/// Describes the change between two numbers, as a percentage of the first one, e.g. `+12.5%`.
+/// If the first number is zero, the change is described by the difference of the numbers instead, e.g. `-3`.
+/// The values are only the text of the numbers, so the caller has to make sure they are numbers in the data.
+/// Returns None if either of the values is not written as a number.
+pub fn describe_numeric_change(value1: &str, value2: &str) -> Option<String> {
+    let number1 = serde_json::from_str::<serde_json::Value>(value1)
+        .ok()?
+        .as_f64()?;
+    let number2 = serde_json::from_str::<serde_json::Value>(value2)
+        .ok()?
+        .as_f64()?;
+    let (change, unit) = if number1 == 0.0 {
+        (number2, "")
+    } else {
+        ((number2 - number1) / number1.abs() * 100.0, "%")
+    };
+    let sign = if change > 0.0 { "+" } else { "" };
+    let change = format!("{:.2}", change);
+    Some(format!(
+        "{}{}{}",
+        sign,
+        change.trim_end_matches('0').trim_end_matches('.'),
+        unit
+    ))
+}
+
 /// Formats JSON strings
 pub fn prettify_json_str(json_str: &str) -> String {
     match serde_json::from_str::<Value>(json_str) {
@@ -81,13 +721,119 @@ pub fn prettify_yaml_str(yaml_str: &str) -> String {
     }
 }
 
-/// Checks if a file is a YAML file
+/// Checks if a file is a YAML file. Gzip compressed YAML files count as well.
 pub fn is_yaml_file(path: &str) -> bool {
+    if is_url(path) {
+        return false;
+    }
+    let path = path.strip_suffix(".gz").unwrap_or(path);
     path.ends_with(".yaml") || path.ends_with(".yml")
 }
 
+/// Checks if a file is a JSON file. Gzip compressed JSON files and URLs count as well.
+pub fn is_json_file(path: &str) -> bool {
+    if is_url(path) {
+        return true;
+    }
+    let path = path.strip_suffix(".gz").unwrap_or(path);
+    path.ends_with(".json")
+}
+
+/// Checks if a file is an XML file. Gzip compressed XML files count as well.
+pub fn is_xml_file(path: &str) -> bool {
+    let path = path.strip_suffix(".gz").unwrap_or(path);
+    path.ends_with(".xml")
+}
+
+/// Checks if a file is a CSV file. Gzip compressed CSV files count as well.
+pub fn is_csv_file(path: &str) -> bool {
+    let path = path.strip_suffix(".gz").unwrap_or(path);
+    path.ends_with(".csv")
+}
+
+/// Checks if a file is a JSON5 or JSONC file, which can have comments. Gzip compressed files count as well.
+pub fn is_json5_file(path: &str) -> bool {
+    let path = path.strip_suffix(".gz").unwrap_or(path);
+    path.ends_with(".json5") || path.ends_with(".jsonc")
+}
+
+/// Checks if a file is a MessagePack file. Gzip compressed files count as well.
+pub fn is_msgpack_file(path: &str) -> bool {
+    let path = path.strip_suffix(".gz").unwrap_or(path);
+    path.ends_with(".msgpack") || path.ends_with(".mp")
+}
+
+/// Gets the name to show for one of the objects in a split file, e.g. `snapshots[0].json` for `snapshots.json`
+pub fn get_split_file_name(path: &str, index: usize) -> String {
+    let name_start = path.rfind(['/', '\\']).map_or(0, |separator| separator + 1);
+    let insert_at = path[name_start..]
+        .find('.')
+        .map_or(path.len(), |dot| name_start + dot);
+    format!("{}[{}]{}", &path[..insert_at], index, &path[insert_at..])
+}
+
+/// Gets the name to show for a part of a file checked with `--self-compare`, e.g. `config.json#/staging`
+pub fn get_self_compare_name(path: &str, pointer: &str) -> String {
+    format!("{}#{}", path, pointer)
+}
+
+/// Gets the name to show for a file at a git revision, e.g. `main:config.json`
+pub fn get_git_revision_name(path: &str, revision: &str) -> String {
+    format!("{}:{}", revision, path)
+}
+
+/// Gets the path of an output file. An empty path means the option was given without a name, so the default name is used.
+/// Relative paths are placed in the output directory, if there is one
+pub fn get_output_path(output_dir: Option<&str>, path: &str, default_name: &str) -> String {
+    let name = if path.is_empty() { default_name } else { path };
+    match output_dir {
+        Some(output_dir) => std::path::Path::new(output_dir)
+            .join(name)
+            .to_string_lossy()
+            .into_owned(),
+        None => name.to_owned(),
+    }
+}
+
+/// Gets the format of a file from its extension. Files with unknown extensions, or without one, are JSON files
+pub fn get_data_format(path: &str) -> DataFormat {
+    if is_yaml_file(path) {
+        DataFormat::Yaml
+    } else if is_json_lines_file(path) {
+        DataFormat::Jsonl
+    } else if is_xml_file(path) {
+        DataFormat::Xml
+    } else if is_csv_file(path) {
+        DataFormat::Csv
+    } else if is_json5_file(path) {
+        DataFormat::Json5
+    } else if is_msgpack_file(path) {
+        DataFormat::MessagePack
+    } else {
+        DataFormat::Json
+    }
+}
+
+/// Checks if a file is a JSON Lines file, holding a JSON value in every line. Gzip compressed files count as well.
+pub fn is_json_lines_file(path: &str) -> bool {
+    let path = path.strip_suffix(".gz").unwrap_or(path);
+    path.ends_with(".ndjson") || path.ends_with(".jsonl")
+}
+
+/// Checks if a path is an HTTP(S) URL. The data behind URLs is always read as JSON
+pub fn is_url(path: &str) -> bool {
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+/// Checks if a file is gzip compressed
+pub fn is_gzip_file(path: &str) -> bool {
+    path.ends_with(".gz")
+}
+
 #[cfg(test)]
 mod tests {
+    use libdtf::core::diff_types::KeyDiff;
+
     use crate::dtfterminal_types::ConfigBuilder;
 
     use super::*;
@@ -127,15 +873,388 @@ mod tests {
         assert_eq!(display_values, vec!["value1", "value2", "value3"]);
     }
 
+    #[test]
+    fn test_get_display_values_by_file() {
+        let context = WorkingContext::new(
+            LibWorkingContext::new(
+                WorkingFile::new("file_a.txt".to_owned()),
+                WorkingFile::new("file_b.txt".to_owned()),
+                LibConfig::new(false),
+            ),
+            ConfigBuilder::new().build(),
+        );
+
+        let diffs = [
+            (ArrayDiffDesc::AHas, "value1"),
+            (ArrayDiffDesc::BMisses, "value1"),
+            (ArrayDiffDesc::BMisses, "value2"),
+            (ArrayDiffDesc::BHas, "value3"),
+            (ArrayDiffDesc::AMisses, "value4"),
+        ]
+        .into_iter()
+        .map(|(descriptor, value)| ArrayDiff {
+            descriptor,
+            key: "key".to_owned(),
+            value: value.to_owned(),
+        })
+        .collect::<Vec<ArrayDiff>>();
+        let values = diffs.iter().collect::<Vec<&ArrayDiff>>();
+
+        let (values_a, values_b) = get_display_values_by_file(&context, &values);
+
+        assert_eq!(values_a, vec!["value1", "value2"]);
+        assert_eq!(values_b, vec!["value3", "value4"]);
+    }
+
+    #[test]
+    fn test_merge_descriptions_keeps_repeated_values() {
+        let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+        let values = merge_descriptions(strings(&["1", "1", "2"]), strings(&["1", "3"]));
+
+        assert_eq!(values, strings(&["1", "1", "2", "3"]));
+    }
+
+    #[test]
+    fn test_format_data_compact_values() {
+        let context = WorkingContext::new(
+            LibWorkingContext::new(
+                WorkingFile::new("file_a.json".to_owned()),
+                WorkingFile::new("file_b.json".to_owned()),
+                LibConfig::new(false),
+            ),
+            ConfigBuilder::new()
+                .compact_values(true)
+                .max_width(Some(250))
+                .build(),
+        );
+        let data = serde_json::to_string_pretty(&serde_json::json!({
+            "notes": { "description": "Call between 9am and 5pm", "tags": ["work", "mobile"] }
+        }))
+        .unwrap();
+
+        let formatted = format_data(&context, &data);
+
+        assert_eq!(
+            formatted,
+            r#"{"notes":{"description":"Call between 9am and 5pm","tags":["work","mobile"]}}"#
+        );
+        assert!(!prettify_data(context.get_file_names(), &data)
+            .lines()
+            .eq(formatted.lines()));
+    }
+
+    #[test]
+    fn test_compact_data_truncates() {
+        assert_eq!(compact_data("[1, 2, 3, 4]", 6), "[1,2,\u{2026}");
+        assert_eq!(compact_data("[1, 2]", 6), "[1,2]");
+        assert_eq!(compact_data("plain text", 80), "plain text");
+    }
+
+    #[test]
+    fn test_get_column_width() {
+        let column_width =
+            |max_width| get_column_width(&ConfigBuilder::new().max_width(Some(max_width)).build());
+
+        assert_eq!(column_width(130), 40);
+        assert_eq!(column_width(40), 10);
+        assert_eq!(column_width(20), MIN_COLUMN_WIDTH);
+        assert_eq!(column_width(1000), MAX_COLUMN_WIDTH);
+    }
+
+    #[test]
+    fn test_has_diffs_of_kinds() {
+        let diffs: DiffCollection = (
+            Some(vec![KeyDiff {
+                key: "employment.employer".to_owned(),
+                has: "a.json".to_owned(),
+                misses: "b.json".to_owned(),
+            }]),
+            Some(vec![]),
+            Some(vec![]),
+            None,
+        );
+
+        assert!(!has_diffs_of_kinds(&[DiffKind::Value], &diffs));
+        assert!(!has_diffs_of_kinds(
+            &[DiffKind::Type, DiffKind::Array],
+            &diffs
+        ));
+        assert!(!has_diffs_of_kinds(&[], &diffs));
+        assert!(has_diffs_of_kinds(
+            &[DiffKind::Value, DiffKind::Key],
+            &diffs
+        ));
+        assert!(has_diffs_of_kinds(&[DiffKind::All], &diffs));
+    }
+
+    #[test]
+    fn test_time_phase_verbose() {
+        let mut output = vec![];
+
+        let result = time_phase_to(&mut output, true, "Reading files", || 42);
+
+        assert_eq!(result, 42);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Reading files took "));
+    }
+
+    #[test]
+    fn test_time_phase_not_verbose() {
+        let mut output = vec![];
+
+        let result = time_phase_to(&mut output, false, "Reading files", || 42);
+
+        assert_eq!(result, 42);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_write_diff_counts() {
+        let mut output = vec![];
+        let diffs: DiffCollection = (
+            Some(vec![KeyDiff {
+                key: "a".to_owned(),
+                has: "a.json".to_owned(),
+                misses: "b.json".to_owned(),
+            }]),
+            None,
+            Some(vec![]),
+            None,
+        );
+
+        write_diff_counts(&mut output, &diffs);
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Key differences found: 1\nValue differences found: 0\n"
+        );
+    }
+
+    #[test]
+    fn test_format_counts() {
+        let diffs: DiffCollection = (
+            Some(vec![KeyDiff {
+                key: "a".to_owned(),
+                has: "a.json".to_owned(),
+                misses: "b.json".to_owned(),
+            }]),
+            None,
+            Some(vec![
+                ValueDiff {
+                    key: "b".to_owned(),
+                    value1: "1".to_owned(),
+                    value2: "2".to_owned(),
+                },
+                ValueDiff {
+                    key: "c".to_owned(),
+                    value1: "true".to_owned(),
+                    value2: "false".to_owned(),
+                },
+            ]),
+            Some(vec![]),
+        );
+
+        assert_eq!(
+            format_counts(&diffs),
+            "key: 1\ntype: 0\nvalue: 2\narray: 0\n"
+        );
+    }
+
+    #[test]
+    fn test_describe_array_diff() {
+        let file_names = ("a.json", "b.json");
+
+        assert_eq!(
+            describe_array_diff(&ArrayDiffDesc::AHas, file_names),
+            "only in a.json"
+        );
+        assert_eq!(
+            describe_array_diff(&ArrayDiffDesc::BHas, file_names),
+            "only in b.json"
+        );
+        assert_eq!(
+            describe_array_diff(&ArrayDiffDesc::AMisses, file_names),
+            "missing from a.json"
+        );
+        assert_eq!(
+            describe_array_diff(&ArrayDiffDesc::BMisses, file_names),
+            "missing from b.json"
+        );
+    }
+
+    #[test]
+    fn test_format_summary() {
+        let value_diff = ValueDiff {
+            key: "name".to_owned(),
+            value1: "Sarah".to_owned(),
+            value2: "John".to_owned(),
+        };
+        let diffs: DiffCollection = (
+            Some(vec![
+                KeyDiff {
+                    key: "a".to_owned(),
+                    has: "a.json".to_owned(),
+                    misses: "b.json".to_owned(),
+                };
+                3
+            ]),
+            None,
+            Some(vec![value_diff; 5]),
+            Some(vec![ArrayDiff {
+                key: "tags".to_owned(),
+                descriptor: ArrayDiffDesc::AHas,
+                value: "new".to_owned(),
+            }]),
+        );
+
+        assert_eq!(
+            format_summary(&diffs),
+            "datadiff: 3 key, 0 type, 5 value, 1 array"
+        );
+    }
+
+    #[test]
+    fn test_find_value_types() {
+        let data1 =
+            serde_json::json!({ "price": 42, "code": "42", "tags": { "a\u{E000}b": true } });
+        let data2 =
+            serde_json::json!({ "price": 43, "code": "43", "tags": { "a\u{E000}b": false } });
+        let value_diffs = ["price", "code", "tags.a\u{E000}b", "missing"].map(|key| ValueDiff {
+            key: key.to_owned(),
+            value1: String::new(),
+            value2: String::new(),
+        });
+
+        let value_types =
+            find_value_types(&data1, &data2, &value_diffs, &ConfigBuilder::new().build());
+
+        assert_eq!(value_types.len(), 3);
+        assert_eq!(value_types["price"], (ValueType::Number, ValueType::Number));
+        assert_eq!(value_types["code"], (ValueType::String, ValueType::String));
+        assert_eq!(value_types["tags.a.b"], (ValueType::Bool, ValueType::Bool));
+
+        let config = ConfigBuilder::new().json_pointer(true).build();
+        let value_types = find_value_types(&data1, &data2, &value_diffs, &config);
+
+        assert_eq!(value_types["/tags/a.b"], (ValueType::Bool, ValueType::Bool));
+    }
+
+    #[test]
+    fn test_split_key() {
+        assert_eq!(
+            split_key("members[0].email", false),
+            vec!["members", "0", "email"]
+        );
+        assert_eq!(
+            split_key("/paths/~1usr~1bin/a~0b", true),
+            vec!["paths", "/usr/bin", "a~b"]
+        );
+    }
+
+    #[test]
+    fn test_find_value_at_key() {
+        let data = serde_json::json!({
+            "a.b": { "c": [1, { "d": 2 }] },
+            "a": { "x": 3 },
+            "items": { "[id1]": { "name": "Pen" } },
+            "e\u{E000}f": 4
+        });
+
+        assert_eq!(
+            find_value_at_key(&data, "a.b.c[1].d", "."),
+            Some(&serde_json::json!(2))
+        );
+        assert_eq!(
+            find_value_at_key(&data, "a.x", "."),
+            Some(&serde_json::json!(3))
+        );
+        assert_eq!(
+            find_value_at_key(&data, "items.[id1].name", "."),
+            Some(&serde_json::json!("Pen"))
+        );
+        assert_eq!(
+            find_value_at_key(&data, "items[id1]/name", "/"),
+            Some(&serde_json::json!("Pen"))
+        );
+        assert_eq!(
+            find_value_at_key(&data, "e.f", "/"),
+            Some(&serde_json::json!(4))
+        );
+        assert_eq!(find_value_at_key(&data, "a.b.c[2]", "."), None);
+        assert_eq!(find_value_at_key(&data, "a.y", "."), None);
+    }
+
+    #[test]
+    fn test_find_unchanged_siblings() {
+        let data1 = serde_json::json!({ "users": [{ "name": "Sarah", "age": 30, "city": "Paris", "tags": [] }] });
+        let data2 = serde_json::json!({ "users": [{ "name": "John", "age": 30, "city": "Rome", "tags": [] }] });
+        let value_diffs = ["users[0].name", "users[0].city"]
+            .into_iter()
+            .map(|key| ValueDiff {
+                key: key.to_owned(),
+                value1: String::new(),
+                value2: String::new(),
+            })
+            .collect::<Vec<ValueDiff>>();
+
+        let siblings = find_unchanged_siblings(&data1, &data2, &value_diffs, 1, false);
+
+        assert_eq!(
+            siblings,
+            vec![vec![("users[0].age".to_owned(), "30".to_owned())], vec![]]
+        );
+    }
+
+    #[test]
+    fn test_find_equal_values() {
+        let data1 = serde_json::json!({ "name": "Sarah", "address": { "city": "Paris", "zip": 1 }, "tags": ["a"], "age": 30 });
+        let data2 = serde_json::json!({ "name": "John", "address": { "city": "Paris", "zip": 2 }, "tags": ["a"] });
+
+        assert_eq!(
+            find_equal_values(&data1, &data2, false),
+            vec![
+                ("address.city".to_owned(), "Paris".to_owned()),
+                ("tags".to_owned(), "[\"a\"]".to_owned())
+            ]
+        );
+        assert_eq!(
+            find_equal_values(&data1, &data2, true)[0].0,
+            "/address/city"
+        );
+    }
+
+    #[test]
+    fn test_find_unchanged_siblings_json_pointer() {
+        let data1 = serde_json::json!({ "a": { "b/c": 1, "d": "same", "e": true } });
+        let data2 = serde_json::json!({ "a": { "b/c": 2, "d": "same", "e": true } });
+        let value_diffs = vec![ValueDiff {
+            key: "/a/b~1c".to_owned(),
+            value1: "1".to_owned(),
+            value2: "2".to_owned(),
+        }];
+
+        let siblings = find_unchanged_siblings(&data1, &data2, &value_diffs, 5, true);
+
+        assert_eq!(
+            siblings,
+            vec![vec![
+                ("/a/d".to_owned(), "same".to_owned()),
+                ("/a/e".to_owned(), "true".to_owned())
+            ]]
+        );
+    }
+
     #[test]
     fn test_create_working_context() {
         let config = ConfigBuilder::new()
             .file_a(Some("file_a.txt".to_owned()))
             .file_b(Some("file_b.txt".to_owned()))
+            .check_for_value_diffs(true)
             .array_same_order(true)
             .build();
 
-        let working_context = create_working_context(&config);
+        let working_context = create_working_context(&config).unwrap();
 
         let (file_a_in_context, file_b_in_context) = working_context.get_file_names();
         assert_eq!(file_a_in_context, "file_a.txt");
@@ -146,6 +1265,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_create_working_context_without_checks() {
+        let config = ConfigBuilder::new()
+            .file_a(Some("file_a.txt".to_owned()))
+            .file_b(Some("file_b.txt".to_owned()))
+            .build();
+
+        assert!(matches!(
+            create_working_context(&config),
+            Err(DtfError::DiffError(message)) if message == "no diff categories enabled"
+        ));
+    }
+
+    #[test]
+    fn test_get_data_format() {
+        assert_eq!(get_data_format("file.yml.gz"), DataFormat::Yaml);
+        assert_eq!(get_data_format("file.ndjson"), DataFormat::Jsonl);
+        assert_eq!(get_data_format("file.xml"), DataFormat::Xml);
+        assert_eq!(get_data_format("file.csv"), DataFormat::Csv);
+        assert_eq!(get_data_format("file.jsonc"), DataFormat::Json5);
+        assert_eq!(get_data_format("file.mp"), DataFormat::MessagePack);
+        assert_eq!(get_data_format("file.json"), DataFormat::Json);
+        assert_eq!(get_data_format("file"), DataFormat::Json);
+    }
+
+    #[test]
+    fn test_abbreviate_key() {
+        assert_eq!(abbreviate_key("a.b.c.d.e", false), "a\u{2026}d.e");
+        assert_eq!(abbreviate_key("a.b.c", false), "a.b.c");
+        assert_eq!(abbreviate_key("/a/b/c/d", true), "/a\u{2026}c/d");
+        assert_eq!(abbreviate_key("/a/b", true), "/a/b");
+    }
+
+    #[test]
+    fn test_describe_numeric_change() {
+        assert_eq!(
+            describe_numeric_change("100", "125"),
+            Some("+25%".to_owned())
+        );
+        assert_eq!(describe_numeric_change("8", "9"), Some("+12.5%".to_owned()));
+        assert_eq!(describe_numeric_change("-4", "-5"), Some("-25%".to_owned()));
+        assert_eq!(describe_numeric_change("0", "-3"), Some("-3".to_owned()));
+        assert_eq!(describe_numeric_change("10", "twelve"), None);
+    }
+
+    #[test]
+    fn test_group_thousands() {
+        assert_eq!(
+            group_thousands("1000000", ","),
+            Some("1,000,000".to_owned())
+        );
+        assert_eq!(
+            group_thousands("-12345.678", " "),
+            Some("-12 345.678".to_owned())
+        );
+        assert_eq!(group_thousands("999", ","), Some("999".to_owned()));
+        assert_eq!(group_thousands("1e10", ","), None);
+        assert_eq!(group_thousands("1000 apples", ","), None);
+    }
+
     #[test]
     fn test_is_yaml_file() {
         let yaml_file = "file.yaml";
@@ -157,6 +1336,89 @@ mod tests {
         assert_eq!(is_yaml_file(yml_file), true);
         assert_eq!(is_yaml_file(txt_file), false);
         assert_eq!(is_yaml_file(json_file), false);
+        assert!(is_yaml_file("file.yaml.gz"));
+        assert!(!is_yaml_file("file.json.gz"));
+    }
+
+    #[test]
+    fn test_get_output_path() {
+        assert_eq!(get_output_path(None, "", "diff.json"), "diff.json");
+        assert_eq!(
+            get_output_path(None, "saved.json", "diff.json"),
+            "saved.json"
+        );
+        assert_eq!(
+            get_output_path(Some("reports"), "", "diff.html"),
+            "reports/diff.html"
+        );
+        assert_eq!(
+            get_output_path(Some("reports"), "/tmp/saved.json", "diff.json"),
+            "/tmp/saved.json"
+        );
+    }
+
+    #[test]
+    fn test_get_self_compare_name() {
+        assert_eq!(
+            get_self_compare_name("config.json", "/staging"),
+            "config.json#/staging"
+        );
+    }
+
+    #[test]
+    fn test_get_split_file_name() {
+        assert_eq!(
+            get_split_file_name("data/snapshots.json", 0),
+            "data/snapshots[0].json"
+        );
+        assert_eq!(
+            get_split_file_name("./snapshots.yaml.gz", 1),
+            "./snapshots[1].yaml.gz"
+        );
+        assert_eq!(get_split_file_name("snapshots", 1), "snapshots[1]");
+    }
+
+    #[test]
+    fn test_is_xml_file() {
+        assert!(is_xml_file("file.xml"));
+        assert!(is_xml_file("file.xml.gz"));
+        assert!(!is_xml_file("file.json"));
+    }
+
+    #[test]
+    fn test_is_csv_file() {
+        assert!(is_csv_file("file.csv"));
+        assert!(is_csv_file("file.csv.gz"));
+        assert!(!is_csv_file("file.json"));
+    }
+
+    #[test]
+    fn test_is_json5_file() {
+        assert!(is_json5_file("file.json5"));
+        assert!(is_json5_file("file.jsonc.gz"));
+        assert!(!is_json5_file("file.json"));
+    }
+
+    #[test]
+    fn test_is_msgpack_file() {
+        assert!(is_msgpack_file("file.msgpack"));
+        assert!(is_msgpack_file("file.mp.gz"));
+        assert!(!is_msgpack_file("file.json"));
+    }
+
+    #[test]
+    fn test_is_json_file() {
+        assert!(is_json_file("file.json"));
+        assert!(is_json_file("file.json.gz"));
+        assert!(!is_json_file("file.yaml.gz"));
+        assert!(!is_json_file("file.gz"));
+    }
+
+    #[test]
+    fn test_is_json_lines_file() {
+        assert!(is_json_lines_file("file.ndjson"));
+        assert!(is_json_lines_file("file.jsonl.gz"));
+        assert!(!is_json_lines_file("file.json"));
     }
 
     #[test]
@@ -191,4 +1453,32 @@ mod tests {
         assert_eq!(grouped_data.get("key2"), Some(&vec![&data[1], &data[2]]));
         assert_eq!(grouped_data.get("key3"), Some(&vec![&data[3]]));
     }
+
+    #[test]
+    fn test_group_by_key_keeps_source_order() {
+        let data = vec![
+            ArrayDiff {
+                descriptor: ArrayDiffDesc::AHas,
+                key: "zeta".to_owned(),
+                value: "value1".to_owned(),
+            },
+            ArrayDiff {
+                descriptor: ArrayDiffDesc::AHas,
+                key: "alpha".to_owned(),
+                value: "value2".to_owned(),
+            },
+            ArrayDiff {
+                descriptor: ArrayDiffDesc::BHas,
+                key: "zeta".to_owned(),
+                value: "value3".to_owned(),
+            },
+        ];
+
+        let grouped_data = group_by_key(&data);
+
+        assert_eq!(
+            grouped_data.keys().collect::<Vec<_>>(),
+            vec![&"zeta", &"alpha"]
+        );
+    }
 }
@@ -1,15 +1,23 @@
+use colored::Colorize;
 use libdtf::core::diff_types::ValueDiff;
 use term_table::{
     row::Row,
     table_cell::{Alignment, TableCell},
 };
 
-use crate::dtfterminal_types::{TableContext, TermTable, WorkingContext};
-use crate::utils::prettify_data;
+use crate::dtfterminal_types::{
+    DiffKind, TableContext, TermTable, ValueType, ValueTypes, WorkingContext,
+};
+use crate::utils::{describe_numeric_change, format_data, group_thousands};
 
 /// Table to display value differences in the terminal
 pub struct ValueTable<'a> {
     context: TableContext<'a>,
+    unchanged_siblings: Vec<Vec<(String, String)>>,
+    /// The types of the values in both files, shown next to the values if they are known
+    value_types: ValueTypes,
+    /// The keys with the same value in both files, to show after the differences
+    equal_values: Vec<(String, String)>,
 }
 
 impl<'a> TermTable<ValueDiff> for ValueTable<'a> {
@@ -26,10 +34,20 @@ impl<'a> TermTable<ValueDiff> for ValueTable<'a> {
         let (file_name_a_str, file_name_b_str) = self.context.working_context().get_file_names();
         let file_name_a = file_name_a_str.to_owned();
         let file_name_b = file_name_b_str.to_owned();
-        self.context.add_row(Row::new(vec![TableCell::builder("Value Differences")
+        let title = self
+            .context
+            .working_context()
+            .config
+            .theme
+            .color_title("Value Differences");
+        self.context.add_row(Row::new(vec![TableCell::builder(title)
             .col_span(3)
             .alignment(Alignment::Center)
         ]));
+        self.context.add_explanation_row(DiffKind::Value);
+        if self.context.working_context().config.stacked {
+            return;
+        }
         self.context.add_row(Row::new(vec![
             TableCell::new("Key"),
             TableCell::new(file_name_a),
@@ -38,28 +56,442 @@ impl<'a> TermTable<ValueDiff> for ValueTable<'a> {
     }
 
     fn add_rows(&mut self, data: &[ValueDiff]) {
-        for vd in data {
-            self.context.add_row(Row::new(vec![
-                TableCell::new(&vd.key),
-                TableCell::new(prettify_data(
-                    self.context.working_context().get_file_names(),
-                    &vd.value1,
-                )),
-                TableCell::new(prettify_data(
-                    self.context.working_context().get_file_names(),
-                    &vd.value2,
-                )),
-            ]));
+        for (index, vd) in data.iter().enumerate().take(self.context.row_limit()) {
+            if self.context.working_context().config.stacked {
+                self.add_stacked_rows(vd);
+            } else {
+                self.context.add_row(Row::new(vec![
+                    TableCell::new(self.key_text(vd)),
+                    TableCell::new(self.format_value(&vd.value1, self.type_of(vd, 0))),
+                    TableCell::new(self.format_new_value(vd)),
+                ]));
+            }
+            self.add_unchanged_sibling_rows(index);
         }
+        self.add_equal_rows();
+        self.context.add_truncated_row(data.len());
     }
 }
 
 impl<'a> ValueTable<'a> {
-    pub fn new(data: &[ValueDiff], working_context: &'a WorkingContext) -> ValueTable<'a> {
+    /// Creates the table with the unchanged keys to show after each difference.
+    /// `unchanged_siblings` holds the keys and values for every difference in the same order, it can be empty.
+    /// `value_types` holds the types of the values found in the data, which are shown next to them. Values of unknown types are shown alone.
+    /// Rows with values of different types, i.e. Type differences shown with their values, get the change of their type shown below the key.
+    /// `equal_values` holds the keys with the same value in both files, which are shown after the differences.
+    pub fn new(
+        data: &[ValueDiff],
+        unchanged_siblings: Vec<Vec<(String, String)>>,
+        value_types: &ValueTypes,
+        equal_values: &[(String, String)],
+        working_context: &'a WorkingContext,
+    ) -> ValueTable<'a> {
         let mut table = ValueTable {
             context: TableContext::new(working_context),
+            unchanged_siblings,
+            value_types: value_types.clone(),
+            equal_values: equal_values.to_vec(),
         };
         table.create_table(data);
         table
     }
+
+    /// Formats the key of a difference, followed by the change of its type if the values are of different types
+    fn key_text(&self, vd: &ValueDiff) -> String {
+        let types = self.value_types.get(&vd.key);
+        let key_text = self.context.key_text(&vd.key, || match types {
+            Some((type1, type2)) if type1 == type2 => {
+                format!("values differ, both are {}", type1.name())
+            }
+            Some(_) => "types differ".to_owned(),
+            None => "values differ".to_owned(),
+        });
+        match types {
+            Some((type1, type2)) if type1 != type2 => format!(
+                "{}\n{}",
+                key_text,
+                format!("type changed: {} -> {}", type1.name(), type2.name()).yellow()
+            ),
+            _ => key_text,
+        }
+    }
+
+    /// Gets the type of the value of the difference in the first file with `side` 0, or the second one with `side` 1
+    fn type_of(&self, vd: &ValueDiff, side: usize) -> Option<ValueType> {
+        let (type1, type2) = self.value_types.get(&vd.key)?;
+        Some(if side == 0 { *type1 } else { *type2 })
+    }
+
+    /// Formats a value for display, followed by its type if it's known.
+    /// Numbers get their digits grouped, if the user asked for it.
+    fn format_value(&self, value: &str, value_type: Option<ValueType>) -> String {
+        let context = self.context.working_context();
+        let grouped = match (&context.config.thousands_sep, value_type) {
+            (Some(separator), Some(ValueType::Number)) => group_thousands(value, separator),
+            _ => None,
+        };
+        let formatted = grouped.unwrap_or_else(|| format_data(context, value));
+        match value_type {
+            Some(value_type) => format!("{} ({})", formatted, value_type.name()),
+            None => formatted,
+        }
+    }
+
+    /// Formats the value of the second file, followed by the relative change of numbers if the user asked for it
+    fn format_new_value(&self, vd: &ValueDiff) -> String {
+        let value = self.format_value(&vd.value2, self.type_of(vd, 1));
+        let are_numbers = self.type_of(vd, 0) == Some(ValueType::Number)
+            && self.type_of(vd, 1) == Some(ValueType::Number);
+        if !self.context.working_context().config.show_delta || !are_numbers {
+            return value;
+        }
+
+        match describe_numeric_change(&vd.value1, &vd.value2) {
+            Some(change) => format!("{} ({})", value, change),
+            None => value,
+        }
+    }
+
+    /// Adds a difference as a full-width row of its key, followed by a row for the value of each file, labelled with the file name
+    fn add_stacked_rows(&mut self, vd: &ValueDiff) {
+        let (file_name_a, file_name_b) = self.context.working_context().get_file_names();
+        let rows = vec![
+            Row::new(vec![TableCell::builder(self.key_text(vd)).col_span(3)]),
+            Row::new(vec![
+                TableCell::new(file_name_a),
+                TableCell::builder(self.format_value(&vd.value1, self.type_of(vd, 0)))
+                    .col_span(2)
+                    .build(),
+            ]),
+            Row::new(vec![
+                TableCell::new(file_name_b),
+                TableCell::builder(self.format_new_value(vd))
+                    .col_span(2)
+                    .build(),
+            ]),
+        ];
+        rows.into_iter().for_each(|row| self.context.add_row(row));
+    }
+
+    /// Adds the keys with the same value in both files as greyed out rows, with `=` in the column of the second file
+    fn add_equal_rows(&mut self) {
+        let rows = self
+            .equal_values
+            .iter()
+            .map(|(key, value)| {
+                let value = format_data(self.context.working_context(), value);
+                Row::new(vec![
+                    TableCell::new(key.bright_black()),
+                    TableCell::new(value.bright_black()),
+                    TableCell::new("=".bright_black()),
+                ])
+            })
+            .collect::<Vec<Row>>();
+        rows.into_iter().for_each(|row| self.context.add_row(row));
+    }
+
+    /// Adds the unchanged keys of a difference as greyed out rows, with the same value in both columns
+    fn add_unchanged_sibling_rows(&mut self, index: usize) {
+        let Some(siblings) = self.unchanged_siblings.get(index) else {
+            return;
+        };
+
+        let rows = siblings
+            .iter()
+            .map(|(key, value)| {
+                let value = format_data(self.context.working_context(), value);
+                Row::new(vec![
+                    TableCell::new(key.bright_black()),
+                    TableCell::new(value.bright_black()),
+                    TableCell::new(value.bright_black()),
+                ])
+            })
+            .collect::<Vec<Row>>();
+        rows.into_iter().for_each(|row| self.context.add_row(row));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{dtfterminal_types::ConfigBuilder, json_app::JsonApp, utils::find_value_types};
+
+    use super::*;
+
+    #[test]
+    fn test_unchanged_sibling_rows() {
+        let working_context = get_working_context();
+        let data = vec![ValueDiff {
+            key: "user.name".to_owned(),
+            value1: "Sarah".to_owned(),
+            value2: "John".to_owned(),
+        }];
+        let unchanged_siblings = vec![vec![("user.age".to_owned(), "30".to_owned())]];
+
+        let rendered = ValueTable::new(
+            &data,
+            unchanged_siblings,
+            &ValueTypes::new(),
+            &[],
+            &working_context,
+        )
+        .render();
+
+        let lines = rendered.lines().collect::<Vec<&str>>();
+        let position = |text| lines.iter().position(|line| line.contains(text)).unwrap();
+        let (diff_line, sibling_line) = (position("user.name"), position("user.age"));
+        assert!(diff_line < sibling_line);
+        assert_eq!(lines[sibling_line].matches("30").count(), 2);
+    }
+
+    #[test]
+    fn test_values_show_type() {
+        let mut working_context = get_working_context();
+        working_context.config.check_for_value_diffs = true;
+        let (data1, data2) = (
+            json!({ "price": 42, "code": "42" }),
+            json!({ "price": 43, "code": "43" }),
+        );
+        let data = JsonApp::from_data(
+            data1.as_object().unwrap().clone(),
+            data2.as_object().unwrap().clone(),
+            working_context.clone(),
+        )
+        .perform_new_check()
+        .2
+        .unwrap();
+        let value_types = find_value_types(&data1, &data2, &data, &working_context.config);
+
+        let rendered = ValueTable::new(&data, vec![], &value_types, &[], &working_context).render();
+
+        let row = |key| rendered.lines().find(|line| line.contains(key)).unwrap();
+        assert!(row("price").contains("42 (number)"));
+        assert!(row("code").contains("42 (string)"));
+    }
+
+    #[test]
+    fn test_values_of_unknown_types_shown_alone() {
+        let working_context = get_working_context();
+        let data = vec![ValueDiff {
+            key: "price".to_owned(),
+            value1: "42".to_owned(),
+            value2: "43".to_owned(),
+        }];
+
+        let rendered =
+            ValueTable::new(&data, vec![], &ValueTypes::new(), &[], &working_context).render();
+
+        let row = rendered
+            .lines()
+            .find(|line| line.contains("price"))
+            .unwrap();
+        assert!(row.contains("42") && !row.contains('('));
+    }
+
+    #[test]
+    fn test_type_change_shown_below_key() {
+        let config = ConfigBuilder::new()
+            .check_for_type_diffs(true)
+            .check_for_value_diffs(true)
+            .render_type_diffs(true)
+            .render_value_diffs(true)
+            .combine_type_value(true)
+            .build();
+        let mut runner = crate::diff_runner::DiffRunner::from_data(
+            "a.json",
+            json!({ "user": { "age": 30, "name": "Sarah" } })
+                .as_object()
+                .unwrap()
+                .clone(),
+            "b.json",
+            json!({ "user": { "age": "30", "name": "John" } })
+                .as_object()
+                .unwrap()
+                .clone(),
+        )
+        .with_config(config);
+        runner.run().unwrap();
+
+        let rendered = runner.render();
+
+        let lines = rendered.lines().collect::<Vec<&str>>();
+        let age_line = lines
+            .iter()
+            .position(|line| line.contains("user.age"))
+            .unwrap();
+        let row = lines[age_line..=age_line + 1].join("\n");
+        assert!(row.contains("30 (number)") && row.contains("30 (string)"));
+        assert!(row.contains("type changed: number -> string"));
+        assert_eq!(rendered.matches("type changed").count(), 1);
+        assert!(
+            !rendered.contains("Type Differences"),
+            "the Type difference is only shown in the Value table"
+        );
+    }
+
+    #[test]
+    fn test_numeric_change_shown() {
+        let mut working_context = get_working_context();
+        working_context.config.show_delta = true;
+        let data = vec![
+            ValueDiff {
+                key: "visits".to_owned(),
+                value1: "100".to_owned(),
+                value2: "125".to_owned(),
+            },
+            ValueDiff {
+                key: "code".to_owned(),
+                value1: "100".to_owned(),
+                value2: "125".to_owned(),
+            },
+        ];
+        let value_types = ValueTypes::from([
+            ("visits".to_owned(), (ValueType::Number, ValueType::Number)),
+            ("code".to_owned(), (ValueType::String, ValueType::String)),
+        ]);
+
+        let rendered = ValueTable::new(&data, vec![], &value_types, &[], &working_context).render();
+
+        let visits_row = rendered
+            .lines()
+            .find(|line| line.contains("visits"))
+            .unwrap();
+        assert!(visits_row.contains("125 (number) (+25%)"));
+        assert_eq!(
+            rendered.matches('%').count(),
+            1,
+            "strings of numbers have no change"
+        );
+    }
+
+    #[test]
+    fn test_large_numbers_grouped() {
+        let mut working_context = get_working_context();
+        working_context.config.thousands_sep = Some(",".to_owned());
+        let data = vec![
+            ValueDiff {
+                key: "population".to_owned(),
+                value1: "1000000".to_owned(),
+                value2: "1250000".to_owned(),
+            },
+            ValueDiff {
+                key: "zip".to_owned(),
+                value1: "10001".to_owned(),
+                value2: "10002".to_owned(),
+            },
+        ];
+        let value_types = ValueTypes::from([
+            (
+                "population".to_owned(),
+                (ValueType::Number, ValueType::Number),
+            ),
+            ("zip".to_owned(), (ValueType::String, ValueType::String)),
+        ]);
+
+        let rendered = ValueTable::new(&data, vec![], &value_types, &[], &working_context).render();
+
+        let row = rendered
+            .lines()
+            .find(|line| line.contains("population"))
+            .unwrap();
+        assert!(row.contains("1,000,000 (number)"));
+        assert!(row.contains("1,250,000 (number)"));
+        let zip_row = rendered.lines().find(|line| line.contains("zip")).unwrap();
+        assert!(
+            zip_row.contains("10001 (string)"),
+            "strings are left as they are"
+        );
+    }
+
+    #[test]
+    fn test_stacked_rows() {
+        let mut working_context = get_working_context();
+        working_context.config.stacked = true;
+        let data = vec![ValueDiff {
+            key: "price".to_owned(),
+            value1: "42".to_owned(),
+            value2: "43".to_owned(),
+        }];
+        let value_types =
+            ValueTypes::from([("price".to_owned(), (ValueType::Number, ValueType::Number))]);
+
+        let rendered = ValueTable::new(&data, vec![], &value_types, &[], &working_context).render();
+
+        let lines = rendered.lines().collect::<Vec<&str>>();
+        let position = |text| lines.iter().position(|line| line.contains(text)).unwrap();
+        let (key_line, line_a, line_b) = (
+            position("price"),
+            position("file_a.json"),
+            position("file_b.json"),
+        );
+        assert!(key_line < line_a && line_a < line_b);
+        assert!(!lines[key_line].contains("42"));
+        assert!(lines[line_a].contains("42 (number)"));
+        assert!(lines[line_b].contains("43 (number)"));
+        assert!(
+            !lines.iter().any(|line| line.contains("Key")),
+            "the header row is left out"
+        );
+    }
+
+    #[test]
+    fn test_equal_rows() {
+        let working_context = get_working_context();
+        let data = vec![ValueDiff {
+            key: "user.name".to_owned(),
+            value1: "Sarah".to_owned(),
+            value2: "John".to_owned(),
+        }];
+        let equal_values = vec![("user.age".to_owned(), "30".to_owned())];
+
+        let rendered = ValueTable::new(
+            &data,
+            vec![],
+            &ValueTypes::new(),
+            &equal_values,
+            &working_context,
+        )
+        .render();
+
+        let lines = rendered.lines().collect::<Vec<&str>>();
+        assert!(lines.iter().any(|line| line.contains("user.name")));
+        let equal_line = lines.iter().find(|line| line.contains("user.age")).unwrap();
+        assert!(equal_line.contains("30"));
+        assert!(equal_line.contains('='));
+    }
+
+    #[test]
+    fn test_limit_rows() {
+        let mut working_context = get_working_context();
+        working_context.config.limit = Some(2);
+        let data = (1..=5)
+            .map(|i| ValueDiff {
+                key: format!("key{}", i),
+                value1: i.to_string(),
+                value2: (i * 10).to_string(),
+            })
+            .collect::<Vec<ValueDiff>>();
+
+        let rendered =
+            ValueTable::new(&data, vec![], &ValueTypes::new(), &[], &working_context).render();
+
+        assert!(rendered.contains("key1"));
+        assert!(rendered.contains("key2"));
+        assert!(!rendered.contains("key3"));
+        assert!(rendered.contains("\u{2026} and 3 more"));
+    }
+
+    fn get_working_context() -> WorkingContext {
+        let working_file_a = libdtf::core::diff_types::WorkingFile::new("file_a.json".to_string());
+        let working_file_b = libdtf::core::diff_types::WorkingFile::new("file_b.json".to_string());
+        let lib_working_context = libdtf::core::diff_types::WorkingContext::new(
+            working_file_a,
+            working_file_b,
+            libdtf::core::diff_types::Config {
+                array_same_order: false,
+            },
+        );
+        WorkingContext::new(lib_working_context, ConfigBuilder::new().build())
+    }
 }
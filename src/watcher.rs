@@ -0,0 +1,136 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{
+    app::App,
+    dtfterminal_types::{Config, DtfError},
+};
+
+/// How long the files have to stay unchanged before they are checked again, so a save touching a file multiple times triggers a single check
+const DEBOUNCE_TIME: Duration = Duration::from_millis(200);
+
+type Events = Receiver<notify::Result<Event>>;
+
+/// Checks the files, then checks them again every time one of them changes, until the app is stopped with Ctrl-C.
+/// A check failing, e.g. because a file is only half-written, is reported and the files are watched further.
+pub fn watch(path1: String, path2: String, mut config: Config) -> Result<(), DtfError> {
+    let paths = [get_watched_path(&path1)?, get_watched_path(&path2)?];
+    let (_watcher, receiver) = watch_files(&paths)?;
+
+    loop {
+        // Clears the screen and moves the cursor to the top left corner
+        print!("\x1B[2J\x1B[H");
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            App::with_timeout(Some(path1.clone()), Some(path2.clone()), config.clone())
+                .and_then(|app| app.execute())
+        }));
+        match result {
+            // The output files were written by this check, so the next checks can replace them
+            Ok(Ok(())) => config.force = true,
+            Ok(Err(e)) => eprintln!("{}", e),
+            Err(_) => (),
+        }
+        wait_for_change(&receiver, &paths)?;
+    }
+}
+
+/// Gets the absolute path of a file, as the paths of the change events are absolute
+fn get_watched_path(path: &str) -> Result<PathBuf, DtfError> {
+    Path::new(path)
+        .canonicalize()
+        .map_err(|e| DtfError::read_error(path, e))
+}
+
+/// Starts watching the directories of the files.
+/// The directories are watched instead of the files, as many editors save by replacing the file.
+fn watch_files(paths: &[PathBuf]) -> Result<(RecommendedWatcher, Events), DtfError> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+    for path in paths {
+        let directory = path.parent().unwrap_or(Path::new("/"));
+        watcher
+            .watch(directory, RecursiveMode::NonRecursive)
+            .map_err(watch_error)?;
+    }
+    Ok((watcher, receiver))
+}
+
+/// Waits until one of the files changes, then until no more changes follow within the debounce time
+fn wait_for_change(receiver: &Events, paths: &[PathBuf]) -> Result<(), DtfError> {
+    loop {
+        let event = receiver
+            .recv()
+            .map_err(|e| DtfError::DiffError(e.to_string()))?
+            .map_err(watch_error)?;
+        if is_change_of(&event, paths) {
+            break;
+        }
+    }
+
+    loop {
+        match receiver.recv_timeout(DEBOUNCE_TIME) {
+            Ok(_) => continue,
+            Err(RecvTimeoutError::Timeout) => return Ok(()),
+            Err(e) => return Err(DtfError::DiffError(e.to_string())),
+        }
+    }
+}
+
+/// Checks if the event is a change of any of the files. Reading the files doesn't count as a change
+fn is_change_of(event: &Event, paths: &[PathBuf]) -> bool {
+    let kind = event.kind;
+    (kind.is_create() || kind.is_modify() || kind.is_remove())
+        && event.paths.iter().any(|path| paths.contains(path))
+}
+
+/// The error of the files not being watchable, or the watching being interrupted
+fn watch_error(e: notify::Error) -> DtfError {
+    DtfError::DiffError(format!("Could not watch the files: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, thread};
+
+    use super::*;
+
+    #[test]
+    fn test_wait_for_change() {
+        let directory = std::env::temp_dir().join("dtfterminal_watch_test");
+        fs::create_dir_all(&directory).unwrap();
+        let watched_file = directory.join("watched.json");
+        let other_file = directory.join("other.json");
+        fs::write(&watched_file, "{}").unwrap();
+        let paths = [get_watched_path(&watched_file.to_string_lossy()).unwrap()];
+        let (_watcher, receiver) = watch_files(&paths).unwrap();
+
+        let writer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            fs::write(&other_file, "{}").unwrap();
+            fs::write(&watched_file, "{\"changed\": true}").unwrap();
+        });
+
+        wait_for_change(&receiver, &paths).unwrap();
+        writer.join().unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_change_of_other_file_ignored() {
+        let paths = [PathBuf::from("/data/watched.json")];
+        let event = Event::new(notify::EventKind::Modify(notify::event::ModifyKind::Any))
+            .add_path(PathBuf::from("/data/other.json"));
+
+        assert!(!is_change_of(&event, &paths));
+        assert!(is_change_of(
+            &event.add_path(PathBuf::from("/data/watched.json")),
+            &paths
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+use serde_json::{Map, Value};
+
+/// An element, that is still being read, with the data collected from it so far
+struct OpenElement {
+    name: String,
+    children: Map<String, Value>,
+    text: String,
+}
+
+/// Converts an XML document into JSON data, so it can be checked like a JSON file.
+/// * The root element becomes the only key of the map
+/// * Attributes become keys prefixed with `@`, e.g. `@id`
+/// * Child elements become keys named after their tag, and elements repeated under the same parent become an array
+/// * The text of an element is its value, or the `#text` key if it has attributes or child elements as well
+/// * Empty elements without attributes are `null`
+///
+/// Every attribute and text value is a string, as XML has no other types.
+pub fn xml_to_json(content: &str) -> Result<Map<String, Value>, String> {
+    let mut reader = Reader::from_str(content);
+    reader.trim_text(true);
+
+    let mut open_elements: Vec<OpenElement> = vec![];
+    let mut root = Map::new();
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            format!(
+                "invalid XML at position {}: {}",
+                reader.buffer_position(),
+                e
+            )
+        })?;
+        match event {
+            Event::Start(start) => open_elements.push(open_element(&start)?),
+            Event::Empty(start) => {
+                let element = open_element(&start)?;
+                close_element(element, &mut open_elements, &mut root);
+            }
+            Event::End(_) => {
+                // The reader checks, that the end tags match the start tags
+                let element = open_elements.pop().ok_or("unexpected end tag".to_owned())?;
+                close_element(element, &mut open_elements, &mut root);
+            }
+            Event::Text(text) => {
+                if let Some(element) = open_elements.last_mut() {
+                    element
+                        .text
+                        .push_str(&text.unescape().map_err(|e| e.to_string())?);
+                }
+            }
+            Event::CData(cdata) => {
+                if let Some(element) = open_elements.last_mut() {
+                    element
+                        .text
+                        .push_str(&String::from_utf8_lossy(&cdata.into_inner()));
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    if !open_elements.is_empty() {
+        return Err("unexpected end of document".to_owned());
+    }
+    Ok(root)
+}
+
+/// Starts reading an element, storing its attributes as `@` prefixed keys
+fn open_element(start: &BytesStart) -> Result<OpenElement, String> {
+    let mut children = Map::new();
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|e| e.to_string())?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref());
+        let value = attribute.unescape_value().map_err(|e| e.to_string())?;
+        children.insert(format!("@{}", key), Value::String(value.into_owned()));
+    }
+
+    Ok(OpenElement {
+        name: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+        children,
+        text: String::new(),
+    })
+}
+
+/// Finishes reading an element and adds its value to its parent, or to the root if it has no parent
+fn close_element(
+    element: OpenElement,
+    open_elements: &mut [OpenElement],
+    root: &mut Map<String, Value>,
+) {
+    let OpenElement {
+        name,
+        mut children,
+        text,
+    } = element;
+    let value = if children.is_empty() {
+        if text.is_empty() {
+            Value::Null
+        } else {
+            Value::String(text)
+        }
+    } else {
+        if !text.is_empty() {
+            children.insert("#text".to_owned(), Value::String(text));
+        }
+        Value::Object(children)
+    };
+
+    let parent = match open_elements.last_mut() {
+        Some(parent) => &mut parent.children,
+        None => root,
+    };
+    match parent.get_mut(&name) {
+        Some(Value::Array(values)) => values.push(value),
+        Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+        None => {
+            parent.insert(name, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_xml_to_json() {
+        let xml = r#"<?xml version="1.0"?>
+            <order id="12" status="open">
+                <!-- The customer placing the order -->
+                <customer>Sarah &amp; John</customer>
+                <item sku="pen">2</item>
+                <item sku="paper"/>
+                <note><![CDATA[<fragile>]]></note>
+                <gift/>
+            </order>"#;
+
+        assert_eq!(
+            Value::Object(xml_to_json(xml).unwrap()),
+            json!({
+                "order": {
+                    "@id": "12",
+                    "@status": "open",
+                    "customer": "Sarah & John",
+                    "item": [
+                        { "@sku": "pen", "#text": "2" },
+                        { "@sku": "paper" }
+                    ],
+                    "note": "<fragile>",
+                    "gift": null
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_xml_to_json_with_invalid_xml() {
+        assert!(xml_to_json("<order><item></order>").is_err());
+        assert!(xml_to_json("<order>").is_err());
+    }
+}
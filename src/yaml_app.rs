@@ -1,6 +1,8 @@
 use crate::{
-    dtfterminal_types::{DiffCollection, WorkingContext},
-    file_handler::FileHandler,
+    dtfterminal_types::{merge_diffs, DiffCollection, MovedElement, WorkingContext},
+    file_handler::{read_with_retry, FileHandler},
+    preprocessor::{prepare_typed_yaml_data, prepare_yaml_data, unescape_key_dots, TypedData},
+    utils::{remove_ignored_value_types, remove_small_array_diffs, time_phase},
 };
 
 use libdtf::{
@@ -14,6 +16,12 @@ pub struct YamlApp {
     data1: Mapping,
     data2: Mapping,
     context: WorkingContext,
+    /// Array differences found while preparing the data, which the check itself can't find
+    prepared_array_diffs: Vec<ArrayDiff>,
+    /// The array items moved to another position, found while preparing the data
+    moved_elements: Vec<MovedElement>,
+    /// The data converted to JSON and prepared with the original types of the values, if the checked data has other ones
+    typed_data: Option<TypedData>,
 }
 
 impl YamlApp {
@@ -21,56 +29,149 @@ impl YamlApp {
     /// 1. Parses the command line arguments
     /// 2. Checks for differences and stores them
     pub fn new(path1: String, path2: String, context: WorkingContext) -> YamlApp {
-        let data1 = FileHandler::read_yaml_file(&path1).expect("Could not read YAML file");
-        let data2 = FileHandler::read_yaml_file(&path2).expect("Could not read YAML file");
+        let (data1, data2) = time_phase(&context.config, "Reading files", || {
+            (
+                read_with_retry(context.config.read_retry, || {
+                    FileHandler::read_yaml_file(&path1)
+                })
+                .expect("Could not read YAML file"),
+                read_with_retry(context.config.read_retry, || {
+                    FileHandler::read_yaml_file(&path2)
+                })
+                .expect("Could not read YAML file"),
+            )
+        });
+        YamlApp::from_data(data1, data2, context)
+    }
+
+    /// Creates a new App instance from data already in memory
+    pub fn from_data(mut data1: Mapping, mut data2: Mapping, context: WorkingContext) -> YamlApp {
+        let typed_data = prepare_typed_yaml_data(&data1, &data2, &context.config)
+            .expect("Could not prepare YAML data");
+        let (prepared_array_diffs, moved_elements) =
+            prepare_yaml_data(&mut data1, &mut data2, &context.config)
+                .expect("Could not prepare YAML data");
         YamlApp {
             data1,
             data2,
             context,
+            prepared_array_diffs,
+            moved_elements,
+            typed_data,
+        }
+    }
+
+    /// Returns the data of both files converted to JSON, as it was checked, with the dots in keys restored
+    pub fn data_as_json(&self) -> (serde_json::Value, serde_json::Value) {
+        let (mut data1, mut data2) = (
+            serde_json::to_value(&self.data1).unwrap_or_default(),
+            serde_json::to_value(&self.data2).unwrap_or_default(),
+        );
+        for data in [&mut data1, &mut data2] {
+            if let serde_json::Value::Object(map) = data {
+                unescape_key_dots(map);
+            }
         }
+        (data1, data2)
+    }
+
+    /// Returns the data of both files converted to JSON, as it was checked, but with the original types of the values.
+    /// The dots in keys are escaped like in the keys of the differences, before they are processed.
+    pub fn typed_data_as_json(&self) -> (serde_json::Value, serde_json::Value) {
+        match &self.typed_data {
+            Some((data1, data2)) => (
+                serde_json::Value::Object(data1.clone()),
+                serde_json::Value::Object(data2.clone()),
+            ),
+            None => (
+                serde_json::to_value(&self.data1).unwrap_or_default(),
+                serde_json::to_value(&self.data2).unwrap_or_default(),
+            ),
+        }
+    }
+
+    /// Returns the array items moved to another position, if the user asked for them
+    pub fn moved_elements(&self) -> &[MovedElement] {
+        &self.moved_elements
     }
 
     /// Checks for differences between the two files
     pub fn perform_new_check(&self) -> DiffCollection {
-        self.check_for_diffs(&self.data1, &self.data2)
+        let mut diffs = (None, None, None, None);
+        self.perform_streaming_check(|checked| merge_diffs(&mut diffs, checked));
+        diffs
+    }
+
+    /// Checks for differences between the two files, handing over the differences of every kind as soon as the kind is checked
+    pub fn perform_streaming_check(&self, on_checked: impl FnMut(DiffCollection)) {
+        self.check_for_diffs(&self.data1, &self.data2, on_checked)
     }
 
     /// Checks for differences between the two files
-    fn check_for_diffs(&self, data1: &Mapping, data2: &Mapping) -> DiffCollection {
-        let key_diff = if self.context.config.check_for_key_diffs {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn check_for_diffs(
+        &self,
+        data1: &Mapping,
+        data2: &Mapping,
+        mut on_checked: impl FnMut(DiffCollection),
+    ) {
+        if self.context.config.check_for_key_diffs {
             let mut checking_data: CheckingData<KeyDiff> =
                 CheckingData::new("", data1, data2, &self.context.lib_working_context);
-            checking_data.check();
-            Some(checking_data.diffs()).cloned()
-        } else {
-            None
-        };
-        let type_diff = if self.context.config.check_for_type_diffs {
+            time_phase(&self.context.config, "Checking for key differences", || {
+                checking_data.check()
+            });
+            on_checked((Some(checking_data.diffs().clone()), None, None, None));
+        }
+
+        if self.context.config.check_for_type_diffs {
             let mut checking_data: CheckingData<TypeDiff> =
                 CheckingData::new("", data1, data2, &self.context.lib_working_context);
-            checking_data.check();
-            Some(checking_data.diffs()).cloned()
-        } else {
-            None
-        };
-        let value_diff = if self.context.config.check_for_value_diffs {
+            time_phase(
+                &self.context.config,
+                "Checking for type differences",
+                || checking_data.check(),
+            );
+            on_checked((None, Some(checking_data.diffs().clone()), None, None));
+        }
+
+        if self.context.config.check_for_value_diffs {
             let mut checking_data: CheckingData<ValueDiff> =
                 CheckingData::new("", data1, data2, &self.context.lib_working_context);
-            checking_data.check();
-            Some(checking_data.diffs()).cloned()
-        } else {
-            None
-        };
-        let array_diff = if self.context.config.check_for_array_diffs {
+            time_phase(
+                &self.context.config,
+                "Checking for value differences",
+                || checking_data.check(),
+            );
+            let mut value_diffs = checking_data.diffs().clone();
+            if !self.context.config.ignore_types.is_empty() {
+                let (data1, data2) = self.typed_data_as_json();
+                value_diffs = remove_ignored_value_types(
+                    value_diffs,
+                    (&data1, &data2),
+                    &self.context.config.ignore_types,
+                );
+            }
+            on_checked((None, None, Some(value_diffs), None));
+        }
+
+        if self.context.config.check_for_array_diffs {
             let mut checking_data: CheckingData<ArrayDiff> =
                 CheckingData::new("", data1, data2, &self.context.lib_working_context);
-            checking_data.check();
-            Some(checking_data.diffs()).cloned()
-        } else {
-            None
-        };
-
-        (key_diff, type_diff, value_diff, array_diff)
+            time_phase(
+                &self.context.config,
+                "Checking for array differences",
+                || checking_data.check(),
+            );
+            let mut array_diffs = checking_data.diffs().clone();
+            array_diffs.extend(self.prepared_array_diffs.iter().cloned());
+            if let Some(min_array_size) = self.context.config.min_array_size {
+                let (data1, data2) = self.typed_data_as_json();
+                array_diffs =
+                    remove_small_array_diffs(array_diffs, (&data1, &data2), min_array_size);
+            }
+            on_checked((None, None, None, Some(array_diffs)));
+        }
     }
 }
 